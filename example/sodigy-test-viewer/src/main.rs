@@ -7,13 +7,22 @@ use ragit_fs::{
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use shev::{
+    ActionOutput,
+    CanvasState,
+    Chart,
+    ClickAction,
     Color,
     Entries,
     Entry,
+    EntryAction,
     EntryFlag,
     EntryState,
     Filter,
     Graphic,
+    JsonTree,
+    Markdown,
+    SuiteRun,
+    Syntax,
     TextBox,
     Transition,
 };
@@ -91,7 +100,23 @@ fn main() {
                             cond: |e| e.flag == EntryFlag::Red,
                         },
                     ],
-                    render_canvas: render_single_file_test,
+                    actions: vec![
+                        // The test files are somewhere in the sodigy repository.
+                        EntryAction {
+                            name: String::from("git log"),
+                            key: None,
+                            command: [
+                                "git", "-C", &sodigy_at, "log", "--oneline", "-20", "--", ":(glob)**/{name}*",
+                            ].iter().map(|arg| arg.to_string()).collect(),
+                            output: ActionOutput::Entries,
+                        },
+                    ],
+                    render_stateful_canvas: Some(render_single_file_test),
+                    // so that the history (T key) and the flaky tests (F key) are in run order
+                    run: Some(SuiteRun {
+                        suite: String::from("sodigy"),
+                        order: run_order(&file),
+                    }),
                     ..Entries::default()
                 },
             );
@@ -104,12 +129,24 @@ fn main() {
             id: String::from("index"),
             title: Some(String::from("Tests")),
             entries: tests,
+            entry_state_count: 2,
             transition: None,
             render_canvas: render_test_result,
             ..Entries::default()
         },
     );
-    shev::run(shev::Config::default(), entries_map, String::from("index"))
+    let mut config = shev::Config {
+        pins_path: join(&test_results_at, ".index/pins.json").ok(),
+        annotations_path: join(&test_results_at, ".index/annotations.json").ok(),
+        ..shev::Config::default()
+    };
+
+    // an optional keymap file
+    if let Some(keymap_at) = args.get(3) {
+        config.keymap = shev::Keymap::from_file(keymap_at).unwrap();
+    }
+
+    shev::run(config, entries_map, String::from("index"))
 }
 
 #[derive(Deserialize, Serialize)]
@@ -148,101 +185,118 @@ pub struct SingleFileTest {
     __test_result_meta: Option<HashMap<String, String>>,
 }
 
-fn render_test_result(e: &Entry, _: EntryState) -> Result<Vec<Graphic>, String> {
+fn render_test_result(e: &Entry, es: EntryState) -> Result<Vec<Graphic>, String> {
     let test_result: TestResult = serde_json::from_str(&e.content.as_ref().unwrap()).map_err(|e| format!("{e:?}"))?;
+
+    if es == EntryState(1) {
+        let names = test_result.crate_test.iter().map(|t| t.name.as_str()).collect::<Vec<_>>();
+        let elapsed = |f: fn(&CrateTest) -> &CrateTestResult| test_result.crate_test.iter().map(|t| f(t).elapsed as f32).collect::<Vec<_>>();
+
+        return Ok(Chart::bar()
+            .with_title("crate-test elapsed time")
+            .with_axis_labels(None, Some("ms"))
+            .with_categories(&names)
+            .add_series("debug", &elapsed(|t| &t.debug))
+            .add_series("release", &elapsed(|t| &t.release))
+            .add_series("doc", &elapsed(|t| &t.doc))
+            .render());
+    }
+
     let crate_test_success = test_result.crate_test.iter().filter(|t| t.debug.error.is_none() && t.release.error.is_none() && t.doc.error.is_none()).count();
     let crate_test_fail = test_result.crate_test.len() - crate_test_success;
     let single_file_test_success = test_result.single_file_test.iter().filter(|t| t.error.is_none()).count();
     let single_file_test_fail = test_result.single_file_test.len() - single_file_test_success;
-    Ok(TextBox::new(
-        &format!("
+    let summary = format!("
 crate-test: {{ success: {crate_test_success}, fail: {crate_test_fail} }}
 single-file-test: {{ success: {single_file_test_success}, fail: {single_file_test_fail} }}
 meta: {}",
-            serde_json::to_string_pretty(&test_result.meta).unwrap(),
-        ),
+        serde_json::to_string_pretty(&test_result.meta).unwrap(),
+    );
+    let mut graphics = TextBox::new(
+        &summary,
         16.0,
         Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 },
         [20.0, 20.0, 800.0, 2000.0],
-    ).render())
+    ).render();
+
+    // Each failing case is a link to its details.
+    let mut curr_y = 20.0 + (summary.lines().count() + 1) as f32 * 17.6;
+
+    for (i, t) in test_result.single_file_test.iter().enumerate() {
+        if t.error.is_none() {
+            continue;
+        }
+
+        graphics.extend(TextBox::new(
+            &format!("failed: {}", t.name),
+            16.0,
+            Color { r: 0.75, g: 0.25, b: 0.25, a: 1.0 },
+            [20.0, curr_y, 800.0, 20.0],
+        ).render());
+        graphics.push(Graphic::Clickable {
+            x: 20.0,
+            y: curr_y - 14.0,
+            w: 800.0,
+            h: 17.6,
+            action: ClickAction::Transit {
+                id: e.name.to_string(),
+                cursor: Some(i),
+            },
+        });
+        curr_y += 17.6;
+    }
+
+    Ok(graphics)
 }
 
-fn render_single_file_test(e: &Entry, es: EntryState) -> Result<Vec<Graphic>, String> {
+fn render_single_file_test(e: &Entry, es: EntryState, cs: &CanvasState) -> Result<Vec<Graphic>, String> {
     let test_result: SingleFileTest = serde_json::from_str(e.content.as_ref().unwrap()).map_err(|e| format!("{e:?}"))?;
     let file_content = test_result.__test_file_blob.clone().unwrap();
 
     let s = match es {
         EntryState(0) => file_content,
         EntryState(1) => format!("# stdout\n\n```\n{}\n```\n\n# stderr\n\n```\n{}\n```", test_result.stdout, test_result.stderr),
-        EntryState(2) => serde_json::to_string_pretty(&test_result.__test_result_meta.clone().unwrap()).unwrap(),
+        EntryState(2) => {
+            let meta = serde_json::to_value(&test_result.__test_result_meta.clone().unwrap()).unwrap();
+            return Ok(JsonTree::from_value(meta, 16.0, [20.0, 20.0, 800.0, 2000.0]).render(cs));
+        },
         _ => unreachable!(),
     };
-    let (s, colors) = apply_ansi_term_color(&s);
-    Ok(TextBox::new(
+
+    if es == EntryState(1) {
+        return Ok(Markdown::new(
+            &s,
+            16.0,
+            Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 },
+            [20.0, 20.0, 800.0, 2000.0],
+        ).render());
+    }
+
+    let mut text_box = TextBox::from_ansi(
         &s,
         16.0,
         Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 },
         [20.0, 20.0, 800.0, 2000.0],
-    ).with_color_map(colors).render())
-}
-
-#[derive(Clone, Copy)]
-enum TermColorParseState {
-    Text,
-    Control,
-}
-
-fn apply_ansi_term_color(s: &str) -> (String, Vec<Color>) {
-    let mut chars: Vec<char> = vec![];
-    let mut colors = vec![];
-    let mut curr_color = Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
-    let mut state = TermColorParseState::Text;
-    let mut digits_buffer = vec![];
-
-    for ch in s.chars() {
-        match state {
-            TermColorParseState::Text => match ch {
-                '\u{1b}' => {
-                    digits_buffer = vec![];
-                    state = TermColorParseState::Control;
-                },
-                _ => {
-                    chars.push(ch);
-                    colors.push(curr_color);
-                },
-            },
-            TermColorParseState::Control => match ch {
-                '0'..='9' => {
-                    digits_buffer.push(ch);
-                },
-                'm' => {
-                    match digits_buffer.iter().collect::<String>().parse::<u32>() {
-                        Ok(0) => {
-                            curr_color = Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
-                        },
-                        Ok(31) => {
-                            curr_color = Color { r: 0.75, g: 0.25, b: 0.25, a: 1.0 };
-                        },
-                        Ok(32) => {
-                            curr_color = Color { r: 0.25, g: 0.75, b: 0.25, a: 1.0 };
-                        },
-                        Ok(33) => {
-                            curr_color = Color { r: 0.75, g: 0.75, b: 0.25, a: 1.0 };
-                        },
-                        Ok(34) => {
-                            curr_color = Color { r: 0.25, g: 0.2, b: 0.75, a: 1.0 };
-                        },
-                        _ => {},
-                    };
+    );
 
-                    state = TermColorParseState::Text;
-                },
-                _ => {},
-            },
-        }
+    if es == EntryState(0) {
+        // shev doesn't know sodigy, so we give it the keywords.
+        text_box.with_line_numbers(1).with_syntax(&Syntax::custom(&[
+            "let", "fn", "if", "else", "match", "struct", "enum", "use", "as",
+        ]));
     }
 
-    (chars.iter().collect(), colors)
+    Ok(text_box.render())
+}
+
+// The results don't have timestamps, so the runs are ordered by when the result files were written.
+fn run_order(file: &str) -> i64 {
+    std::fs::metadata(file)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
 }
 
 fn load_blobs(blobs_at: &str) -> HashMap<String, String> {
@@ -7,8 +7,50 @@ pub enum Action {
         cursor: Option<usize>,
     },
     TransitToTmpEntries {
-        entries: Entries,
+        entries: Box<Entries>,
         cursor: Option<usize>,
     },
+
+    /// `State` doesn't know the other `Entries`, so the main loop creates the
+    /// history view (see `history::history_entries`).
+    ShowHistory {
+        name: String,
+    },
+
+    /// Like `ShowHistory`, it's handled by the main loop (see `history::flaky_entries`).
+    ShowFlakyEntries,
+
+    /// The command palette lists every `Entries` in the map, so it's opened by the main
+    /// loop (see `palette::palette_items`).
+    OpenPalette,
+
+    /// The navigation history is kept by the main loop (see `navigation::Navigation`).
+    GoBack,
+    GoForward,
+
+    /// The main loop checks that the entry exists in the original `Entries` (see `pin::entry_key`).
+    TogglePin,
+    ShowPinned,
+
+    /// Like `TogglePin`, the main loop checks the entry before opening the editor.
+    EditAnnotation,
+
+    /// If `issue` is not set, it's the issues of the current entry.
+    ShowIssue {
+        issue: Option<String>,
+    },
+
+    /// An index of `State::triage_labels`, or `None` to clear the label.
+    SetTriageLabel {
+        label: Option<usize>,
+    },
+
+    /// If `all` is set, it exports every (non-tmp) `Entries`. Otherwise, it exports the current one.
+    ExportHtml {
+        all: bool,
+    },
+
+    /// It exports the shown canvas (or the dashboard) to an svg file.
+    ExportCanvasSvg,
     Quit,
 }
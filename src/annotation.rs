@@ -0,0 +1,396 @@
+use crate::entry::Entries;
+use crate::history::is_tmp_entries;
+use crate::pin::linked_entries;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+/// A note, issue ids and a triage label that the user attached to an entry.
+#[derive(Clone, Debug, Default)]
+pub struct Annotation {
+    pub note: String,
+    pub issues: Vec<String>,
+
+    /// `TriageLabel::name`
+    pub label: Option<String>,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        self.note.trim().is_empty() && self.issues.is_empty() && self.label.is_none()
+    }
+
+    /// Whether it has a note or issues, not just a triage label.
+    pub fn has_note(&self) -> bool {
+        !self.note.trim().is_empty() || !self.issues.is_empty()
+    }
+
+    /// It's shown in the top bar.
+    pub fn summary(&self) -> String {
+        let note = self.note.lines().next().unwrap_or("").trim();
+        let label = match &self.label {
+            Some(label) => format!("[{label}] "),
+            None => String::new(),
+        };
+
+        match (note.is_empty(), self.issues.is_empty()) {
+            (true, true) => label.trim_end().to_string(),
+            (false, true) => format!("{label}note: {note}"),
+            (true, false) => format!("{label}issues: {}", self.issues.join(", ")),
+            (false, false) => format!("{label}note: {note} ({})", self.issues.join(", ")),
+        }
+    }
+}
+
+/// Annotations are keyed by (scope, `Entry::name`). The scope of an `Entries` is
+/// `SuiteRun::suite` if it's a run of a suite, and `Entries::id` otherwise (see `scope`),
+/// so that the notes survive across the runs of a suite.
+///
+/// If `path` is set, the annotations are saved to the file (json) every time they change.
+pub struct Annotations {
+    path: Option<String>,
+    annotations: Vec<(String, String, Annotation)>,
+}
+
+impl Annotations {
+    pub fn new(path: Option<String>) -> Annotations {
+        Annotations { path, annotations: vec![] }
+    }
+
+    /// Like `Pins::load`, it stops saving if the file is broken.
+    pub fn load(&mut self) -> Result<(), String> {
+        let Some(path) = &self.path else { return Ok(()); };
+
+        let Ok(s) = std::fs::read_to_string(path) else { return Ok(()); };
+
+        match parse_annotations(&s) {
+            Some(annotations) => {
+                self.annotations = annotations;
+                Ok(())
+            },
+            None => {
+                let e = format!("Failed to load annotations from `{path}`. Annotations won't be saved.");
+                self.path = None;
+                Err(e)
+            },
+        }
+    }
+
+    pub fn get(&self, scope: &str, name: &str) -> Option<&Annotation> {
+        self.annotations.iter().find(|(s, n, _)| s == scope && n == name).map(|(_, _, annotation)| annotation)
+    }
+
+    /// If `annotation` is empty, the annotation of the entry is removed.
+    pub fn set(&mut self, scope: &str, name: &str, annotation: Annotation) -> Result<(), String> {
+        match self.annotations.iter().position(|(s, n, _)| s == scope && n == name) {
+            Some(i) if annotation.is_empty() => { self.annotations.remove(i); },
+            Some(i) => { self.annotations[i].2 = annotation; },
+            None if annotation.is_empty() => {},
+            None => { self.annotations.push((scope.to_string(), name.to_string(), annotation)); },
+        }
+
+        self.save()
+    }
+
+    /// An editor of the annotation of `targets` (scope, `Entry::name`). If there're multiple
+    /// targets, it starts with their annotation only if they all have the same one.
+    ///
+    /// Like pins, annotations can only be attached to entries of non-tmp entries. The caller
+    /// has to filter them out (see `State::annotation_targets`).
+    pub fn editor(&self, targets: Vec<(String, String)>) -> Result<AnnotationEditor, String> {
+        let Some((scope, name)) = targets.first() else {
+            return Err(String::from("This entry can't be annotated."));
+        };
+        let annotation = self.get(scope, name);
+        let is_shared = targets.iter().all(|(s, n)| match (self.get(s, n), annotation) {
+            (Some(a1), Some(a2)) => a1.note == a2.note && a1.issues == a2.issues,
+            (None, None) => true,
+            _ => false,
+        });
+
+        Ok(AnnotationEditor::new(targets.clone(), if is_shared { annotation } else { None }))
+    }
+
+    /// issue id -> (scope, name) of the linked entries. It's sorted by issue id.
+    pub fn issues(&self) -> Vec<(String, Vec<(String, String)>)> {
+        let mut issues: HashMap<&str, Vec<(String, String)>> = HashMap::new();
+
+        for (scope, name, annotation) in self.annotations.iter() {
+            for issue in annotation.issues.iter() {
+                issues.entry(issue.as_str()).or_default().push((scope.to_string(), name.to_string()));
+            }
+        }
+
+        let mut issues = issues.into_iter().map(|(issue, links)| (issue.to_string(), links)).collect::<Vec<_>>();
+        issues.sort_by(|(issue1, _), (issue2, _)| issue1.cmp(issue2));
+        issues
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else { return Ok(()); };
+        let annotations = self.annotations.iter().map(
+            |(scope, name, annotation)| json!({
+                "scope": scope,
+                "name": name,
+                "note": annotation.note,
+                "issues": annotation.issues,
+                "label": annotation.label,
+            })
+        ).collect::<Vec<_>>();
+
+        std::fs::write(path, serde_json::to_string_pretty(&annotations).unwrap()).map_err(|e| format!("Failed to save annotations to `{path}`: {e:?}"))
+    }
+}
+
+fn parse_annotations(s: &str) -> Option<Vec<(String, String, Annotation)>> {
+    let annotations: Value = serde_json::from_str(s).ok()?;
+
+    annotations.as_array()?.iter().map(
+        |annotation| Some((
+            annotation["scope"].as_str()?.to_string(),
+            annotation["name"].as_str()?.to_string(),
+            Annotation {
+                note: annotation["note"].as_str().unwrap_or("").to_string(),
+                issues: annotation["issues"].as_array().map(
+                    |issues| issues.iter().filter_map(|issue| issue.as_str()).map(|issue| issue.to_string()).collect()
+                ).unwrap_or_default(),
+                label: annotation["label"].as_str().map(|label| label.to_string()),
+            },
+        ))
+    ).collect()
+}
+
+pub fn scope(entries: &Entries) -> String {
+    match &entries.run {
+        Some(run) => run.suite.to_string(),
+        None => entries.id.to_string(),
+    }
+}
+
+/// It finds the `Entries` of a scope. If the scope is a suite, it's the newest run
+/// that has an entry named `name`.
+pub fn resolve<'a>(entries_map: &'a HashMap<String, Entries>, scope: &str, name: &str) -> Option<&'a Entries> {
+    if let Some(entries) = entries_map.get(scope) && !is_tmp_entries(scope) {
+        return Some(entries);
+    }
+
+    entries_map.values().filter(
+        |entries| entries.run.as_ref().map(|run| run.suite == scope).unwrap_or(false) && entries.iter().any(|e| e.name == name)
+    ).max_by_key(|entries| (entries.run.as_ref().map(|run| run.order), entries.id.to_string()))
+}
+
+/// It splits the issues field of the editor (e.g. "#12, #34").
+pub fn parse_issues(s: &str) -> Vec<String> {
+    let mut issues: Vec<String> = vec![];
+
+    for issue in s.split(|ch: char| ch == ',' || ch.is_whitespace()).filter(|issue| !issue.is_empty()) {
+        if !issues.iter().any(|i| i == issue) {
+            issues.push(issue.to_string());
+        }
+    }
+
+    issues
+}
+
+/// A text-input overlay that edits the annotation of an entry, or of every selected entry
+/// at once (see `State::selection`).
+pub struct AnnotationEditor {
+    /// (scope, `Entry::name`) of the entries to annotate
+    pub targets: Vec<(String, String)>,
+    pub note: String,
+
+    /// comma-separated issue ids
+    pub issues: String,
+
+    /// 0: note, 1: issues
+    pub field: usize,
+}
+
+impl AnnotationEditor {
+    /// `annotation` is the initial value of the fields.
+    pub fn new(targets: Vec<(String, String)>, annotation: Option<&Annotation>) -> AnnotationEditor {
+        AnnotationEditor {
+            targets,
+            note: annotation.map(|a| a.note.to_string()).unwrap_or_default(),
+            issues: annotation.map(|a| a.issues.join(", ")).unwrap_or_default(),
+            field: 0,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self.targets.as_slice() {
+            [(_, name)] => format!("Annotate {name}"),
+            targets => format!("Annotate {} entries", targets.len()),
+        }
+    }
+
+    pub fn curr_field(&mut self) -> &mut String {
+        if self.field == 0 { &mut self.note } else { &mut self.issues }
+    }
+
+    /// The triage label is not editable in the editor, so the caller has to
+    /// keep the label of each entry.
+    pub fn annotation(&self, label: Option<String>) -> Annotation {
+        Annotation {
+            note: self.note.trim_end().to_string(),
+            issues: parse_issues(&self.issues),
+            label,
+        }
+    }
+}
+
+/// It creates a tmp `Entries` of the entries that are linked to any of `issues`.
+pub fn issue_entries(
+    entries_map: &HashMap<String, Entries>,
+    annotations: &Annotations,
+    issues: &[String],
+    original_entries_id: &str,
+) -> Result<Entries, String> {
+    let mut links = vec![];
+
+    for (issue, issue_links) in annotations.issues() {
+        if !issues.contains(&issue) {
+            continue;
+        }
+
+        for (scope, name) in issue_links {
+            if let Some(entries) = resolve(entries_map, &scope, &name) {
+                let link = (entries.id.to_string(), name);
+
+                if !links.contains(&link) {
+                    links.push(link);
+                }
+            }
+        }
+    }
+
+    let title = format!("Issue{} {}", if issues.len() > 1 { "s" } else { "" }, issues.join(", "));
+    linked_entries(entries_map, &links, &title, original_entries_id).ok_or_else(|| format!("There's no entry linked to {}.", issues.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::{Entry, SuiteRun};
+
+    fn note(note: &str, issues: &[&str]) -> Annotation {
+        Annotation {
+            note: note.to_string(),
+            issues: issues.iter().map(|issue| issue.to_string()).collect(),
+            ..Annotation::default()
+        }
+    }
+
+    fn run(id: &str, order: i64, names: &[&str]) -> Entries {
+        Entries {
+            id: id.to_string(),
+            entries: names.iter().map(|name| Entry { name: name.to_string(), ..Entry::default() }).collect(),
+            run: Some(SuiteRun { suite: String::from("suite"), order }),
+            ..Entries::default()
+        }
+    }
+
+    #[test]
+    fn issue_ids() {
+        assert_eq!(parse_issues("#12, #34 #12,,"), vec!["#12", "#34"]);
+        assert!(parse_issues(" , ").is_empty());
+    }
+
+    #[test]
+    fn set_and_remove() {
+        let mut annotations = Annotations::new(None);
+        annotations.set("s", "a", note("flaky", &["#1", "#2"])).unwrap();
+        annotations.set("s", "b", note("", &["#1"])).unwrap();
+        annotations.set("t", "a", note(" ", &[])).unwrap();
+
+        assert_eq!(annotations.get("s", "a").map(|a| a.note.as_str()), Some("flaky"));
+        assert!(annotations.get("t", "a").is_none());
+        assert_eq!(
+            annotations.issues(),
+            vec![
+                (String::from("#1"), vec![(String::from("s"), String::from("a")), (String::from("s"), String::from("b"))]),
+                (String::from("#2"), vec![(String::from("s"), String::from("a"))]),
+            ],
+        );
+
+        annotations.set("s", "a", note("fixed", &[])).unwrap();
+        assert_eq!(annotations.issues().len(), 1);
+
+        // An empty annotation removes the old one.
+        annotations.set("s", "b", Annotation::default()).unwrap();
+        assert!(annotations.get("s", "b").is_none());
+        assert!(annotations.issues().is_empty());
+    }
+
+    #[test]
+    fn parse_annotation_file() {
+        let annotations = parse_annotations(r##"[{"scope": "s", "name": "a", "note": "n", "issues": ["#1", 2]}, {"scope": "s", "name": "b"}]"##).unwrap();
+
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].2.note, "n");
+        assert_eq!(annotations[0].2.issues, vec!["#1"]);
+        assert!(annotations[1].2.is_empty());
+        assert!(parse_annotations(r#"[{"name": "a"}]"#).is_none());
+        assert!(parse_annotations("{}").is_none());
+    }
+
+    #[test]
+    fn scopes() {
+        let entries_map: HashMap<String, Entries> = [
+            run("r1", 1, &["a", "b"]),
+            run("r2", 2, &["a"]),
+            Entries { id: String::from("plain"), ..Entries::default() },
+        ].into_iter().map(|entries| (entries.id.to_string(), entries)).collect();
+
+        assert_eq!(scope(&entries_map["r1"]), "suite");
+        assert_eq!(scope(&entries_map["plain"]), "plain");
+        assert_eq!(resolve(&entries_map, "suite", "a").map(|entries| entries.id.as_str()), Some("r2"));
+        assert_eq!(resolve(&entries_map, "suite", "b").map(|entries| entries.id.as_str()), Some("r1"));
+        assert_eq!(resolve(&entries_map, "plain", "x").map(|entries| entries.id.as_str()), Some("plain"));
+        assert!(resolve(&entries_map, "suite", "x").is_none());
+    }
+
+    #[test]
+    fn summaries() {
+        let labeled = |label: &str, annotation: Annotation| Annotation { label: Some(label.to_string()), ..annotation };
+
+        assert_eq!(note("first\nsecond", &[]).summary(), "note: first");
+        assert_eq!(note("", &["#1", "#2"]).summary(), "issues: #1, #2");
+        assert_eq!(note("n", &["#1"]).summary(), "note: n (#1)");
+        assert_eq!(labeled("known issue", note("n", &[])).summary(), "[known issue] note: n");
+        assert_eq!(labeled("known issue", Annotation::default()).summary(), "[known issue]");
+
+        assert!(note("n", &[]).has_note());
+        assert!(note("", &["#1"]).has_note());
+        assert!(!labeled("known issue", note(" ", &[])).has_note());
+        assert!(!labeled("known issue", Annotation::default()).is_empty());
+    }
+
+    #[test]
+    fn bulk_editor() {
+        let mut annotations = Annotations::new(None);
+        annotations.set("s", "a", note("n", &["#1"])).unwrap();
+        annotations.set("s", "b", note("n", &["#1"])).unwrap();
+        annotations.set("s", "c", note("other", &[])).unwrap();
+
+        let targets = |names: &[&str]| names.iter().map(|name| (String::from("s"), name.to_string())).collect::<Vec<_>>();
+
+        let editor = annotations.editor(targets(&["a", "b"])).unwrap();
+        assert_eq!((editor.title().as_str(), editor.note.as_str(), editor.issues.as_str()), ("Annotate 2 entries", "n", "#1"));
+
+        // They don't share an annotation, so the editor starts empty.
+        let editor = annotations.editor(targets(&["a", "c"])).unwrap();
+        assert_eq!((editor.note.as_str(), editor.issues.as_str()), ("", ""));
+
+        let editor = annotations.editor(targets(&["d"])).unwrap();
+        assert_eq!((editor.title().as_str(), editor.note.as_str()), ("Annotate d", ""));
+        assert!(annotations.editor(vec![]).is_err());
+
+        let mut editor = annotations.editor(targets(&["c"])).unwrap();
+        editor.curr_field().push('\n');
+        editor.field = 1;
+        editor.curr_field().push_str("#2 #3");
+
+        let annotation = editor.annotation(Some(String::from("known issue")));
+        assert_eq!((annotation.note.as_str(), annotation.issues.len(), annotation.label.as_deref()), ("other", 2, Some("known issue")));
+    }
+}
@@ -1,6 +1,7 @@
-use crate::entry::{Entries, Entry, EntryFlag, EntryState};
-use crate::graphic::Graphic;
+use crate::entry::{CanvasState, Entries, Entry, EntryFlag, EntryState};
+use crate::graphic::{Graphic, render_dashboard};
 use crate::state::State;
+use crate::triage::{TriageLabel, label_color};
 use macroquad::color::Color;
 use macroquad::prelude::ImageFormat;
 use macroquad::texture::{Texture2D, load_texture};
@@ -9,6 +10,7 @@ use std::hash::Hash;
 
 // This is for heavy (memory & computation) values.
 // It also assumes that `capacity` isn't that big (less than 100).
+#[allow(clippy::upper_case_acronyms)]
 pub struct LRU<K, V> {
     capacity: usize,
     data: HashMap<K, V>,
@@ -38,6 +40,16 @@ impl<K: Clone + Eq + Hash + PartialEq, V> LRU<K, V> {
         self.data.contains_key(key)
     }
 
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.order.retain(|k| k != key);
+        self.data.remove(key)
+    }
+
+    pub fn clear(&mut self) {
+        self.order.clear();
+        self.data.clear();
+    }
+
     pub fn insert(&mut self, key: K, value: V) {
         self.order.sort_by_key(|k| if k == &key { 1 } else { 0 });
 
@@ -60,6 +72,11 @@ impl<K: Clone + Eq + Hash + PartialEq, V> LRU<K, V> {
 pub struct RenderCache {
     pub canvas: LRU<(String, usize, EntryState), Vec<Graphic>>,
     pub scroll_bar_colors: LRU<String, Vec<Color>>,
+
+    /// It has to be cleared when the user changes a triage label.
+    pub scroll_bar_label_colors: LRU<String, Vec<Option<Color>>>,
+    pub top_bar_extra_message: LRU<(String, usize, EntryState), Option<String>>,
+    pub dashboard: LRU<String, Vec<Graphic>>,
 }
 
 impl RenderCache {
@@ -67,6 +84,9 @@ impl RenderCache {
         RenderCache {
             canvas: LRU::with_capacity(128),
             scroll_bar_colors: LRU::with_capacity(128),
+            scroll_bar_label_colors: LRU::with_capacity(128),
+            top_bar_extra_message: LRU::with_capacity(128),
+            dashboard: LRU::with_capacity(16),
         }
     }
 }
@@ -78,25 +98,43 @@ impl State {
 
             if !self.cache.canvas.contains_key(&canvas_key) {
                 // TODO: render error message
-                let mut canvas = (entries.render_canvas)(&entries[self.cursor], self.entry_state).unwrap();
+                let mut canvas = entries.render_entry_canvas(
+                    &entries[self.cursor],
+                    self.entry_state,
+                    self.canvas_states.get(&canvas_key).unwrap_or(&CanvasState::default()),
+                ).unwrap();
 
                 for graphic in canvas.iter_mut() {
-                    match graphic {
-                        Graphic::ImageFile { path, x, y, w, h } => {
-                            textures.register(path).await;
-                            *graphic = Graphic::Image { path: path.to_string(), x: *x, y: *y, w: *w, h: *h };
-                        },
-                        _ => {},
+                    if let Graphic::ImageFile { path, x, y, w, h } = graphic {
+                        textures.register(path).await;
+                        *graphic = Graphic::Image { path: path.to_string(), x: *x, y: *y, w: *w, h: *h };
                     }
                 }
 
-                self.cache.canvas.insert(canvas_key, canvas);
+                self.cache.canvas.insert(canvas_key.clone(), canvas);
             }
+
+            if !self.cache.top_bar_extra_message.contains_key(&canvas_key) {
+                let message = entries.render_top_bar_extra_message.and_then(
+                    |render| render(&entries[self.cursor], self.entry_state)
+                );
+                self.cache.top_bar_extra_message.insert(canvas_key, message);
+            }
+        }
+
+        if (self.show_dashboard || entries.is_empty()) && !self.cache.dashboard.contains_key(&self.curr_entries_id) {
+            self.cache.dashboard.insert(self.curr_entries_id.clone(), render_dashboard(entries));
         }
 
         if !self.cache.scroll_bar_colors.contains_key(&self.curr_entries_id) {
             self.cache.scroll_bar_colors.insert(self.curr_entries_id.clone(), calc_scroll_bar_colors(&entries.entries));
         }
+
+        if !self.cache.scroll_bar_label_colors.contains_key(&self.curr_entries_id) {
+            let labels = entries.iter().map(|entry| self.triage_label_of(entries, entry)).collect::<Vec<_>>();
+            let colors = calc_scroll_bar_label_colors(&labels, &self.triage_labels);
+            self.cache.scroll_bar_label_colors.insert(self.curr_entries_id.clone(), colors);
+        }
     }
 }
 
@@ -169,3 +207,19 @@ fn calc_scroll_bar_colors(entries: &[Entry]) -> Vec<Color> {
 
     colors
 }
+
+// Each cell is the color of the most common label in the cell, or `None` if no entry
+// in the cell has a label.
+fn calc_scroll_bar_label_colors(labels: &[Option<String>], triage_labels: &[TriageLabel]) -> Vec<Option<Color>> {
+    let mut counts: Vec<HashMap<&str, usize>> = vec![HashMap::new(); 128];
+
+    for (i, label) in labels.iter().enumerate() {
+        if let Some(label) = label {
+            *counts[i * 128 / labels.len()].entry(label.as_str()).or_default() += 1;
+        }
+    }
+
+    counts.iter().map(
+        |counts| counts.iter().max_by(|(l1, c1), (l2, c2)| c1.cmp(c2).then(l2.cmp(l1))).map(|(label, _)| label_color(triage_labels, label))
+    ).collect()
+}
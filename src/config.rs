@@ -1,3 +1,6 @@
+use crate::keymap::Keymap;
+use crate::layout::SideBarPosition;
+use crate::triage::{TriageLabel, default_triage_labels};
 use macroquad::color::Color;
 
 pub struct Config {
@@ -11,6 +14,29 @@ pub struct Config {
     pub top_bar_font: Color,
     pub side_bar_bg: Color,
     pub side_bar_font: Color,
+
+    /// `SideBarPosition::Auto` puts the side bar on the right side if the window is
+    /// landscape, and at the bottom if it's portrait.
+    pub side_bar_position: SideBarPosition,
+
+    /// The key bindings. The help message is generated from this. You can load it
+    /// from a file with `Keymap::from_file`.
+    pub keymap: Keymap,
+
+    /// If it's set, the pinned entries are saved to this file (json), and loaded
+    /// when the engine starts.
+    pub pins_path: Option<String>,
+
+    /// Like `pins_path`, but for the notes and the issue ids of the entries.
+    pub annotations_path: Option<String>,
+
+    /// The labels that the user can put on entries (Shift+1~9). The labels are saved
+    /// with the annotations.
+    pub triage_labels: Vec<TriageLabel>,
+
+    /// Where the exported files (e.g. html reports) are written. If it's not set,
+    /// they're written to the current directory.
+    pub export_dir: Option<String>,
 }
 
 impl Default for Config {
@@ -45,6 +71,12 @@ impl Default for Config {
                 b: 1.0,
                 a: 1.0,
             },
+            side_bar_position: SideBarPosition::Auto,
+            keymap: Keymap::default(),
+            pins_path: None,
+            annotations_path: None,
+            triage_labels: default_triage_labels(),
+            export_dir: None,
         }
     }
 }
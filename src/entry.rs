@@ -1,4 +1,5 @@
-use crate::{Filter, Graphic};
+use crate::{ClickAction, EntryAction, Filter, Graphic};
+use std::collections::HashSet;
 
 #[derive(Clone, Debug)]
 pub struct Entry {
@@ -34,6 +35,11 @@ pub struct Entry {
     /// If you're using shev to render a result of a test suite, you can use this to
     /// indicate whether a test case is successful.
     pub flag: EntryFlag,
+
+    /// If it's set, the engine draws this canvas instead of calling `render_canvas` (or
+    /// `render_stateful_canvas`), regardless of `EntryState`. It's useful if you already
+    /// have the graphics when you create the entry.
+    pub canvas: Option<Vec<Graphic>>,
 }
 
 impl Default for Entry {
@@ -46,6 +52,7 @@ impl Default for Entry {
             transition1: None,
             transition2: None,
             flag: EntryFlag::None,
+            canvas: None,
         }
     }
 }
@@ -73,6 +80,9 @@ pub struct Entries {
     /// Users can filter `Entry`s with Ctrl+1~9.
     pub filters: Vec<Filter>,
 
+    /// External commands that users can run on the entries (see `EntryAction`).
+    pub actions: Vec<EntryAction>,
+
     /// The engine will use this function to render the currently selected `Entry`.
     /// The canvas size is always 900x600. If the graphic goes out of canvas,
     /// the user has to use WASD to move the camera.
@@ -83,11 +93,42 @@ pub struct Entries {
     /// The user can change `EntryState` by pressing M key.
     pub render_canvas: fn(&Entry, EntryState) -> Result<Vec<Graphic>, String>,
 
+    /// If it's set, the engine uses this function instead of `render_canvas`.
+    ///
+    /// Use this if the canvas is interactive. When the user clicks a
+    /// `Graphic::Clickable` with `ClickAction::Toggle`, the engine updates
+    /// the `CanvasState` of the entry and calls this function again.
+    #[allow(clippy::type_complexity)]
+    pub render_stateful_canvas: Option<fn(&Entry, EntryState, &CanvasState) -> Result<Vec<Graphic>, String>>,
+
+    /// The engine calls this function when the user clicks a `Graphic::Clickable`
+    /// with `ClickAction::Custom(id)`. If it returns an action, the engine runs the action.
+    #[allow(clippy::type_complexity)]
+    pub on_click: Option<fn(&Entry, EntryState, &str) -> Option<ClickAction>>,
+
     /// If you set this, you can dump extra message to the top-bar.
     pub render_top_bar_extra_message: Option<fn(&Entry, EntryState) -> Option<String>>,
+
+    /// If it's set, this `Entries` is a run of a test suite. When the user asks for
+    /// the history of an entry (T key), the engine looks for the entries with the
+    /// same name in the runs of the same suite, and sorts them by `SuiteRun::order`.
+    ///
+    /// If it's not set, the engine looks for them in every `Entries`, and sorts them by id,
+    /// which is not a meaningful order in most cases. So you have to set this to get a
+    /// meaningful timeline.
+    pub run: Option<SuiteRun>,
 }
 
 impl Entries {
+    /// It renders the canvas of `entry`, which is an entry of this `Entries` (see `Entry::canvas`).
+    pub(crate) fn render_entry_canvas(&self, entry: &Entry, entry_state: EntryState, canvas_state: &CanvasState) -> Result<Vec<Graphic>, String> {
+        match (&entry.canvas, self.render_stateful_canvas) {
+            (Some(canvas), _) => Ok(canvas.clone()),
+            (None, Some(render_stateful_canvas)) => render_stateful_canvas(entry, entry_state, canvas_state),
+            (None, None) => (self.render_canvas)(entry, entry_state),
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.entries.len()
     }
@@ -114,8 +155,12 @@ impl Default for Entries {
             entry_state_count: 1,
             transition: None,
             filters: vec![],
+            actions: vec![],
             render_canvas: |_, _| Ok(vec![]),
+            render_stateful_canvas: None,
+            on_click: None,
             render_top_bar_extra_message: None,
+            run: None,
         }
     }
 }
@@ -145,6 +190,36 @@ impl EntryFlag {
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct EntryState(pub u32);
 
+/// Each (`Entries`, `Entry`, `EntryState`) has its own `CanvasState`.
+/// It's a set of keys that are toggled by `ClickAction::Toggle`.
+/// Nothing is toggled when an entry is opened for the first time.
+#[derive(Clone, Debug, Default)]
+pub struct CanvasState {
+    toggled: HashSet<String>,
+}
+
+impl CanvasState {
+    pub fn is_toggled(&self, key: &str) -> bool {
+        self.toggled.contains(key)
+    }
+
+    pub(crate) fn toggle(&mut self, key: &str) {
+        if !self.toggled.remove(key) {
+            self.toggled.insert(key.to_string());
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SuiteRun {
+    /// Runs with the same `suite` are compared with each other.
+    pub suite: String,
+
+    /// Runs are sorted by this value, from the oldest to the newest. It doesn't have to
+    /// be contiguous, so you can use a timestamp.
+    pub order: i64,
+}
+
 #[derive(Clone, Debug)]
 pub struct Transition {
     pub id: String,
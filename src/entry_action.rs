@@ -0,0 +1,320 @@
+use crate::entry::{Entries, Entry, EntryFlag, EntryState, Transition};
+use crate::graphic::{GRAY, Graphic, RED, TextBox, WHITE, push_text};
+use crate::keymap::{Command, KeyChord, Keymap};
+use serde_json::{Value, json};
+use std::process::{self, Output};
+use std::thread::{self, JoinHandle};
+
+/// An external command that the user can run on the current entry (or on the selected
+/// entries, once per entry). For example, "rerun this test" or "open in editor".
+///
+/// Users can run the actions with Alt+1~9, with `key`, or with the command palette.
+#[derive(Clone, Debug)]
+pub struct EntryAction {
+    pub name: String,
+
+    /// If it's bound to a command in the keymap, the command wins.
+    pub key: Option<KeyChord>,
+
+    /// The program and its arguments. It's not run by a shell, so you don't have to
+    /// quote anything. Each argument can have placeholders:
+    ///
+    /// - `{name}`: `Entry::name`
+    /// - `{content}`: `Entry::content` (empty if there's none)
+    /// - `{category}`: `Entry::categories`, joined with ","
+    /// - `{entries}`: `Entries::id`
+    ///
+    /// If you need pipes or globs, use a shell explicitly (e.g. `["sh", "-c", "... {name} ..."]`).
+    pub command: Vec<String>,
+    pub output: ActionOutput,
+}
+
+impl EntryAction {
+    /// The key chords of the `index`-th action of an `Entries` (e.g. "Alt+1/F5").
+    pub(crate) fn describe_keys(&self, index: usize, keymap: &Keymap) -> String {
+        let mut keys = if index < 9 { vec![keymap.describe(&[Command::RunEntryAction(index + 1)])] } else { vec![] };
+        keys.extend(self.key.map(|key| key.to_string()));
+        keys.into_iter().filter(|keys| !keys.is_empty()).collect::<Vec<_>>().join("/")
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ActionOutput {
+    /// The exit status and the first line of the output are shown in a popup.
+    Popup,
+
+    /// A tmp `Entries` shows the stdout and the stderr of each run.
+    Entries,
+}
+
+// The result of an action on an entry.
+struct ActionRun {
+    name: String,
+    command: String,
+    output: Result<Output, String>,
+}
+
+impl ActionRun {
+    fn is_success(&self) -> bool {
+        matches!(&self.output, Ok(output) if output.status.success())
+    }
+
+    fn status(&self) -> String {
+        match &self.output {
+            Ok(output) => match output.status.code() {
+                Some(code) => format!("exit {code}"),
+                None => String::from("killed"),
+            },
+            Err(e) => e.to_string(),
+        }
+    }
+}
+
+pub(crate) enum ActionResult {
+    /// for the popup
+    Message(String),
+
+    /// a tmp `Entries` (see `ActionOutput::Entries`)
+    Entries(Box<Entries>),
+}
+
+/// An action that is running in another thread, so that the viewer doesn't freeze.
+pub(crate) struct RunningAction {
+    action: EntryAction,
+    entries_id: String,
+    title: String,
+    handle: JoinHandle<Vec<ActionRun>>,
+}
+
+impl RunningAction {
+    pub fn spawn(action: &EntryAction, entries: &Entries, targets: &[usize]) -> RunningAction {
+        let commands = targets.iter().filter_map(|i| entries.get(*i)).map(
+            |entry| (entry.name.to_string(), expand(&action.command, entries, entry))
+        ).collect::<Vec<_>>();
+
+        RunningAction {
+            action: action.clone(),
+            entries_id: entries.id.to_string(),
+            title: entries.title.as_ref().unwrap_or(&entries.id).to_string(),
+            handle: thread::spawn(move || commands.into_iter().map(
+                |(name, args)| ActionRun {
+                    name,
+                    command: args.join(" "),
+                    output: match args.split_first() {
+                        Some((program, args)) => process::Command::new(program).args(args).output().map_err(|e| format!("failed to run `{program}`: {e}")),
+                        None => Err(String::from("empty command")),
+                    },
+                }
+            ).collect()),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    pub fn join(self) -> ActionResult {
+        let RunningAction { action, entries_id, title, handle } = self;
+        let name = &action.name;
+        let runs = match handle.join() {
+            Ok(runs) => runs,
+            Err(_) => { return ActionResult::Message(format!("`{name}` panicked.")); },
+        };
+
+        match (action.output, runs.as_slice()) {
+            (_, []) => ActionResult::Message(format!("`{name}` had no entry to run on.")),
+            (ActionOutput::Popup, [run]) => {
+                let output = match &run.output {
+                    Ok(output) if !output.stdout.is_empty() => String::from_utf8_lossy(&output.stdout).to_string(),
+                    Ok(output) => String::from_utf8_lossy(&output.stderr).to_string(),
+                    Err(_) => String::new(),
+                };
+
+                match output.lines().find(|line| !line.trim().is_empty()) {
+                    Some(line) => ActionResult::Message(format!("`{name}`: {} ({})", line.trim(), run.status())),
+                    None => ActionResult::Message(format!("`{name}`: {}", run.status())),
+                }
+            },
+            (ActionOutput::Popup, runs) => {
+                let failed = runs.iter().filter(|run| !run.is_success()).count();
+                ActionResult::Message(format!("`{name}`: {} succeeded, {failed} failed", runs.len() - failed))
+            },
+            (ActionOutput::Entries, runs) => ActionResult::Entries(Box::new(output_entries(&action, &entries_id, &title, runs))),
+        }
+    }
+}
+
+// It has an entry per run, and the canvas shows the output of the run.
+fn output_entries(action: &EntryAction, entries_id: &str, title: &str, runs: &[ActionRun]) -> Entries {
+    let entries = runs.iter().map(
+        |run| {
+            let (stdout, stderr) = match &run.output {
+                Ok(output) => (String::from_utf8_lossy(&output.stdout).to_string(), String::from_utf8_lossy(&output.stderr).to_string()),
+                Err(e) => (String::new(), e.to_string()),
+            };
+
+            Entry {
+                name: run.name.to_string(),
+                content: Some(json!({
+                    "command": run.command,
+                    "status": run.status(),
+                    "stdout": stdout,
+                    "stderr": stderr,
+                }).to_string()),
+                flag: if run.is_success() { EntryFlag::Green } else { EntryFlag::Red },
+                ..Entry::default()
+            }
+        }
+    ).collect();
+
+    Entries {
+        id: format!("@@tmp-{:x}", rand::random::<u64>()),
+        title: Some(format!("{} ({})", action.name, title)),
+        entries,
+        entry_state_count: 2,
+        transition: Some(Transition {
+            id: entries_id.to_string(),
+            description: Some(format!("exit {}", action.name)),
+        }),
+        render_canvas: render_output,
+        ..Entries::default()
+    }
+}
+
+// It substitutes the placeholders of each argument (see `EntryAction::command`). It's done
+// in a single pass, so a placeholder in the substituted values (e.g. `{name}` in the content)
+// is kept as it is.
+fn expand(command: &[String], entries: &Entries, entry: &Entry) -> Vec<String> {
+    let categories = entry.categories.join(",");
+    let placeholders = [
+        ("{name}", entry.name.as_str()),
+        ("{content}", entry.content.as_deref().unwrap_or("")),
+        ("{category}", categories.as_str()),
+        ("{entries}", entries.id.as_str()),
+    ];
+
+    command.iter().map(
+        |arg| {
+            let mut result = String::new();
+            let mut rest = arg.as_str();
+
+            while !rest.is_empty() {
+                match placeholders.iter().find(|(placeholder, _)| rest.starts_with(placeholder)) {
+                    Some((placeholder, value)) => {
+                        result.push_str(value);
+                        rest = &rest[placeholder.len()..];
+                    },
+                    None => {
+                        let ch = rest.chars().next().unwrap();
+                        result.push(ch);
+                        rest = &rest[ch.len_utf8()..];
+                    },
+                }
+            }
+
+            result
+        }
+    ).collect()
+}
+
+// EntryState(0) shows stdout, and EntryState(1) shows stderr.
+fn render_output(entry: &Entry, state: EntryState) -> Result<Vec<Graphic>, String> {
+    let content: Value = serde_json::from_str(entry.content.as_ref().ok_or_else(|| String::from("no content"))?).map_err(|e| format!("{e:?}"))?;
+    let command = content["command"].as_str().unwrap_or("");
+    let status = content["status"].as_str().unwrap_or("");
+    let (stream, output) = if state.0 == 0 {
+        ("stdout", content["stdout"].as_str().unwrap_or(""))
+    } else {
+        ("stderr", content["stderr"].as_str().unwrap_or(""))
+    };
+    let mut graphics = vec![];
+
+    push_text(&mut graphics, &format!("$ {command}"), 20.0, 30.0, 14.0, WHITE);
+    push_text(&mut graphics, &format!("{status}, {stream} (M to switch)"), 20.0, 52.0, 14.0, if entry.flag == EntryFlag::Red { RED } else { GRAY });
+    graphics.extend(TextBox::from_ansi(output, 14.0, WHITE, [20.0, 70.0, 860.0, 510.0]).render());
+    Ok(graphics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(names: &[&str]) -> Entries {
+        Entries {
+            id: String::from("tests"),
+            entries: names.iter().map(
+                |name| Entry {
+                    name: name.to_string(),
+                    content: Some(String::from("{name} is not expanded")),
+                    categories: vec![String::from("a"), String::from("b")],
+                    ..Entry::default()
+                }
+            ).collect(),
+            ..Entries::default()
+        }
+    }
+
+    fn action(command: &[&str], output: ActionOutput) -> EntryAction {
+        EntryAction {
+            name: String::from("act"),
+            key: None,
+            command: command.iter().map(|arg| arg.to_string()).collect(),
+            output,
+        }
+    }
+
+    // `join` waits for the thread.
+    fn run(action: &EntryAction, entries: &Entries, targets: &[usize]) -> ActionResult {
+        RunningAction::spawn(action, entries, targets).join()
+    }
+
+    #[test]
+    fn placeholders() {
+        let entries = entries(&["føø"]);
+        let command = ["run", "--name={name}", "{content}", "{category}/{entries}", "{unknown}{name"].map(|arg| arg.to_string());
+
+        assert_eq!(
+            expand(&command, &entries, &entries.entries[0]),
+            vec!["run", "--name=føø", "{name} is not expanded", "a,b/tests", "{unknown}{name"],
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn popup() {
+        let entries = entries(&["x", "y"]);
+
+        match run(&action(&["echo", "\n hi {name}"], ActionOutput::Popup), &entries, &[1]) {
+            ActionResult::Message(message) => assert_eq!(message, "`act`: hi y (exit 0)"),
+            ActionResult::Entries(_) => panic!(),
+        }
+
+        match run(&action(&["sh", "-c", "test {name} = x"], ActionOutput::Popup), &entries, &[0, 1]) {
+            ActionResult::Message(message) => assert_eq!(message, "`act`: 1 succeeded, 1 failed"),
+            ActionResult::Entries(_) => panic!(),
+        }
+
+        match run(&action(&["no-such-program-for-shev"], ActionOutput::Popup), &entries, &[]) {
+            ActionResult::Message(message) => assert_eq!(message, "`act` had no entry to run on."),
+            ActionResult::Entries(_) => panic!(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn entries_output() {
+        let entries = entries(&["x", "y"]);
+
+        match run(&action(&["sh", "-c", "echo {name}; test {name} = x"], ActionOutput::Entries), &entries, &[0, 1]) {
+            ActionResult::Entries(output) => {
+                assert_eq!(output.transition.map(|transition| transition.id), Some(String::from("tests")));
+                assert_eq!(output.entries.iter().map(|entry| entry.flag).collect::<Vec<_>>(), vec![EntryFlag::Green, EntryFlag::Red]);
+
+                let content: Value = serde_json::from_str(output.entries[1].content.as_ref().unwrap()).unwrap();
+                assert_eq!(content["stdout"], "y\n");
+                assert_eq!(content["status"], "exit 1");
+            },
+            ActionResult::Message(message) => panic!("{message}"),
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use crate::entry::{CanvasState, Entries, Entry, EntryFlag, EntryState, Transition};
+use crate::graphic::{Graphic, css_rgb, escape_xml, flag_color, graphics_to_svg};
+use std::collections::HashMap;
+
+const STYLE: &str = "
+body { margin: 0; display: flex; height: 100vh; background: #111; color: #eee; font-family: monospace; }
+#side { width: 320px; overflow-y: auto; background: #1a1a1a; padding: 8px; box-sizing: border-box; }
+#side input { width: 100%; box-sizing: border-box; margin-bottom: 8px; background: #222; color: #eee; border: 1px solid #555; padding: 4px; }
+#side h3 { margin: 12px 0 4px 0; font-size: 14px; color: #aac; }
+#side a { display: block; color: #eee; text-decoration: none; padding: 1px 4px; white-space: nowrap; overflow: hidden; text-overflow: ellipsis; }
+#side a:hover { background: #333; }
+#main { flex: 1; overflow: auto; padding: 16px; }
+.entry { display: none; }
+.entry:target { display: block; }
+.top { background: #334; padding: 8px; margin-bottom: 8px; }
+.top a { color: #9cf; }
+.states button { background: #222; color: #eee; border: 1px solid #555; margin-right: 4px; }
+.states button.on { background: #446; }
+.canvas { display: none; }
+.canvas.on { display: block; }
+.flag { display: inline-block; width: 10px; height: 10px; border-radius: 5px; margin-right: 6px; }
+";
+
+const SCRIPT: &str = "
+document.getElementById('filter').addEventListener('input', function (e) {
+    const query = e.target.value.toLowerCase();
+
+    for (const item of document.querySelectorAll('#side a')) {
+        item.style.display = item.dataset.name.toLowerCase().includes(query) ? '' : 'none';
+    }
+});
+
+function showState(entry, state) {
+    for (const [i, canvas] of document.querySelectorAll('#' + entry + ' .canvas').entries()) {
+        canvas.classList.toggle('on', i === state);
+    }
+
+    for (const [i, button] of document.querySelectorAll('#' + entry + ' .states button').entries()) {
+        button.classList.toggle('on', i === state);
+    }
+}
+
+if (!location.hash) {
+    const first = document.querySelector('#side a');
+
+    if (first) {
+        location.hash = first.getAttribute('href');
+    }
+}
+";
+
+/// It writes `entries` to a single html file that doesn't need shev (or anything else) to
+/// open. It has a side list of the entries with a filter box, and the canvases of every
+/// `EntryState` as inline svg. The transitions between the exported `Entries` are links.
+///
+/// To export every `Entries`, use `entries_map.values().collect::<Vec<_>>()`.
+pub fn export_html(entries: &[&Entries], path: &str) -> Result<(), String> {
+    std::fs::write(path, entries_to_html(entries)).map_err(|e| format!("Failed to write `{path}`: {e:?}"))
+}
+
+/// It writes `graphics` (a 900x600 canvas) to an svg file. See `graphics_to_svg`.
+pub fn export_svg(graphics: &[Graphic], path: &str) -> Result<(), String> {
+    std::fs::write(path, graphics_to_svg(graphics)).map_err(|e| format!("Failed to write `{path}`: {e:?}"))
+}
+
+pub fn entries_to_html(entries: &[&Entries]) -> String {
+    // `Entries::id` -> index, for the links. An empty `Entries` has nothing to link to.
+    let indexes = entries.iter().enumerate().map(
+        |(i, entries)| (entries.id.as_str(), if entries.is_empty() { None } else { Some(i) })
+    ).collect::<HashMap<_, _>>();
+    let title = match entries {
+        [entries] => entries.title.as_ref().unwrap_or(&entries.id).to_string(),
+        _ => String::from("shev report"),
+    };
+    let mut side = vec![String::from(r#"<input id="filter" placeholder="filter entries...">"#)];
+    let mut main = vec![];
+
+    for (i, curr) in entries.iter().enumerate() {
+        side.push(format!("<h3>{}</h3>", escape_xml(curr.title.as_ref().unwrap_or(&curr.id))));
+
+        for (j, entry) in curr.iter().enumerate() {
+            side.push(format!(
+                r##"<a href="#e-{i}-{j}" data-name="{}">{}{}</a>"##,
+                escape_xml(&entry.name),
+                flag(entry.flag),
+                escape_xml(&entry.name),
+            ));
+            main.push(render_entry(&indexes, curr, i, j, entry));
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<div id=\"side\">\n{}\n</div>\n<div id=\"main\">\n{}\n</div>\n<script>{SCRIPT}</script>\n</body>\n</html>\n",
+        escape_xml(&title),
+        side.join("\n"),
+        main.join("\n"),
+    )
+}
+
+fn render_entry(indexes: &HashMap<&str, Option<usize>>, entries: &Entries, i: usize, j: usize, entry: &Entry) -> String {
+    let id = format!("e-{i}-{j}");
+    let mut top = vec![format!(
+        "<b>{}</b> / {}{}",
+        escape_xml(entries.title.as_ref().unwrap_or(&entries.id)),
+        flag(entry.flag),
+        escape_xml(&entry.name),
+    )];
+
+    for (key, transition) in [
+        ("parent", entries.transition.as_ref()),
+        ("transition 1", entry.transition1.as_ref()),
+        ("transition 2", entry.transition2.as_ref()),
+    ] {
+        if let Some(transition) = transition {
+            top.push(link(indexes, key, transition));
+        }
+    }
+
+    let mut states = vec![];
+    let mut canvases = vec![];
+
+    for state in 0..entries.entry_state_count {
+        let on = if state == 0 { " class=\"on\"" } else { "" };
+        let canvas = entries.render_entry_canvas(entry, EntryState(state), &CanvasState::default());
+        let message = entries.render_top_bar_extra_message.and_then(|render| render(entry, EntryState(state)));
+        let message = match message {
+            Some(message) => format!("<div>{}</div>", escape_xml(&message)),
+            None => String::new(),
+        };
+        let canvas = match canvas {
+            Ok(canvas) => graphics_to_svg(&canvas),
+            Err(e) => format!("<pre>Failed to render the canvas: {}</pre>", escape_xml(&e)),
+        };
+
+        states.push(format!(r#"<button{on} onclick="showState('{id}', {state})">state {state}</button>"#));
+        canvases.push(format!("<div class=\"canvas{}\">{message}\n{canvas}</div>", if state == 0 { " on" } else { "" }));
+    }
+
+    // There's nothing to switch.
+    if states.len() == 1 {
+        states.clear();
+    }
+
+    format!(
+        "<div class=\"entry\" id=\"{id}\">\n<div class=\"top\">{}</div>\n<div class=\"states\">{}</div>\n{}\n</div>",
+        top.join("<br>"),
+        states.join(""),
+        canvases.join("\n"),
+    )
+}
+
+/// `{dir}/{name}.{extension}`. The characters that may not be allowed in a file name are
+/// replaced with `_`.
+pub(crate) fn export_path(dir: Option<&str>, name: &str, extension: &str) -> String {
+    let name = name.chars().map(
+        |ch| if ch.is_alphanumeric() || ch == '-' || ch == '_' || ch == '.' { ch } else { '_' }
+    ).collect::<String>();
+    let file_name = format!("{}.{extension}", name.trim_start_matches('.'));
+
+    match dir {
+        Some(dir) => std::path::Path::new(dir).join(file_name).to_string_lossy().to_string(),
+        None => file_name,
+    }
+}
+
+// If the destination is not exported or is empty, it's not a link.
+fn link(indexes: &HashMap<&str, Option<usize>>, key: &str, transition: &Transition) -> String {
+    let description = escape_xml(transition.description.as_ref().unwrap_or(&transition.id));
+
+    match indexes.get(transition.id.as_str()) {
+        Some(Some(i)) => format!(r##"{key}: <a href="#e-{i}-0">{description}</a>"##),
+        Some(None) => format!("{key}: {description} (empty)"),
+        None => format!("{key}: {description} (not exported)"),
+    }
+}
+
+fn flag(flag: EntryFlag) -> String {
+    match flag {
+        EntryFlag::None => String::new(),
+        _ => format!(r#"<span class="flag" style="background: {}"></span>"#, css_rgb(flag_color(flag))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(id: &str, names: &[&str]) -> Entries {
+        Entries {
+            id: id.to_string(),
+            entries: names.iter().map(|name| Entry { name: name.to_string(), ..Entry::default() }).collect(),
+            ..Entries::default()
+        }
+    }
+
+    #[test]
+    fn escape_names() {
+        let mut tests = entries("tests", &["<a> & \"b\""]);
+        tests.title = Some(String::from("<title>"));
+        let html = entries_to_html(&[&tests]);
+
+        assert!(html.contains("<title>&lt;title&gt;</title>"));
+        assert!(html.contains(r##"<a href="#e-0-0" data-name="&lt;a&gt; &amp; &quot;b&quot;">&lt;a&gt; &amp; &quot;b&quot;</a>"##));
+        assert!(!html.contains("<a>"));
+    }
+
+    #[test]
+    fn links() {
+        let mut tests = entries("tests", &["a", "b"]);
+        tests.entries[0].transition1 = Some(Transition { id: String::from("details"), description: Some(String::from("see <details>")) });
+        tests.entries[0].transition2 = Some(Transition { id: String::from("empty"), description: None });
+        tests.entries[1].transition1 = Some(Transition { id: String::from("elsewhere"), description: None });
+
+        let details = entries("details", &["c"]);
+        let empty = entries("empty", &[]);
+        let html = entries_to_html(&[&tests, &details, &empty]);
+
+        assert!(html.contains("<title>shev report</title>"));
+        assert!(html.contains(r##"transition 1: <a href="#e-1-0">see &lt;details&gt;</a>"##));
+        assert!(html.contains("transition 2: empty (empty)"));
+        assert!(html.contains("transition 1: elsewhere (not exported)"));
+    }
+
+    #[test]
+    fn entry_states() {
+        let mut tests = entries("tests", &["a"]);
+        tests.entries[0].flag = EntryFlag::Red;
+        let html = entries_to_html(&[&tests]);
+
+        assert!(!html.contains("<button"));
+        assert!(html.contains(&css_rgb(flag_color(EntryFlag::Red))));
+
+        tests.entry_state_count = 3;
+        let html = entries_to_html(&[&tests]);
+
+        assert_eq!(html.matches("<button").count(), 3);
+        assert!(html.contains(r#"<button class="on" onclick="showState('e-0-0', 0)">state 0</button>"#));
+        assert!(flag(EntryFlag::None).is_empty());
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(export_path(None, "a/b c", "html"), "a_b_c.html");
+        assert_eq!(export_path(None, "..hidden", "svg"), "hidden.svg");
+        assert_eq!(export_path(Some("out"), "x", "svg"), std::path::Path::new("out").join("x.svg").to_string_lossy());
+    }
+}
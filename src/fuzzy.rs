@@ -0,0 +1,90 @@
+/// It checks whether the chars of `query` appear in `candidate` in order (case-insensitive),
+/// and returns the score of the match. The higher, the better. It returns `None` if
+/// it doesn't match.
+///
+/// A match gets more points if the chars are consecutive, or if they're at the start
+/// of a word (e.g. "ng" matches "nested_generic" better than "tangent"). Shorter
+/// candidates are preferred.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let query = query.chars().filter(|ch| !ch.is_whitespace()).flat_map(|ch| ch.to_lowercase()).collect::<Vec<_>>();
+
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let candidate = candidate.chars().collect::<Vec<_>>();
+    let mut score = 0;
+    let mut query_index = 0;
+    let mut prev_match = None;
+
+    for (i, ch) in candidate.iter().enumerate() {
+        if query_index == query.len() {
+            break;
+        }
+
+        if !ch.to_lowercase().eq(query[query_index].to_lowercase()) {
+            continue;
+        }
+
+        score += 1;
+
+        if prev_match == Some(i.wrapping_sub(1)) {
+            score += 5;
+        }
+
+        if i == 0 || !candidate[i - 1].is_alphanumeric() || (candidate[i - 1].is_lowercase() && ch.is_uppercase()) {
+            score += 8;
+        }
+
+        prev_match = Some(i);
+        query_index += 1;
+    }
+
+    if query_index < query.len() {
+        return None;
+    }
+
+    Some(score * 16 - candidate.len().min(255) as i32)
+}
+
+/// It returns the indexes of `candidates` that match `query`, the best match first.
+/// Matches with the same score keep the original order.
+pub fn fuzzy_search<'a>(query: &str, candidates: impl Iterator<Item = &'a str>) -> Vec<usize> {
+    let mut matches = candidates.enumerate().filter_map(
+        |(i, candidate)| fuzzy_score(query, candidate).map(|score| (i, score))
+    ).collect::<Vec<_>>();
+
+    matches.sort_by_key(|(i, score)| (-score, *i));
+    matches.into_iter().map(|(i, _)| i).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_in_order() {
+        assert!(fuzzy_score("abc", "a_b_c").is_some());
+        assert!(fuzzy_score("ABC", "abc").is_some());
+        assert!(fuzzy_score("a c", "abc").is_some());
+        assert_eq!(fuzzy_score("cba", "abc"), None);
+        assert_eq!(fuzzy_score("abcd", "abc"), None);
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn prefers_word_starts_and_consecutive_chars() {
+        assert!(fuzzy_score("ng", "nested_generic") > fuzzy_score("ng", "tangent"));
+        assert!(fuzzy_score("ng", "nestedGeneric") > fuzzy_score("ng", "tangent"));
+        assert!(fuzzy_score("abc", "xabc") > fuzzy_score("abc", "xaxbxc"));
+        assert!(fuzzy_score("foo", "foo") > fuzzy_score("foo", "foo_bar_baz"));
+    }
+
+    #[test]
+    fn search_order() {
+        let candidates = ["tangent", "nested_generic", "foo", "nested_generic"];
+        assert_eq!(fuzzy_search("ng", candidates.into_iter()), vec![1, 3, 0]);
+        assert_eq!(fuzzy_search("", candidates.into_iter()), vec![0, 1, 2, 3]);
+        assert_eq!(fuzzy_search("xyz", candidates.into_iter()), Vec::<usize>::new());
+    }
+}
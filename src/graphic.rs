@@ -1,14 +1,55 @@
 use crate::cache::TextureCache;
+use crate::entry::EntryFlag;
 use macroquad::color::Color;
 use macroquad::math::Vec2;
-use macroquad::shapes::{draw_circle, draw_rectangle, draw_triangle};
+use macroquad::shapes::{
+    draw_circle,
+    draw_circle_lines,
+    draw_ellipse,
+    draw_ellipse_lines,
+    draw_line,
+    draw_rectangle,
+    draw_rectangle_lines,
+    draw_triangle,
+};
 use macroquad::text::{Font, TextParams, draw_text_ex};
 use macroquad::texture::{DrawTextureParams, draw_texture_ex};
+use std::f32::consts::{FRAC_PI_2, PI};
 
+mod ansi;
+mod chart;
+mod dashboard;
+mod geometry;
+mod json_tree;
+mod markdown;
+mod plain_text;
+mod svg;
+mod syntax;
 mod text_box;
 
+use geometry::{dashed_segments, rounded_rect_outline, triangulate};
+pub use chart::Chart;
+pub use dashboard::render_dashboard;
+pub use json_tree::{JsonTree, render_json_tree};
+pub use markdown::Markdown;
+pub use plain_text::graphics_to_text;
+pub(crate) use svg::{css_rgb, escape_xml};
+pub use svg::graphics_to_svg;
+pub use syntax::Syntax;
 pub use text_box::TextBox;
 
+// The canvas is always 900x600 for `render_canvas`. It's scaled to fit the canvas rect.
+pub(crate) const CANVAS_WIDTH: f32 = 900.0;
+pub(crate) const CANVAS_HEIGHT: f32 = 600.0;
+
+// The colors that the engine uses to draw its own canvases.
+pub(crate) const WHITE: Color = Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
+pub(crate) const GRAY: Color = Color { r: 0.5, g: 0.5, b: 0.5, a: 1.0 };
+pub(crate) const RED: Color = Color { r: 0.75, g: 0.25, b: 0.25, a: 1.0 };
+pub(crate) const GREEN: Color = Color { r: 0.25, g: 0.75, b: 0.25, a: 1.0 };
+pub(crate) const BLUE: Color = Color { r: 0.25, g: 0.25, b: 0.75, a: 1.0 };
+
+/// If `thickness` is set, it only draws the outline of the shape.
 #[derive(Clone, Debug)]
 pub enum Graphic {
     Rect {
@@ -35,6 +76,30 @@ pub enum Graphic {
         color: Color,
     },
 
+    /// `dash` is (length of a dash, length of a gap). If it's not set, it's a solid line.
+    Line {
+        p1: (f32, f32),
+        p2: (f32, f32),
+        thickness: f32,
+        dash: Option<(f32, f32)>,
+        color: Color,
+    },
+
+    /// Like `Line`, but it connects all the points. The dash pattern continues across the points.
+    Polyline {
+        points: Vec<(f32, f32)>,
+        thickness: f32,
+        dash: Option<(f32, f32)>,
+        color: Color,
+    },
+
+    /// The polygon may be concave, but it must not intersect itself.
+    Polygon {
+        points: Vec<(f32, f32)>,
+        thickness: Option<f32>,
+        color: Color,
+    },
+
     /// NOTE: (x, y) is bottom-left of the character.
     /// TODO: I'm not sure whether (x, y) is bottom-left of the character...
     Char {
@@ -65,7 +130,43 @@ pub enum Graphic {
         y: f32,
         w: f32,
         h: f32,
-    }
+    },
+
+    /// It's invisible. If the user clicks this area of the canvas, the engine runs `action`.
+    /// If clickable areas overlap, the one that comes later in the vector wins.
+    /// The area is highlighted when the mouse is on it.
+    Clickable {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        action: ClickAction,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum ClickAction {
+    /// It toggles `key` in the `CanvasState` of the current entry, and re-renders the canvas.
+    /// It only makes sense if the `Entries` has `render_stateful_canvas`.
+    Toggle(String),
+
+    /// It transits to another `Entries`, like `Transition`. If `cursor` is not set,
+    /// it uses the last cursor of the `Entries`.
+    Transit {
+        id: String,
+        cursor: Option<usize>,
+    },
+
+    /// It changes the `EntryState` of the current entry.
+    SetEntryState(u32),
+
+    /// It opens a url (http, https or file) or an existing file with the default application
+    /// of the OS. Anything else is rejected with a popup.
+    Open(String),
+
+    /// The engine calls `Entries::on_click` with this id. Use this if none of the above
+    /// fits your need.
+    Custom(String),
 }
 
 impl Graphic {
@@ -73,7 +174,8 @@ impl Graphic {
         match self {
             Graphic::Rect { x, y, w, h, .. } |
             Graphic::ImageFile { x, y, w, h, .. } |
-            Graphic::Image { x, y, w, h, .. } => [*x, *y, *w, *h],
+            Graphic::Image { x, y, w, h, .. } |
+            Graphic::Clickable { x, y, w, h, .. } => [*x, *y, *w, *h],
             Graphic::Ellipse { x, y, rx, ry, .. } => [*x - *rx, *y - *ry, *rx * 2.0, *ry * 2.0],
             Graphic::Triangle { p1: (x1, y1), p2: (x2, y2), p3: (x3, y3), .. } => {
                 let x_min = (*x1).min(*x2).min(*x3);
@@ -82,11 +184,26 @@ impl Graphic {
                 let y_max = (*y1).max(*y2).max(*y3);
                 [x_min, y_min, x_max - x_min, y_max - y_min]
             },
+            Graphic::Line { p1, p2, thickness, .. } => bounding_box(&[*p1, *p2], *thickness),
+            Graphic::Polyline { points, thickness, .. } => bounding_box(points, *thickness),
+            Graphic::Polygon { points, thickness, .. } => bounding_box(points, thickness.unwrap_or(0.0)),
             Graphic::Char { x, y, size, .. } => [*x, *y, *x + *size, *y + *size],
         }
     }
 }
 
+fn bounding_box(points: &[(f32, f32)], thickness: f32) -> [f32; 4] {
+    if points.is_empty() {
+        return [0.0; 4];
+    }
+
+    let x_min = points.iter().map(|(x, _)| *x).fold(f32::INFINITY, f32::min) - thickness / 2.0;
+    let x_max = points.iter().map(|(x, _)| *x).fold(f32::NEG_INFINITY, f32::max) + thickness / 2.0;
+    let y_min = points.iter().map(|(_, y)| *y).fold(f32::INFINITY, f32::min) - thickness / 2.0;
+    let y_max = points.iter().map(|(_, y)| *y).fold(f32::NEG_INFINITY, f32::max) + thickness / 2.0;
+    [x_min, y_min, x_max - x_min, y_max - y_min]
+}
+
 pub async fn render(graphics: &[Graphic], font: &Font, textures: &mut TextureCache, (screen_width, screen_height): (f32, f32)) {
     for graphic in graphics.iter() {
         let [x, y, w, h] = graphic.get_rect();
@@ -137,12 +254,12 @@ pub async fn render(graphics: &[Graphic], font: &Font, textures: &mut TextureCac
                 );
 
                 for (angles, (c_x, c_y)) in [
-                    ([0.0f32, 0.2618, 0.5236, 0.7854, 1.0472, 1.3090], (*x + *w - *r, *y + *h - *r)),
-                    ([1.5708, 1.8326, 2.0944, 2.3562, 2.6180, 2.8798], (*x + *r, *y + *h - *r)),
-                    ([3.1416, 3.4034, 3.6652, 3.9270, 4.1888, 4.4506], (*x + *r, *y + *r)),
-                    ([4.7124, 4.9742, 5.2360, 5.4978, 5.7596, 6.0214], (*x + *w - *r, *y + *r)),
+                    (0.0, (*x + *w - *r, *y + *h - *r)),
+                    (FRAC_PI_2, (*x + *r, *y + *h - *r)),
+                    (PI, (*x + *r, *y + *r)),
+                    (PI + FRAC_PI_2, (*x + *w - *r, *y + *r)),
                 ] {
-                    for angle in angles.into_iter() {
+                    for angle in (0..6).map(|i| angles + i as f32 * 0.2618) {
                         let (a1_x, a1_y) = (angle.cos(), angle.sin());
                         let (a2_x, a2_y) = ((angle + 0.2618).cos(), (angle + 0.2618).sin());
 
@@ -155,11 +272,51 @@ pub async fn render(graphics: &[Graphic], font: &Font, textures: &mut TextureCac
                     }
                 }
             },
-            Graphic::Rect { .. } => todo!(),
+            Graphic::Rect { x, y, w, h, radius: None, thickness: Some(t), color } => {
+                draw_rectangle_lines(*x, *y, *w, *h, *t, *color);
+            },
+            Graphic::Rect { x, y, w, h, radius: Some(r), thickness: Some(t), color } => {
+                let mut outline = rounded_rect_outline(*x, *y, *w, *h, *r);
+                outline.push(outline[0]);
+                draw_polyline(&outline, *t, None, *color);
+            },
             Graphic::Ellipse { x, y, rx, ry, thickness: None, color } if rx == ry => {
                 draw_circle(*x, *y, *rx, *color);
             },
-            Graphic::Ellipse { .. } => todo!(),
+            Graphic::Ellipse { x, y, rx, ry, thickness: None, color } => {
+                draw_ellipse(*x, *y, *rx, *ry, 0.0, *color);
+            },
+            Graphic::Ellipse { x, y, rx, ry, thickness: Some(t), color } if rx == ry => {
+                draw_circle_lines(*x, *y, *rx, *t, *color);
+            },
+            Graphic::Ellipse { x, y, rx, ry, thickness: Some(t), color } => {
+                draw_ellipse_lines(*x, *y, *rx, *ry, 0.0, *t, *color);
+            },
+            Graphic::Line { p1, p2, thickness, dash, color } => {
+                draw_polyline(&[*p1, *p2], *thickness, *dash, *color);
+            },
+            Graphic::Polyline { points, thickness, dash, color } => {
+                draw_polyline(points, *thickness, *dash, *color);
+            },
+            Graphic::Polygon { points, thickness: None, color } => {
+                for [p1, p2, p3] in triangulate(points) {
+                    draw_triangle(
+                        Vec2::new(p1.0, p1.1),
+                        Vec2::new(p2.0, p2.1),
+                        Vec2::new(p3.0, p3.1),
+                        *color,
+                    );
+                }
+            },
+            Graphic::Polygon { points, thickness: Some(t), color } => {
+                let mut outline = points.clone();
+
+                if let Some(first) = points.first() {
+                    outline.push(*first);
+                }
+
+                draw_polyline(&outline, *t, None, *color);
+            },
             Graphic::Triangle { p1: (x1, y1), p2: (x2, y2), p3: (x3, y3), color } => {
                 draw_triangle(
                     Vec2::new(*x1, *y1),
@@ -170,7 +327,7 @@ pub async fn render(graphics: &[Graphic], font: &Font, textures: &mut TextureCac
             },
             Graphic::Char { ch, x, y, size, color } => {
                 draw_text_ex(
-                    &std::iter::once(*ch).collect::<String>(),
+                    std::iter::once(*ch).collect::<String>().as_str(),
                     *x,
                     *y,
                     TextParams {
@@ -200,10 +357,33 @@ pub async fn render(graphics: &[Graphic], font: &Font, textures: &mut TextureCac
                     },
                 );
             },
+            Graphic::Clickable { .. } => {},
         }
     }
 }
 
+fn draw_polyline(points: &[(f32, f32)], thickness: f32, dash: Option<(f32, f32)>, color: Color) {
+    match dash {
+        Some(dash) => {
+            for ((x1, y1), (x2, y2)) in dashed_segments(points, dash) {
+                draw_line(x1, y1, x2, y2, thickness, color);
+            }
+        },
+        None => {
+            for w in points.windows(2) {
+                draw_line(w[0].0, w[0].1, w[1].0, w[1].1, thickness, color);
+            }
+
+            // It fills the gaps at the joints.
+            if thickness > 2.0 {
+                for (x, y) in points.iter() {
+                    draw_circle(*x, *y, thickness / 2.0, color);
+                }
+            }
+        },
+    }
+}
+
 pub fn hide_off_screen(graphics: &mut Vec<Graphic>, screen_width: f32, screen_height: f32) {
     graphics.push(Graphic::Rect {
         x: -400.0,
@@ -242,3 +422,31 @@ pub fn hide_off_screen(graphics: &mut Vec<Graphic>, screen_width: f32, screen_he
         color: Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 },
     });
 }
+
+/// It draws `s` in a single line, one `Graphic::Char` per character. `y` is the baseline.
+/// Spaces are skipped.
+pub(crate) fn push_text(graphics: &mut Vec<Graphic>, s: &str, x: f32, y: f32, size: f32, color: Color) {
+    for (i, ch) in s.chars().enumerate() {
+        if ch != ' ' {
+            graphics.push(Graphic::Char { ch, x: x + i as f32 * size * 0.55, y, size, color });
+        }
+    }
+}
+
+/// If `s` is longer than `max_len` characters, it cuts `s` and appends "...".
+pub(crate) fn truncate(s: &str, max_len: usize) -> String {
+    if s.chars().count() > max_len {
+        format!("{}...", s.chars().take(max_len.saturating_sub(3)).collect::<String>())
+    } else {
+        s.to_string()
+    }
+}
+
+pub(crate) fn flag_color(flag: EntryFlag) -> Color {
+    match flag {
+        EntryFlag::Red => RED,
+        EntryFlag::Green => GREEN,
+        EntryFlag::Blue => BLUE,
+        EntryFlag::None => GRAY,
+    }
+}
@@ -0,0 +1,359 @@
+use macroquad::color::Color;
+
+/// A string with ANSI escape sequences removed, and the style of each character.
+/// `colors`, `backgrounds` and `underlines` have the same length as `text.chars()`.
+pub struct AnsiText {
+    pub text: String,
+    pub colors: Vec<Color>,
+    pub backgrounds: Vec<Option<Color>>,
+    pub underlines: Vec<bool>,
+}
+
+#[derive(Clone, Copy)]
+struct Style {
+    // `None` is the default color.
+    fg: Option<Color>,
+    bg: Option<Color>,
+
+    // If the foreground is one of the 8 basic colors, bold text uses the bright variant.
+    // That's what most terminals do.
+    fg_index: Option<u8>,
+    bold: bool,
+    dim: bool,
+    underline: bool,
+    inverse: bool,
+}
+
+impl Style {
+    fn reset() -> Style {
+        Style {
+            fg: None,
+            bg: None,
+            fg_index: None,
+            bold: false,
+            dim: false,
+            underline: false,
+            inverse: false,
+        }
+    }
+
+    fn resolve(&self, default_color: Color) -> (Color, Option<Color>) {
+        let mut fg = match (self.fg_index, self.bold) {
+            (Some(i @ 0..=7), true) => palette(i + 8),
+            _ => self.fg.unwrap_or(default_color),
+        };
+
+        if self.dim {
+            fg = Color { r: fg.r * 0.6, g: fg.g * 0.6, b: fg.b * 0.6, a: fg.a };
+        }
+
+        if self.inverse {
+            (self.bg.unwrap_or(Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 }), Some(fg))
+        }
+
+        else {
+            (fg, self.bg)
+        }
+    }
+}
+
+enum ParseState {
+    Text,
+
+    // right after ESC
+    Escape,
+
+    // `ESC [`, the buffer has the parameter bytes
+    Csi(String),
+
+    // `ESC ]`, it ends with BEL or `ESC \`
+    Osc { escaped: bool },
+
+    // `ESC` followed by intermediate bytes (e.g. `ESC ( B`)
+    Intermediate,
+}
+
+/// It parses SGR sequences (colors, bold, dim, underline, inverse and reset) and
+/// strips all the other escape sequences (cursor movements, OSC, ...).
+pub fn parse_ansi(s: &str, default_color: Color) -> AnsiText {
+    let mut result = AnsiText {
+        text: String::with_capacity(s.len()),
+        colors: vec![],
+        backgrounds: vec![],
+        underlines: vec![],
+    };
+    let mut style = Style::reset();
+    let mut state = ParseState::Text;
+
+    for ch in s.chars() {
+        match &mut state {
+            ParseState::Text => match ch {
+                '\u{1b}' => {
+                    state = ParseState::Escape;
+                },
+                // Other control characters are not visible in a terminal, so are they in shev.
+                '\n' | '\t' => {
+                    push_char(&mut result, ch, &style, default_color);
+                },
+                _ if ch.is_control() => {},
+                _ => {
+                    push_char(&mut result, ch, &style, default_color);
+                },
+            },
+            ParseState::Escape => match ch {
+                '[' => {
+                    state = ParseState::Csi(String::new());
+                },
+                ']' => {
+                    state = ParseState::Osc { escaped: false };
+                },
+                ' '..='/' => {
+                    state = ParseState::Intermediate;
+                },
+                _ => {
+                    state = ParseState::Text;
+                },
+            },
+            ParseState::Csi(params) => match ch {
+                // parameter bytes and intermediate bytes
+                '0'..='?' | ' '..='/' => {
+                    params.push(ch);
+                },
+                // final byte
+                '@'..='~' => {
+                    if ch == 'm' {
+                        apply_sgr(&mut style, params);
+                    }
+
+                    state = ParseState::Text;
+                },
+                // malformed sequence
+                _ => {
+                    state = ParseState::Text;
+                },
+            },
+            ParseState::Osc { escaped } => match ch {
+                '\u{7}' => {
+                    state = ParseState::Text;
+                },
+                '\\' if *escaped => {
+                    state = ParseState::Text;
+                },
+                '\u{1b}' => {
+                    *escaped = true;
+                },
+                _ => {
+                    *escaped = false;
+                },
+            },
+            ParseState::Intermediate => match ch {
+                ' '..='/' => {},
+                _ => {
+                    state = ParseState::Text;
+                },
+            },
+        }
+    }
+
+    result
+}
+
+fn push_char(result: &mut AnsiText, ch: char, style: &Style, default_color: Color) {
+    let (fg, bg) = style.resolve(default_color);
+    result.text.push(ch);
+    result.colors.push(fg);
+    result.backgrounds.push(bg);
+    result.underlines.push(style.underline);
+}
+
+fn apply_sgr(style: &mut Style, params: &str) {
+    // `ESC [ m` is the same as `ESC [ 0 m`. An empty parameter is 0.
+    // Some terminals use ':' to separate sub-parameters (e.g. `38:2::255:0:0`),
+    // so each parameter is a list of sub-parameters.
+    let params: Vec<Vec<u32>> = params.split(';').map(
+        |p| p.split(':').map(|n| n.parse::<u32>().unwrap_or(0)).collect()
+    ).collect();
+    let mut i = 0;
+
+    while i < params.len() {
+        match params[i][0] {
+            0 => { *style = Style::reset(); },
+            1 => { style.bold = true; },
+            2 => { style.dim = true; },
+            4 => { style.underline = true; },
+            7 => { style.inverse = true; },
+            21 | 22 => {
+                style.bold = false;
+                style.dim = false;
+            },
+            24 => { style.underline = false; },
+            27 => { style.inverse = false; },
+            n @ 30..=37 => {
+                style.fg = Some(palette(n as u8 - 30));
+                style.fg_index = Some(n as u8 - 30);
+            },
+            n @ 90..=97 => {
+                style.fg = Some(palette(n as u8 - 90 + 8));
+                style.fg_index = Some(n as u8 - 90 + 8);
+            },
+            39 => {
+                style.fg = None;
+                style.fg_index = None;
+            },
+            n @ 40..=47 => { style.bg = Some(palette(n as u8 - 40)); },
+            n @ 100..=107 => { style.bg = Some(palette(n as u8 - 100 + 8)); },
+            49 => { style.bg = None; },
+            n @ (38 | 48) => {
+                let color = if params[i].len() > 1 {
+                    // `38:5:n`, `38:2:r:g:b` or `38:2:color_space:r:g:b`
+                    extended_color(&params[i][1..])
+                }
+
+                else {
+                    // `38;5;n` or `38;2;r;g;b`
+                    let args: Vec<u32> = params[(i + 1)..].iter().take(4).map(|p| p[0]).collect();
+                    let color = extended_color(&args);
+
+                    match args.first() {
+                        Some(5) => { i += 2; },
+                        Some(2) => { i += 4; },
+                        _ => {},
+                    }
+
+                    color
+                };
+
+                if let Some(color) = color {
+                    if n == 38 {
+                        style.fg = Some(color);
+                        style.fg_index = None;
+                    }
+
+                    else {
+                        style.bg = Some(color);
+                    }
+                }
+            },
+            // italic, blink, hidden, strikethrough, fonts, ...
+            // shev cannot render these, so it just ignores them.
+            _ => {},
+        }
+
+        i += 1;
+    }
+}
+
+fn extended_color(args: &[u32]) -> Option<Color> {
+    let rgb = match args {
+        [5, n, ..] => { return Some(palette_256((*n).min(255) as u8)); },
+        [2, _, r, g, b] => [*r, *g, *b],
+        [2, r, g, b] => [*r, *g, *b],
+        _ => { return None; },
+    };
+
+    Some(Color {
+        r: rgb[0].min(255) as f32 / 255.0,
+        g: rgb[1].min(255) as f32 / 255.0,
+        b: rgb[2].min(255) as f32 / 255.0,
+        a: 1.0,
+    })
+}
+
+fn palette(index: u8) -> Color {
+    let (r, g, b) = match index {
+        0 => (0.0, 0.0, 0.0),
+        1 => (0.75, 0.25, 0.25),
+        2 => (0.25, 0.75, 0.25),
+        3 => (0.75, 0.75, 0.25),
+        4 => (0.25, 0.25, 0.75),
+        5 => (0.75, 0.25, 0.75),
+        6 => (0.25, 0.75, 0.75),
+        7 => (0.8, 0.8, 0.8),
+        8 => (0.5, 0.5, 0.5),
+        9 => (1.0, 0.4, 0.4),
+        10 => (0.4, 1.0, 0.4),
+        11 => (1.0, 1.0, 0.4),
+        12 => (0.4, 0.4, 1.0),
+        13 => (1.0, 0.4, 1.0),
+        14 => (0.4, 1.0, 1.0),
+        _ => (1.0, 1.0, 1.0),
+    };
+
+    Color { r, g, b, a: 1.0 }
+}
+
+fn palette_256(index: u8) -> Color {
+    match index {
+        0..=15 => palette(index),
+        16..=231 => {
+            let levels = [0.0, 95.0, 135.0, 175.0, 215.0, 255.0];
+            let i = (index - 16) as usize;
+
+            Color {
+                r: levels[i / 36] / 255.0,
+                g: levels[(i / 6) % 6] / 255.0,
+                b: levels[i % 6] / 255.0,
+                a: 1.0,
+            }
+        },
+        232..=255 => {
+            let v = (8.0 + (index - 232) as f32 * 10.0) / 255.0;
+            Color { r: v, g: v, b: v, a: 1.0 }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT: Color = Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
+
+    #[test]
+    fn plain_text() {
+        let text = parse_ansi("abc\n\td", DEFAULT);
+        assert_eq!(text.text, "abc\n\td");
+        assert_eq!(text.colors, vec![DEFAULT; 6]);
+        assert_eq!(text.backgrounds, vec![None; 6]);
+        assert_eq!(text.underlines, vec![false; 6]);
+    }
+
+    #[test]
+    fn basic_colors_and_reset() {
+        let text = parse_ansi("\x1b[31mr\x1b[0mx\x1b[42;4mg\x1b[mx", DEFAULT);
+        assert_eq!(text.text, "rxgx");
+        assert_eq!(text.colors, vec![palette(1), DEFAULT, DEFAULT, DEFAULT]);
+        assert_eq!(text.backgrounds, vec![None, None, Some(palette(2)), None]);
+        assert_eq!(text.underlines, vec![false, false, true, false]);
+    }
+
+    #[test]
+    fn bold_uses_bright_colors() {
+        let text = parse_ansi("\x1b[1;32ma\x1b[22mb\x1b[92mc", DEFAULT);
+        assert_eq!(text.colors, vec![palette(10), palette(2), palette(10)]);
+    }
+
+    #[test]
+    fn extended_colors() {
+        let text = parse_ansi("\x1b[38;5;196ma\x1b[38;2;0;128;255mb\x1b[38:2::255:0:0mc\x1b[48;5;3md", DEFAULT);
+        assert_eq!(text.text, "abcd");
+        assert_eq!(text.colors[0], Color { r: 1.0, g: 0.0, b: 0.0, a: 1.0 });
+        assert_eq!(text.colors[1], Color { r: 0.0, g: 128.0 / 255.0, b: 1.0, a: 1.0 });
+        assert_eq!(text.colors[2], Color { r: 1.0, g: 0.0, b: 0.0, a: 1.0 });
+        assert_eq!(text.backgrounds[3], Some(palette(3)));
+    }
+
+    #[test]
+    fn inverse() {
+        let text = parse_ansi("\x1b[7ma", DEFAULT);
+        assert_eq!(text.colors, vec![Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 }]);
+        assert_eq!(text.backgrounds, vec![Some(DEFAULT)]);
+    }
+
+    #[test]
+    fn other_sequences_are_stripped() {
+        let text = parse_ansi("\x1b[2Ka\x1b]0;title\x07b\x1b]8;;http://x\x1b\\c\x1b(Bd\r\u{7}e", DEFAULT);
+        assert_eq!(text.text, "abcde");
+        assert_eq!(text.colors.len(), 5);
+    }
+}
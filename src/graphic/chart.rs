@@ -0,0 +1,596 @@
+use super::{GRAY, Graphic, push_text};
+use macroquad::color::Color;
+
+const AXIS: Color = Color { r: 0.7, g: 0.7, b: 0.7, a: 1.0 };
+const GRID: Color = Color { r: 0.3, g: 0.3, b: 0.3, a: 1.0 };
+const LABEL: Color = Color { r: 0.85, g: 0.85, b: 0.85, a: 1.0 };
+const TITLE: Color = Color { r: 0.95, g: 0.8, b: 0.4, a: 1.0 };
+
+/// Series colors, in order.
+const PALETTE: [Color; 6] = [
+    Color { r: 0.4, g: 0.6, b: 1.0, a: 1.0 },
+    Color { r: 0.95, g: 0.6, b: 0.3, a: 1.0 },
+    Color { r: 0.4, g: 0.8, b: 0.5, a: 1.0 },
+    Color { r: 0.85, g: 0.45, b: 0.85, a: 1.0 },
+    Color { r: 0.9, g: 0.8, b: 0.35, a: 1.0 },
+    Color { r: 0.4, g: 0.8, b: 0.8, a: 1.0 },
+];
+
+/// It draws a chart: axes, ticks, grid lines, labels, a legend and the data.
+///
+/// - `Chart::bar`: one bar per value. If there are multiple series, the bars are grouped.
+/// - `Chart::line`: one line per series, from left to right.
+/// - `Chart::histogram`: it counts how many values of the first series fall into each bin.
+///
+/// The default rect fills the canvas (900x600) with a small margin. The legend is drawn
+/// if there're more than 1 series.
+pub struct Chart {
+    kind: ChartKind,
+    rect: [f32; 4],
+    font_size: f32,
+    title: Option<String>,
+    x_label: Option<String>,
+    y_label: Option<String>,
+
+    /// Labels of the x axis. If it's empty, the values are numbered from 1.
+    /// Histograms ignore this and use the edges of the bins.
+    categories: Vec<String>,
+    series: Vec<Series>,
+
+    /// If it's not set, it's from min(0, min value) to max value.
+    y_range: Option<(f32, f32)>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ChartKind {
+    Bar,
+    Line,
+    Histogram { bins: usize },
+}
+
+struct Series {
+    name: String,
+    values: Vec<f32>,
+    color: Color,
+}
+
+impl Chart {
+    pub fn bar() -> Chart {
+        Chart::new(ChartKind::Bar)
+    }
+
+    pub fn line() -> Chart {
+        Chart::new(ChartKind::Line)
+    }
+
+    pub fn histogram(bins: usize) -> Chart {
+        Chart::new(ChartKind::Histogram { bins: bins.max(1) })
+    }
+
+    /// A line chart of values over runs, e.g. the runtime of a test case over the last 30 runs.
+    /// `runs` are the names of the runs, from the oldest to the newest, and each of `series` has
+    /// a value per run (NaN if the run doesn't have the value). Only the last `last` runs are drawn.
+    pub fn runs(runs: &[&str], series: &[(&str, Vec<f32>)], last: usize) -> Chart {
+        let skip = runs.len().saturating_sub(last);
+        let mut chart = Chart::line();
+        chart.with_categories(&runs[skip..]).with_axis_labels(Some("run"), None);
+
+        for (name, values) in series.iter() {
+            chart.add_series(name, &values[skip.min(values.len())..]);
+        }
+
+        chart
+    }
+
+    fn new(kind: ChartKind) -> Chart {
+        Chart {
+            kind,
+            rect: [20.0, 20.0, 860.0, 560.0],
+            font_size: 14.0,
+            title: None,
+            x_label: None,
+            y_label: None,
+            categories: vec![],
+            series: vec![],
+            y_range: None,
+        }
+    }
+
+    pub fn with_rect(&mut self, rect: [f32; 4]) -> &mut Self {
+        self.rect = rect;
+        self
+    }
+
+    pub fn with_font_size(&mut self, font_size: f32) -> &mut Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn with_title(&mut self, title: &str) -> &mut Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn with_axis_labels(&mut self, x_label: Option<&str>, y_label: Option<&str>) -> &mut Self {
+        self.x_label = x_label.map(|l| l.to_string());
+        self.y_label = y_label.map(|l| l.to_string());
+        self
+    }
+
+    pub fn with_categories(&mut self, categories: &[&str]) -> &mut Self {
+        self.categories = categories.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// If `min` is greater than `max`, they're swapped.
+    pub fn with_y_range(&mut self, min: f32, max: f32) -> &mut Self {
+        let (min, max) = if min > max { (max, min) } else { (min, max) };
+        self.y_range = Some(if min == max { (min, min + 1.0) } else { (min, max) });
+        self
+    }
+
+    /// NaN values are skipped (a gap in a line chart, no bar in a bar chart).
+    pub fn add_series(&mut self, name: &str, values: &[f32]) -> &mut Self {
+        let color = PALETTE[self.series.len() % PALETTE.len()];
+        self.add_series_with_color(name, values, color)
+    }
+
+    pub fn add_series_with_color(&mut self, name: &str, values: &[f32], color: Color) -> &mut Self {
+        self.series.push(Series {
+            name: name.to_string(),
+            values: values.to_vec(),
+            color,
+        });
+        self
+    }
+
+    pub fn render(&self) -> Vec<Graphic> {
+        let [x, y, w, h] = self.rect;
+        let mut graphics = vec![];
+        let mut top = y;
+
+        if let Some(title) = &self.title {
+            let size = self.font_size * 1.3;
+            push_text(&mut graphics, title, x, top + size, size, TITLE);
+            top += size * 1.6;
+        }
+
+        if self.series.len() > 1 {
+            top = self.legend(&mut graphics, x, top, w);
+        }
+
+        if let Some(y_label) = &self.y_label {
+            push_text(&mut graphics, y_label, x, top + self.font_size, self.font_size, LABEL);
+            top += self.font_size * 1.6;
+        }
+
+        // (x position of the left edge, height) of each bar, or (x, y) of each point
+        let (bars, x_ticks) = self.data();
+        let (y_min, y_max) = self.y_range.unwrap_or_else(|| {
+            let values = self.plotted_values(&bars);
+            let min = values.iter().copied().fold(0.0, f32::min);
+            let max = values.iter().copied().fold(0.0, f32::max);
+
+            if min == max { (min, min + 1.0) } else { (min, max) }
+        });
+        let (y_ticks, y_step) = nice_ticks(y_min, y_max, ((h / 80.0) as usize).max(2));
+        let (y_min, y_max) = (
+            y_ticks.first().copied().unwrap_or(y_min).min(y_min),
+            y_ticks.last().copied().unwrap_or(y_max).max(y_max),
+        );
+        let y_tick_labels: Vec<String> = y_ticks.iter().map(|t| format_number(*t, y_step)).collect();
+        let y_tick_width = y_tick_labels.iter().map(|l| l.chars().count()).max().unwrap_or(1) as f32 * self.char_width();
+
+        let plot_left = x + y_tick_width + 8.0;
+        let plot_right = x + w;
+        let mut plot_bottom = y + h - self.font_size * 1.6;
+
+        if self.x_label.is_some() {
+            plot_bottom -= self.font_size * 1.6;
+        }
+
+        let plot = Plot {
+            left: plot_left,
+            right: plot_right,
+            top,
+            bottom: plot_bottom,
+            y_min,
+            y_max,
+        };
+
+        if plot.right - plot.left < 1.0 || plot.bottom - plot.top < 1.0 {
+            return graphics;
+        }
+
+        // grid and y ticks
+        for (tick, label) in y_ticks.iter().zip(y_tick_labels.iter()) {
+            let tick_y = plot.y(*tick);
+
+            if *tick != y_min {
+                graphics.push(Graphic::Line {
+                    p1: (plot.left, tick_y),
+                    p2: (plot.right, tick_y),
+                    thickness: 1.0,
+                    dash: Some((4.0, 4.0)),
+                    color: GRID,
+                });
+            }
+
+            let label_width = label.chars().count() as f32 * self.char_width();
+            push_text(&mut graphics, label, plot.left - 8.0 - label_width, tick_y + self.font_size * 0.35, self.font_size, LABEL);
+        }
+
+        // data
+        if self.series.iter().all(|s| s.values.is_empty()) {
+            let message = "no data";
+            let message_width = message.chars().count() as f32 * self.char_width();
+            push_text(
+                &mut graphics,
+                message,
+                (plot.left + plot.right - message_width) / 2.0,
+                (plot.top + plot.bottom) / 2.0,
+                self.font_size,
+                GRAY,
+            );
+        }
+
+        else {
+            match self.kind {
+                ChartKind::Bar | ChartKind::Histogram { .. } => self.bars(&mut graphics, &plot, &bars),
+                ChartKind::Line => self.lines(&mut graphics, &plot),
+            }
+        }
+
+        // x ticks
+        let x_tick_count = x_ticks.len().max(1);
+        let max_label_width = x_ticks.iter().map(|(_, l)| l.chars().count()).max().unwrap_or(1) as f32 * self.char_width();
+        let skip = ((max_label_width + self.char_width() * 2.0) * x_tick_count as f32 / (plot.right - plot.left)).ceil().max(1.0) as usize;
+
+        for (i, (ratio, label)) in x_ticks.iter().enumerate() {
+            let tick_x = plot.left + ratio * (plot.right - plot.left);
+
+            graphics.push(Graphic::Line {
+                p1: (tick_x, plot.bottom),
+                p2: (tick_x, plot.bottom + 4.0),
+                thickness: 1.0,
+                dash: None,
+                color: AXIS,
+            });
+
+            if i % skip == 0 {
+                let label_width = label.chars().count() as f32 * self.char_width();
+                push_text(&mut graphics, label, tick_x - label_width / 2.0, plot.bottom + self.font_size * 1.3, self.font_size, LABEL);
+            }
+        }
+
+        // axes
+        graphics.push(Graphic::Polyline {
+            points: vec![(plot.left, plot.top), (plot.left, plot.bottom), (plot.right, plot.bottom)],
+            thickness: 1.5,
+            dash: None,
+            color: AXIS,
+        });
+
+        // the zero line, if the chart has negative values
+        if y_min < 0.0 && y_max > 0.0 {
+            graphics.push(Graphic::Line {
+                p1: (plot.left, plot.y(0.0)),
+                p2: (plot.right, plot.y(0.0)),
+                thickness: 1.0,
+                dash: None,
+                color: AXIS,
+            });
+        }
+
+        if let Some(x_label) = &self.x_label {
+            let label_width = x_label.chars().count() as f32 * self.char_width();
+            push_text(
+                &mut graphics,
+                x_label,
+                (plot.left + plot.right - label_width) / 2.0,
+                y + h - self.font_size * 0.3,
+                self.font_size,
+                LABEL,
+            );
+        }
+
+        graphics
+    }
+
+    // It returns the bars of bar charts and histograms (series index, left edge ratio,
+    // width ratio, value), and the x ticks (ratio, label).
+    #[allow(clippy::type_complexity)]
+    fn data(&self) -> (Vec<(usize, f32, f32, f32)>, Vec<(f32, String)>) {
+        let mut bars = vec![];
+        let mut x_ticks = vec![];
+
+        match self.kind {
+            ChartKind::Bar => {
+                let count = self.point_count();
+                let series_count = self.series.len().max(1);
+                let group_width = 1.0 / count.max(1) as f32;
+                let bar_width = group_width * 0.8 / series_count as f32;
+
+                for i in 0..count {
+                    for (j, series) in self.series.iter().enumerate() {
+                        if let Some(value) = series.values.get(i) && !value.is_nan() {
+                            bars.push((j, group_width * (i as f32 + 0.1) + bar_width * j as f32, bar_width, *value));
+                        }
+                    }
+
+                    x_ticks.push((group_width * (i as f32 + 0.5), self.category(i)));
+                }
+            },
+            ChartKind::Line => {
+                let count = self.point_count();
+
+                for i in 0..count {
+                    x_ticks.push((line_x(i, count), self.category(i)));
+                }
+            },
+            ChartKind::Histogram { bins } => {
+                let values: Vec<f32> = self.series.first().map(
+                    |s| s.values.iter().copied().filter(|v| v.is_finite()).collect()
+                ).unwrap_or_default();
+
+                if values.is_empty() {
+                    return (bars, x_ticks);
+                }
+
+                let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+                let mut max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+                if min == max {
+                    max = min + 1.0;
+                }
+
+                let bin_size = (max - min) / bins as f32;
+                let mut counts = vec![0; bins];
+
+                for value in values.iter() {
+                    counts[(((value - min) / bin_size) as usize).min(bins - 1)] += 1;
+                }
+
+                for (i, count) in counts.iter().enumerate() {
+                    bars.push((0, i as f32 / bins as f32, 1.0 / bins as f32, *count as f32));
+                }
+
+                for i in 0..=bins {
+                    x_ticks.push((i as f32 / bins as f32, format_number(min + bin_size * i as f32, bin_size)));
+                }
+            },
+        }
+
+        (bars, x_ticks)
+    }
+
+    fn plotted_values(&self, bars: &[(usize, f32, f32, f32)]) -> Vec<f32> {
+        match self.kind {
+            ChartKind::Histogram { .. } => bars.iter().map(|(_, _, _, v)| *v).collect(),
+            _ => self.series.iter().flat_map(|s| s.values.iter().copied().filter(|v| v.is_finite())).collect(),
+        }
+    }
+
+    fn bars(&self, graphics: &mut Vec<Graphic>, plot: &Plot, bars: &[(usize, f32, f32, f32)]) {
+        let width = plot.right - plot.left;
+        let zero = plot.y(0.0_f32.clamp(plot.y_min, plot.y_max));
+
+        for (series, left, bar_width, value) in bars.iter() {
+            let value_y = plot.y(value.clamp(plot.y_min, plot.y_max));
+            let color = self.series.get(*series).map(|s| s.color).unwrap_or(PALETTE[0]);
+            let (bar_x, bar_w) = match self.kind {
+                // histogram bars touch each other
+                ChartKind::Histogram { .. } => (plot.left + left * width + 0.5, (bar_width * width - 1.0).max(1.0)),
+                _ => (plot.left + left * width, (bar_width * width).max(1.0)),
+            };
+
+            graphics.push(Graphic::Rect {
+                x: bar_x,
+                y: value_y.min(zero),
+                w: bar_w,
+                h: (value_y - zero).abs(),
+                radius: None,
+                thickness: None,
+                color,
+            });
+        }
+    }
+
+    fn lines(&self, graphics: &mut Vec<Graphic>, plot: &Plot) {
+        let count = self.point_count();
+        let width = plot.right - plot.left;
+
+        for series in self.series.iter() {
+            let mut points = vec![];
+
+            for (i, value) in series.values.iter().enumerate() {
+                // A NaN breaks the line.
+                if !value.is_finite() {
+                    if points.len() > 1 {
+                        graphics.push(polyline(std::mem::take(&mut points), series.color));
+                    }
+
+                    points.clear();
+                    continue;
+                }
+
+                points.push((plot.left + line_x(i, count) * width, plot.y(value.clamp(plot.y_min, plot.y_max))));
+            }
+
+            if points.len() > 1 {
+                graphics.push(polyline(points.clone(), series.color));
+            }
+
+            // dots on the points, unless there're too many of them
+            if count <= 60 {
+                for (i, value) in series.values.iter().enumerate() {
+                    if value.is_finite() {
+                        graphics.push(Graphic::Ellipse {
+                            x: plot.left + line_x(i, count) * width,
+                            y: plot.y(value.clamp(plot.y_min, plot.y_max)),
+                            rx: 3.0,
+                            ry: 3.0,
+                            thickness: None,
+                            color: series.color,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // It returns the y coordinate of the bottom of the legend.
+    fn legend(&self, graphics: &mut Vec<Graphic>, x: f32, y: f32, w: f32) -> f32 {
+        let box_size = self.font_size * 0.8;
+        let mut curr_x = x;
+        let mut curr_y = y;
+
+        for series in self.series.iter() {
+            let item_width = box_size + 6.0 + series.name.chars().count() as f32 * self.char_width() + 20.0;
+
+            if curr_x + item_width > x + w && curr_x > x {
+                curr_x = x;
+                curr_y += self.font_size * 1.4;
+            }
+
+            graphics.push(Graphic::Rect {
+                x: curr_x,
+                y: curr_y + (self.font_size - box_size) / 2.0,
+                w: box_size,
+                h: box_size,
+                radius: None,
+                thickness: None,
+                color: series.color,
+            });
+            push_text(graphics, &series.name, curr_x + box_size + 6.0, curr_y + self.font_size * 0.85, self.font_size, LABEL);
+            curr_x += item_width;
+        }
+
+        curr_y + self.font_size * 2.0
+    }
+
+    fn point_count(&self) -> usize {
+        self.series.iter().map(|s| s.values.len()).max().unwrap_or(0).max(self.categories.len())
+    }
+
+    fn category(&self, index: usize) -> String {
+        match self.categories.get(index) {
+            Some(category) => category.to_string(),
+            None => (index + 1).to_string(),
+        }
+    }
+
+    fn char_width(&self) -> f32 {
+        self.font_size * 0.55
+    }
+}
+
+struct Plot {
+    left: f32,
+    right: f32,
+    top: f32,
+    bottom: f32,
+    y_min: f32,
+    y_max: f32,
+}
+
+impl Plot {
+    fn y(&self, value: f32) -> f32 {
+        self.bottom - (value - self.y_min) / (self.y_max - self.y_min) * (self.bottom - self.top)
+    }
+}
+
+// ratio of the x coordinate of the `index`th point of a line chart
+fn line_x(index: usize, count: usize) -> f32 {
+    if count < 2 {
+        0.5
+    }
+
+    else {
+        index as f32 / (count - 1) as f32
+    }
+}
+
+fn polyline(points: Vec<(f32, f32)>, color: Color) -> Graphic {
+    Graphic::Polyline {
+        points,
+        thickness: 2.0,
+        dash: None,
+        color,
+    }
+}
+
+// It returns ticks at round numbers (1, 2 or 5 times a power of 10) that cover `min..=max`,
+// and the step between the ticks.
+fn nice_ticks(min: f32, max: f32, count: usize) -> (Vec<f32>, f32) {
+    if !min.is_finite() || !max.is_finite() || min >= max {
+        return (vec![min], 1.0);
+    }
+
+    let raw_step = (max - min) / count as f32;
+    let magnitude = 10.0_f32.powf(raw_step.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0].iter().map(|m| m * magnitude).find(|s| *s >= raw_step).unwrap_or(magnitude * 10.0);
+    let first = (min / step).floor() as i64;
+    let last = (max / step).ceil() as i64;
+
+    ((first..=last).map(|i| i as f32 * step).collect(), step)
+}
+
+fn format_number(n: f32, step: f32) -> String {
+    let decimals = if step >= 1.0 { 0 } else { (-step.log10()).ceil() as usize };
+    let n = if n.abs() < step * 1e-3 { 0.0 } else { n };
+
+    format!("{n:.decimals$}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticks() {
+        assert_eq!(nice_ticks(0.0, 10.0, 5), (vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0], 2.0));
+        assert_eq!(nice_ticks(0.0, 95.0, 4), (vec![0.0, 50.0, 100.0], 50.0));
+        assert_eq!(nice_ticks(-3.0, 7.0, 10), (vec![-3.0, -2.0, -1.0, 0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0], 1.0));
+
+        let (ticks, step) = nice_ticks(0.13, 0.58, 5);
+        assert!((step - 0.1).abs() < 1e-6);
+        assert_eq!(ticks.len(), 6);
+        assert!(ticks[0] <= 0.13 && ticks[5] >= 0.58);
+    }
+
+    #[test]
+    fn ticks_of_empty_ranges() {
+        assert_eq!(nice_ticks(3.0, 3.0, 5), (vec![3.0], 1.0));
+        assert_eq!(nice_ticks(5.0, 3.0, 5), (vec![5.0], 1.0));
+        assert_eq!(nice_ticks(f32::NAN, 3.0, 5).0.len(), 1);
+        assert_eq!(nice_ticks(0.0, f32::INFINITY, 5).0.len(), 1);
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(format_number(20.0, 5.0), "20");
+        assert_eq!(format_number(0.1 + 0.2, 0.1), "0.3");
+        assert_eq!(format_number(0.25, 0.05), "0.25");
+        assert_eq!(format_number(-1e-9, 0.1), "0.0");
+    }
+
+    #[test]
+    fn y_range() {
+        assert_eq!(Chart::line().with_y_range(0.0, 10.0).y_range, Some((0.0, 10.0)));
+        assert_eq!(Chart::line().with_y_range(10.0, 0.0).y_range, Some((0.0, 10.0)));
+        assert_eq!(Chart::line().with_y_range(3.0, 3.0).y_range, Some((3.0, 4.0)));
+    }
+
+    #[test]
+    fn last_runs() {
+        let runs = (1..=40).map(|i| format!("run-{i}")).collect::<Vec<_>>();
+        let runs = runs.iter().map(|run| run.as_str()).collect::<Vec<_>>();
+        let chart = Chart::runs(&runs, &[("a", (1..=40).map(|i| i as f32).collect()), ("b", vec![1.0; 5])], 30);
+
+        assert_eq!(chart.categories.len(), 30);
+        assert_eq!(chart.categories[0], "run-11");
+        assert_eq!(chart.series[0].values, (11..=40).map(|i| i as f32).collect::<Vec<_>>());
+        assert!(chart.series[1].values.is_empty());
+    }
+}
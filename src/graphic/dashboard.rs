@@ -0,0 +1,239 @@
+use super::{GRAY, Graphic, WHITE, flag_color, push_text};
+use crate::entry::{Entries, EntryFlag};
+use macroquad::color::Color;
+use std::collections::HashMap;
+
+const DARK_GRAY: Color = Color { r: 0.25, g: 0.25, b: 0.25, a: 1.0 };
+const TITLE: Color = Color { r: 0.95, g: 0.8, b: 0.4, a: 1.0 };
+
+// It's the order of the counts and the bars.
+const FLAGS: [EntryFlag; 4] = [EntryFlag::Green, EntryFlag::Red, EntryFlag::Blue, EntryFlag::None];
+
+/// It renders a summary of `entries`: the number of entries per `EntryFlag`, a bar that
+/// shows the proportions, and the same breakdown per category (`Entry::categories`).
+/// An entry with multiple categories is counted in each of them.
+///
+/// The engine shows this on `Command::ToggleDashboard`, but you can also use it in your
+/// own canvas.
+pub fn render_dashboard(entries: &Entries) -> Vec<Graphic> {
+    let mut graphics = vec![];
+    let total = entries.len();
+    let counts = count_flags(entries.iter().map(|e| e.flag));
+
+    push_text(&mut graphics, entries.title.as_ref().unwrap_or(&entries.id), 20.0, 45.0, 21.0, TITLE);
+    push_text(&mut graphics, &format!("{total} entries"), 20.0, 75.0, 16.0, GRAY);
+
+    let mut curr_x = 20.0;
+
+    for (flag, count) in FLAGS.iter().zip(counts.iter()) {
+        if *count == 0 && *flag != EntryFlag::Green && *flag != EntryFlag::Red {
+            continue;
+        }
+
+        graphics.push(Graphic::Ellipse {
+            x: curr_x + 7.0,
+            y: 110.0,
+            rx: 7.0,
+            ry: 7.0,
+            thickness: None,
+            color: flag_color(*flag),
+        });
+        let label = format!("{}: {count} ({})", flag_name(*flag), percent(*count, total));
+        push_text(&mut graphics, &label, curr_x + 20.0, 116.0, 16.0, WHITE);
+        curr_x += 20.0 + label.chars().count() as f32 * 8.8 + 30.0;
+    }
+
+    proportional_bar(&mut graphics, &counts, 20.0, 135.0, 860.0, 24.0);
+
+    let categories = count_categories(entries);
+
+    if categories.is_empty() {
+        return graphics;
+    }
+
+    // the table of categories
+    let name_len = categories.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0).clamp(8, 32);
+    let columns_x = 20.0 + (name_len + 2) as f32 * 7.7;
+    let bar_x = columns_x + 4.0 * 70.0;
+    let mut curr_y = 210.0;
+
+    push_text(&mut graphics, "category", 20.0, curr_y, 14.0, GRAY);
+
+    for (i, header) in ["total", "green", "red", "other"].iter().enumerate() {
+        push_text(&mut graphics, header, columns_x + i as f32 * 70.0, curr_y, 14.0, GRAY);
+    }
+
+    graphics.push(Graphic::Line {
+        p1: (20.0, curr_y + 8.0),
+        p2: (880.0, curr_y + 8.0),
+        thickness: 1.0,
+        dash: None,
+        color: DARK_GRAY,
+    });
+    curr_y += 28.0;
+
+    for (name, counts) in categories.iter().take(200) {
+        let total = counts.iter().sum::<usize>();
+        let name = if name.chars().count() > name_len {
+            format!("{}...", name.chars().take(name_len - 3).collect::<String>())
+        } else {
+            name.to_string()
+        };
+
+        push_text(&mut graphics, &name, 20.0, curr_y, 14.0, WHITE);
+
+        for (i, count) in [total, counts[0], counts[1], counts[2] + counts[3]].iter().enumerate() {
+            push_text(&mut graphics, &count.to_string(), columns_x + i as f32 * 70.0, curr_y, 14.0, WHITE);
+        }
+
+        if bar_x < 780.0 {
+            proportional_bar(&mut graphics, counts, bar_x, curr_y - 11.0, 880.0 - bar_x, 12.0);
+        }
+
+        curr_y += 22.0;
+    }
+
+    if categories.len() > 200 {
+        push_text(&mut graphics, &format!("... and {} more categories", categories.len() - 200), 20.0, curr_y, 14.0, GRAY);
+    }
+
+    graphics
+}
+
+// The counts are in the same order as `FLAGS`.
+fn count_flags(flags: impl Iterator<Item = EntryFlag>) -> [usize; 4] {
+    let mut counts = [0; 4];
+
+    for flag in flags {
+        counts[FLAGS.iter().position(|f| *f == flag).unwrap()] += 1;
+    }
+
+    counts
+}
+
+// It's sorted by the number of entries (descending), then by name.
+fn count_categories(entries: &Entries) -> Vec<(String, [usize; 4])> {
+    let mut flags_by_category: HashMap<&str, Vec<EntryFlag>> = HashMap::new();
+
+    // If no entry has a category, there's nothing to break down.
+    if entries.iter().all(|e| e.categories.is_empty()) {
+        return vec![];
+    }
+
+    for entry in entries.iter() {
+        if entry.categories.is_empty() {
+            flags_by_category.entry("(no category)").or_default().push(entry.flag);
+        }
+
+        for category in entry.categories.iter() {
+            flags_by_category.entry(category.as_str()).or_default().push(entry.flag);
+        }
+    }
+
+    let mut result: Vec<(String, [usize; 4])> = flags_by_category.into_iter().map(
+        |(category, flags)| (category.to_string(), count_flags(flags.into_iter()))
+    ).collect();
+    result.sort_by(|(name1, counts1), (name2, counts2)| {
+        counts2.iter().sum::<usize>().cmp(&counts1.iter().sum::<usize>()).then(name1.cmp(name2))
+    });
+    result
+}
+
+fn proportional_bar(graphics: &mut Vec<Graphic>, counts: &[usize; 4], x: f32, y: f32, w: f32, h: f32) {
+    let total = counts.iter().sum::<usize>();
+
+    if total == 0 {
+        graphics.push(Graphic::Rect { x, y, w, h, radius: None, thickness: None, color: DARK_GRAY });
+        return;
+    }
+
+    let mut curr_x = x;
+
+    for (flag, count) in FLAGS.iter().zip(counts.iter()) {
+        let width = w * *count as f32 / total as f32;
+
+        if width > 0.0 {
+            graphics.push(Graphic::Rect {
+                x: curr_x,
+                y,
+                w: width,
+                h,
+                radius: None,
+                thickness: None,
+                color: flag_color(*flag),
+            });
+        }
+
+        curr_x += width;
+    }
+}
+
+fn percent(count: usize, total: usize) -> String {
+    if total == 0 {
+        String::from("-")
+    } else {
+        format!("{:.1}%", count as f32 * 100.0 / total as f32)
+    }
+}
+
+fn flag_name(flag: EntryFlag) -> &'static str {
+    match flag {
+        EntryFlag::Green => "green",
+        EntryFlag::Red => "red",
+        EntryFlag::Blue => "blue",
+        EntryFlag::None => "no flag",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::Entry;
+
+    fn entries(entries: &[(EntryFlag, &[&str])]) -> Entries {
+        Entries {
+            entries: entries.iter().map(
+                |(flag, categories)| Entry {
+                    flag: *flag,
+                    categories: categories.iter().map(|c| c.to_string()).collect(),
+                    ..Entry::default()
+                }
+            ).collect(),
+            ..Entries::default()
+        }
+    }
+
+    #[test]
+    fn counts() {
+        use EntryFlag::{Blue, Green, None, Red};
+
+        assert_eq!(count_flags([Red, Green, None, Red].into_iter()), [1, 2, 0, 1]);
+        assert_eq!(
+            count_categories(&entries(&[(Green, &["a"]), (Red, &["a", "b"]), (Blue, &[]), (Red, &["c"])])),
+            vec![
+                (String::from("a"), [1, 1, 0, 0]),
+                (String::from("(no category)"), [0, 0, 1, 0]),
+                (String::from("b"), [0, 1, 0, 0]),
+                (String::from("c"), [0, 1, 0, 0]),
+            ],
+        );
+        assert!(count_categories(&entries(&[(Green, &[]), (Red, &[])])).is_empty());
+    }
+
+    #[test]
+    fn bars_and_percents() {
+        let mut graphics = vec![];
+        proportional_bar(&mut graphics, &[1, 3, 0, 0], 10.0, 0.0, 100.0, 5.0);
+
+        let widths = graphics.iter().map(
+            |graphic| match graphic {
+                Graphic::Rect { x, w, .. } => (*x, *w),
+                _ => unreachable!(),
+            }
+        ).collect::<Vec<_>>();
+        assert_eq!(widths, vec![(10.0, 25.0), (35.0, 75.0)]);
+
+        assert_eq!(percent(1, 3), "33.3%");
+        assert_eq!(percent(0, 0), "-");
+    }
+}
@@ -0,0 +1,204 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// It returns the outline of a rounded rect, clockwise, starting from the top-left corner.
+/// The first point is not repeated at the end.
+pub fn rounded_rect_outline(x: f32, y: f32, w: f32, h: f32, r: f32) -> Vec<(f32, f32)> {
+    let r = r.min(w / 2.0).min(h / 2.0).max(0.0);
+    let mut points = Vec::with_capacity(28);
+
+    for (start_angle, (c_x, c_y)) in [
+        (PI, (x + r, y + r)),
+        (PI + FRAC_PI_2, (x + w - r, y + r)),
+        (0.0, (x + w - r, y + h - r)),
+        (FRAC_PI_2, (x + r, y + h - r)),
+    ] {
+        for i in 0..=6 {
+            let angle = start_angle + i as f32 * FRAC_PI_2 / 6.0;
+            points.push((c_x + angle.cos() * r, c_y + angle.sin() * r));
+        }
+    }
+
+    points
+}
+
+/// It splits a polyline into the visible segments of a dashed line.
+/// `dash` is (length of a dash, length of a gap). The pattern continues across the vertices.
+pub fn dashed_segments(points: &[(f32, f32)], (dash, gap): (f32, f32)) -> Vec<((f32, f32), (f32, f32))> {
+    let mut segments = vec![];
+
+    if dash <= 0.0 {
+        return segments;
+    }
+
+    if gap <= 0.0 {
+        return points.windows(2).map(|w| (w[0], w[1])).collect();
+    }
+
+    // how far we are in the current dash (or gap)
+    let mut phase = 0.0;
+    let mut drawing = true;
+
+    for w in points.windows(2) {
+        let ((x1, y1), (x2, y2)) = (w[0], w[1]);
+        let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+
+        if length == 0.0 {
+            continue;
+        }
+
+        let (dx, dy) = ((x2 - x1) / length, (y2 - y1) / length);
+        let mut curr = 0.0;
+
+        while curr < length {
+            let remaining = if drawing { dash } else { gap } - phase;
+            let next = (curr + remaining).min(length);
+
+            if drawing {
+                segments.push(((x1 + dx * curr, y1 + dy * curr), (x1 + dx * next, y1 + dy * next)));
+            }
+
+            if curr + remaining <= length {
+                phase = 0.0;
+                drawing = !drawing;
+            }
+
+            else {
+                phase += next - curr;
+            }
+
+            curr = next;
+        }
+    }
+
+    segments
+}
+
+/// It splits a simple polygon (no self-intersections) into triangles, using ear clipping.
+/// It works with both convex and concave polygons, and with both clockwise and
+/// counter-clockwise vertices.
+pub fn triangulate(points: &[(f32, f32)]) -> Vec<[(f32, f32); 3]> {
+    let mut triangles = vec![];
+
+    if points.len() < 3 {
+        return triangles;
+    }
+
+    let signed_area = points.iter().zip(points.iter().cycle().skip(1)).map(
+        |((x1, y1), (x2, y2))| x1 * y2 - x2 * y1
+    ).sum::<f32>();
+    let mut indices: Vec<usize> = (0..points.len()).collect();
+
+    if signed_area < 0.0 {
+        indices.reverse();
+    }
+
+    // It's a counter-clockwise polygon now (in the y-up coordinate system).
+    while indices.len() > 3 {
+        let n = indices.len();
+        let mut ear = None;
+
+        for i in 0..n {
+            let (a, b, c) = (points[indices[(i + n - 1) % n]], points[indices[i]], points[indices[(i + 1) % n]]);
+
+            if cross(a, b, c) <= 0.0 {
+                continue;
+            }
+
+            let contains_other = indices.iter().any(|j| {
+                let p = points[*j];
+                p != a && p != b && p != c && in_triangle(p, a, b, c)
+            });
+
+            if !contains_other {
+                ear = Some(i);
+                break;
+            }
+        }
+
+        // The polygon is degenerate (e.g. self-intersecting). Let's just cut any vertex.
+        let i = ear.unwrap_or(0);
+        triangles.push([points[indices[(i + n - 1) % n]], points[indices[i]], points[indices[(i + 1) % n]]]);
+        indices.remove(i);
+    }
+
+    triangles.push([points[indices[0]], points[indices[1]], points[indices[2]]]);
+    triangles
+}
+
+fn cross((ax, ay): (f32, f32), (bx, by): (f32, f32), (cx, cy): (f32, f32)) -> f32 {
+    (bx - ax) * (cy - ay) - (by - ay) * (cx - ax)
+}
+
+fn in_triangle(p: (f32, f32), a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> bool {
+    cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(points: &[(f32, f32)]) -> f32 {
+        points.iter().zip(points.iter().cycle().skip(1)).map(
+            |((x1, y1), (x2, y2))| x1 * y2 - x2 * y1
+        ).sum::<f32>().abs() / 2.0
+    }
+
+    // Each triangle must be inside the polygon, so the areas add up only if
+    // none of them overlaps or sticks out.
+    fn check_triangulation(points: &[(f32, f32)]) {
+        let triangles = triangulate(points);
+        assert_eq!(triangles.len(), points.len() - 2);
+
+        let total = triangles.iter().map(|t| area(t)).sum::<f32>();
+        assert!((total - area(points)).abs() < area(points) * 1e-5, "{total} != {}", area(points));
+    }
+
+    #[test]
+    fn triangulate_convex() {
+        check_triangulation(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]);
+        check_triangulation(&rounded_rect_outline(0.0, 0.0, 100.0, 50.0, 10.0));
+    }
+
+    #[test]
+    fn triangulate_concave() {
+        // an L shape, in both orders
+        let mut points = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 4.0), (4.0, 4.0), (4.0, 10.0), (0.0, 10.0)];
+        check_triangulation(&points);
+        points.reverse();
+        check_triangulation(&points);
+
+        // a star
+        let star = (0..10).map(
+            |i| {
+                let angle = i as f32 * PI / 5.0;
+                let r = if i % 2 == 0 { 10.0 } else { 4.0 };
+                (angle.cos() * r, angle.sin() * r)
+            }
+        ).collect::<Vec<_>>();
+        check_triangulation(&star);
+    }
+
+    #[test]
+    fn triangulate_too_few_points() {
+        assert!(triangulate(&[]).is_empty());
+        assert!(triangulate(&[(0.0, 0.0), (1.0, 1.0)]).is_empty());
+        assert_eq!(triangulate(&[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]).len(), 1);
+    }
+
+    #[test]
+    fn dashes() {
+        assert_eq!(
+            dashed_segments(&[(0.0, 0.0), (10.0, 0.0)], (3.0, 2.0)),
+            vec![((0.0, 0.0), (3.0, 0.0)), ((5.0, 0.0), (8.0, 0.0))],
+        );
+
+        // The dash continues across the vertex.
+        assert_eq!(
+            dashed_segments(&[(0.0, 0.0), (2.0, 0.0), (2.0, 10.0)], (4.0, 2.0)),
+            vec![((0.0, 0.0), (2.0, 0.0)), ((2.0, 0.0), (2.0, 2.0)), ((2.0, 4.0), (2.0, 8.0))],
+        );
+
+        assert_eq!(dashed_segments(&[(0.0, 0.0), (1.0, 0.0)], (1.0, 0.0)), vec![((0.0, 0.0), (1.0, 0.0))]);
+        assert!(dashed_segments(&[(0.0, 0.0), (1.0, 0.0)], (0.0, 1.0)).is_empty());
+    }
+}
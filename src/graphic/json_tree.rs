@@ -0,0 +1,394 @@
+use super::{ClickAction, Graphic};
+use crate::entry::{CanvasState, Entry, EntryState};
+use macroquad::color::Color;
+use serde_json::Value;
+
+const KEY: Color = Color { r: 0.5, g: 0.7, b: 1.0, a: 1.0 };
+const STRING: Color = Color { r: 0.6, g: 0.85, b: 0.5, a: 1.0 };
+const NUMBER: Color = Color { r: 0.9, g: 0.7, b: 0.4, a: 1.0 };
+const BOOL: Color = Color { r: 0.8, g: 0.5, b: 0.9, a: 1.0 };
+const GRAY: Color = Color { r: 0.5, g: 0.5, b: 0.5, a: 1.0 };
+const PUNCT: Color = Color { r: 0.85, g: 0.85, b: 0.85, a: 1.0 };
+const CONTROL: Color = Color { r: 0.4, g: 0.6, b: 1.0, a: 1.0 };
+
+/// A collapsible tree view of a JSON value. Click an object or an array to fold/unfold it,
+/// and click `[+]` to see the rest of a long string.
+///
+/// The tree is interactive, so you have to render it in `Entries::render_stateful_canvas`.
+/// If `Entry::content` is JSON, you can just use `render_json_tree`.
+///
+/// By default, the first 2 levels are unfolded, unless they're very big.
+pub struct JsonTree {
+    value: Value,
+    font_size: f32,
+    rect: [f32; 4],
+}
+
+/// It renders `entry.content` as a `JsonTree`. You can use this function
+/// as `Entries::render_stateful_canvas`.
+pub fn render_json_tree(entry: &Entry, _: EntryState, canvas_state: &CanvasState) -> Result<Vec<Graphic>, String> {
+    let content = entry.content.as_ref().ok_or_else(|| String::from("This entry has no content."))?;
+
+    Ok(JsonTree::new(content, 16.0, [20.0, 20.0, 860.0, 100000.0])?.render(canvas_state))
+}
+
+impl JsonTree {
+    pub fn new(json: &str, font_size: f32, rect: [f32; 4]) -> Result<JsonTree, String> {
+        Ok(JsonTree {
+            value: serde_json::from_str(json).map_err(|e| format!("{e:?}"))?,
+            font_size,
+            rect,
+        })
+    }
+
+    pub fn from_value(value: Value, font_size: f32, rect: [f32; 4]) -> JsonTree {
+        JsonTree {
+            value,
+            font_size,
+            rect,
+        }
+    }
+
+    pub fn render(&self, canvas_state: &CanvasState) -> Vec<Graphic> {
+        let [x, y, w, h] = self.rect;
+        let mut renderer = Renderer {
+            graphics: vec![],
+            canvas_state,
+            font_size: self.font_size,
+            x,
+            y,
+            max_len: ((w / (self.font_size * 0.55)) as usize).max(16),
+            bottom: y + h,
+            line: String::new(),
+            line_colors: vec![],
+        };
+
+        renderer.node(None, &self.value, "", 0, false);
+        renderer.graphics
+    }
+}
+
+struct Renderer<'a> {
+    graphics: Vec<Graphic>,
+    canvas_state: &'a CanvasState,
+    font_size: f32,
+    x: f32,
+
+    // baseline of the current line
+    y: f32,
+
+    // in characters
+    max_len: usize,
+    bottom: f32,
+
+    // the current line
+    line: String,
+    line_colors: Vec<Color>,
+}
+
+impl Renderer<'_> {
+    fn node(&mut self, key: Option<&str>, value: &Value, path: &str, depth: usize, comma: bool) {
+        if self.y > self.bottom {
+            return;
+        }
+
+        let indent = depth * 2 + 2;
+        self.push(&" ".repeat(indent), PUNCT);
+
+        if let Some(key) = key {
+            self.push(&serde_json::to_string(key).unwrap(), KEY);
+            self.push(": ", PUNCT);
+        }
+
+        let comma = if comma { "," } else { "" };
+
+        match value {
+            Value::Object(_) | Value::Array(_) => {
+                let (open, close, children): (&str, &str, Vec<(Option<&str>, &Value)>) = match value {
+                    Value::Object(object) => ("{", "}", object.iter().map(|(k, v)| (Some(k.as_str()), v)).collect()),
+                    Value::Array(array) => ("[", "]", array.iter().map(|v| (None, v)).collect()),
+                    _ => unreachable!(),
+                };
+
+                if children.is_empty() {
+                    self.push(&format!("{open}{close}{comma}"), PUNCT);
+                    self.newline();
+                    return;
+                }
+
+                let toggle_key = format!("fold:{path}");
+                let unfolded_by_default = depth < 2 && children.len() <= 100;
+                let unfolded = unfolded_by_default != self.canvas_state.is_toggled(&toggle_key);
+                let line_start = self.x + (indent - 2) as f32 * self.char_width();
+                self.marker(line_start, unfolded);
+                self.graphics.push(Graphic::Clickable {
+                    x: line_start,
+                    y: self.y - self.font_size * 0.85,
+                    w: (self.line.chars().count() + 4 - indent) as f32 * self.char_width(),
+                    h: self.font_size * 1.1,
+                    action: ClickAction::Toggle(toggle_key),
+                });
+
+                if unfolded {
+                    self.push(open, PUNCT);
+                    self.newline();
+                    let last = children.len() - 1;
+
+                    for (i, (child_key, child)) in children.into_iter().enumerate() {
+                        let child_path = match child_key {
+                            Some(k) => format!("{path}/{}", k.replace('~', "~0").replace('/', "~1")),
+                            None => format!("{path}/{i}"),
+                        };
+
+                        self.node(child_key, child, &child_path, depth + 1, i != last);
+                    }
+
+                    self.push(&" ".repeat(indent), PUNCT);
+                    self.push(&format!("{close}{comma}"), PUNCT);
+                }
+
+                else {
+                    let summary = match value {
+                        Value::Object(_) => format!(" {} key{}", children.len(), if children.len() == 1 { "" } else { "s" }),
+                        _ => format!(" {} item{}", children.len(), if children.len() == 1 { "" } else { "s" }),
+                    };
+
+                    self.push(&format!("{open}...{close}{comma}"), PUNCT);
+                    self.push(&summary, GRAY);
+                }
+
+                self.newline();
+            },
+            Value::String(s) => {
+                let escaped = serde_json::to_string(s).unwrap();
+                let toggle_key = format!("expand:{path}");
+                let available = self.max_len.saturating_sub(self.line.chars().count() + 20).max(16);
+
+                if escaped.chars().count() <= available {
+                    self.push(&escaped, STRING);
+                    self.push(comma, PUNCT);
+                    self.newline();
+                }
+
+                else if self.canvas_state.is_toggled(&toggle_key) {
+                    self.control("[-]", toggle_key);
+                    self.push(" ", PUNCT);
+                    self.newline();
+
+                    // The full string, with the real newlines.
+                    let text_indent = indent + 2;
+                    let width = self.max_len.saturating_sub(text_indent).max(16);
+
+                    for line in s.split('\n') {
+                        let chars: Vec<char> = line.chars().collect();
+
+                        for chunk in chars.chunks(width).map(|c| c.iter().collect::<String>()).chain(
+                            // an empty line
+                            if chars.is_empty() { Some(String::new()) } else { None }
+                        ) {
+                            self.push(&" ".repeat(text_indent), PUNCT);
+                            self.push(&chunk, STRING);
+                            self.newline();
+
+                            if self.y > self.bottom {
+                                return;
+                            }
+                        }
+                    }
+
+                    if !comma.is_empty() {
+                        self.push(&" ".repeat(indent), PUNCT);
+                        self.push(comma, PUNCT);
+                        self.newline();
+                    }
+                }
+
+                else {
+                    let truncated = escaped.chars().take(available).collect::<String>();
+                    self.push(&format!("{truncated}...\""), STRING);
+                    self.push(comma, PUNCT);
+                    self.push(" ", PUNCT);
+                    self.control(&format!("[+{} chars]", s.chars().count()), toggle_key);
+                    self.newline();
+                }
+            },
+            Value::Number(n) => {
+                self.push(&n.to_string(), NUMBER);
+                self.push(comma, PUNCT);
+                self.newline();
+            },
+            Value::Bool(b) => {
+                self.push(&b.to_string(), BOOL);
+                self.push(comma, PUNCT);
+                self.newline();
+            },
+            Value::Null => {
+                self.push("null", GRAY);
+                self.push(comma, PUNCT);
+                self.newline();
+            },
+        }
+    }
+
+    fn char_width(&self) -> f32 {
+        self.font_size * 0.55
+    }
+
+    fn push(&mut self, s: &str, color: Color) {
+        for ch in s.chars() {
+            self.line.push(ch);
+            self.line_colors.push(color);
+        }
+    }
+
+    // a clickable text in the current line
+    fn control(&mut self, s: &str, toggle_key: String) {
+        self.graphics.push(Graphic::Clickable {
+            x: self.x + self.line.chars().count() as f32 * self.char_width(),
+            y: self.y - self.font_size * 0.85,
+            w: s.chars().count() as f32 * self.char_width(),
+            h: self.font_size * 1.1,
+            action: ClickAction::Toggle(toggle_key),
+        });
+        self.push(s, CONTROL);
+    }
+
+    // a triangle that points right (folded) or down (unfolded)
+    fn marker(&mut self, x: f32, unfolded: bool) {
+        let size = self.font_size * 0.5;
+        let (x, y) = (x + self.char_width() * 0.5, self.y - self.font_size * 0.6);
+
+        self.graphics.push(Graphic::Triangle {
+            p1: (x, y),
+            p2: if unfolded { (x + size, y) } else { (x, y + size) },
+            p3: if unfolded { (x + size * 0.5, y + size * 0.8) } else { (x + size * 0.8, y + size * 0.5) },
+            color: GRAY,
+        });
+    }
+
+    fn newline(&mut self) {
+        let mut curr_x = self.x;
+
+        for (ch, color) in self.line.chars().zip(self.line_colors.iter()) {
+            if ch != ' ' {
+                self.graphics.push(Graphic::Char {
+                    ch,
+                    x: curr_x,
+                    y: self.y,
+                    size: self.font_size,
+                    color: *color,
+                });
+            }
+
+            curr_x += self.char_width();
+        }
+
+        self.line.clear();
+        self.line_colors.clear();
+        self.y += self.font_size * 1.1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(tree: &JsonTree, canvas_state: &CanvasState) -> Vec<String> {
+        let char_width = tree.font_size * 0.55;
+        let mut lines: Vec<(f32, String)> = vec![];
+
+        for graphic in tree.render(canvas_state) {
+            if let Graphic::Char { ch, x, y, .. } = graphic {
+                if lines.last().map(|(line_y, _)| *line_y != y).unwrap_or(true) {
+                    lines.push((y, String::new()));
+                }
+
+                let line = &mut lines.last_mut().unwrap().1;
+                let column = ((x - tree.rect[0]) / char_width).round() as usize;
+
+                while line.chars().count() < column {
+                    line.push(' ');
+                }
+
+                line.push(ch);
+            }
+        }
+
+        lines.into_iter().map(|(_, line)| line).collect()
+    }
+
+    fn toggles(tree: &JsonTree, canvas_state: &CanvasState) -> Vec<String> {
+        tree.render(canvas_state).into_iter().filter_map(
+            |graphic| match graphic {
+                Graphic::Clickable { action: ClickAction::Toggle(key), .. } => Some(key),
+                _ => None,
+            }
+        ).collect()
+    }
+
+    #[test]
+    fn fold_and_unfold() {
+        let tree = JsonTree::new(r#"{"a": [1, true, null], "b/c": {"d": {"e": 0}}, "f": {}}"#, 10.0, [0.0, 0.0, 900.0, 600.0]).unwrap();
+        let mut canvas_state = CanvasState::default();
+
+        assert_eq!(
+            lines(&tree, &canvas_state),
+            vec![
+                "  {",
+                "    \"a\": [",
+                "      1,",
+                "      true,",
+                "      null",
+                "    ],",
+                "    \"b/c\": {",
+                "      \"d\": {...} 1 key",
+                "    },",
+                "    \"f\": {}",
+                "  }",
+            ],
+        );
+        assert_eq!(toggles(&tree, &canvas_state), vec!["fold:", "fold:/a", "fold:/b~1c", "fold:/b~1c/d"]);
+
+        canvas_state.toggle("fold:/b~1c/d");
+        canvas_state.toggle("fold:/a");
+        assert_eq!(
+            lines(&tree, &canvas_state),
+            vec![
+                "  {",
+                "    \"a\": [...], 3 items",
+                "    \"b/c\": {",
+                "      \"d\": {",
+                "        \"e\": 0",
+                "      }",
+                "    },",
+                "    \"f\": {}",
+                "  }",
+            ],
+        );
+
+        canvas_state.toggle("fold:");
+        assert_eq!(lines(&tree, &canvas_state), vec!["  {...} 3 keys"]);
+    }
+
+    #[test]
+    fn long_strings() {
+        let long = "x".repeat(30);
+        let tree = JsonTree::from_value(Value::Array(vec![Value::String(format!("{long}\n\ny"))]), 10.0, [0.0, 0.0, 220.0, 600.0]);
+        let mut canvas_state = CanvasState::default();
+
+        assert_eq!(lines(&tree, &canvas_state), vec!["  [", "    \"xxxxxxxxxxxxxxx...\" [+33 chars]", "  ]"]);
+        assert_eq!(toggles(&tree, &canvas_state), vec!["fold:", "expand:/0"]);
+
+        // The empty line has no chars, so it's not in `lines`.
+        canvas_state.toggle("expand:/0");
+        assert_eq!(
+            lines(&tree, &canvas_state),
+            vec!["  [", "    [-]", "      xxxxxxxxxxxxxxxxxxxxxxxxxxxxxx", "      y", "  ]"],
+        );
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert!(JsonTree::new("{", 10.0, [0.0, 0.0, 900.0, 600.0]).is_err());
+    }
+}
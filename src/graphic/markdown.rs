@@ -0,0 +1,739 @@
+use super::{ClickAction, Graphic};
+use super::ansi::parse_ansi;
+use super::syntax::Syntax;
+use macroquad::color::Color;
+
+const HEADING: Color = Color { r: 0.95, g: 0.8, b: 0.4, a: 1.0 };
+const ITALIC: Color = Color { r: 0.75, g: 0.85, b: 1.0, a: 1.0 };
+const CODE: Color = Color { r: 0.95, g: 0.65, b: 0.55, a: 1.0 };
+const CODE_BG: Color = Color { r: 0.18, g: 0.18, b: 0.2, a: 1.0 };
+const LINK: Color = Color { r: 0.45, g: 0.65, b: 1.0, a: 1.0 };
+const GRAY: Color = Color { r: 0.5, g: 0.5, b: 0.5, a: 1.0 };
+
+/// It renders a markdown document. It supports headings, paragraphs, (nested) lists,
+/// block quotes, horizontal rules, tables, fenced code blocks, and inline styles
+/// (`**bold**`, `*italic*`, `` `code` `` and `[links](url)`).
+/// If the user clicks a link, the engine opens the url (see `ClickAction::Open`).
+///
+/// Fenced code blocks are rendered like a terminal (see `TextBox::from_ansi`). If a code
+/// block has a language (e.g. ```` ```rust ````) that `Syntax` knows, it's highlighted.
+///
+/// Unlike `TextBox`, `rect[1]` is the top of the first line, not its baseline.
+pub struct Markdown {
+    s: String,
+    font_size: f32,
+    color: Color,
+    rect: [f32; 4],
+}
+
+impl Markdown {
+    pub fn new(
+        s: &str,
+        font_size: f32,
+        color: Color,
+        rect: [f32; 4],
+    ) -> Markdown {
+        Markdown {
+            s: s.to_string(),
+            font_size,
+            color,
+            rect,
+        }
+    }
+
+    pub fn render(&self) -> Vec<Graphic> {
+        let [x, y, w, h] = self.rect;
+        let mut layout = Layout {
+            graphics: vec![],
+            color: self.color,
+            font_size: self.font_size,
+            y,
+            bottom: y + h,
+        };
+
+        for block in parse_blocks(&self.s).iter() {
+            if layout.y > layout.bottom {
+                break;
+            }
+
+            layout.block(block, x, w);
+        }
+
+        layout.graphics
+    }
+}
+
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    ListItem {
+        indent: usize,
+
+        // `None` for unordered lists
+        number: Option<String>,
+        text: String,
+    },
+    Quote(String),
+    Code {
+        language: Option<String>,
+        content: String,
+    },
+    Table {
+        header: Option<Vec<String>>,
+        rows: Vec<Vec<String>>,
+    },
+    Rule,
+}
+
+fn parse_blocks(s: &str) -> Vec<Block> {
+    let lines: Vec<&str> = s.lines().collect();
+    let mut blocks = vec![];
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            i += 1;
+        }
+
+        else if let Some(fence) = ["```", "~~~"].iter().find(|fence| trimmed.starts_with(*fence)) {
+            let language = trimmed[3..].trim();
+            let mut content = vec![];
+            i += 1;
+
+            while i < lines.len() && !lines[i].trim().starts_with(fence) {
+                content.push(lines[i]);
+                i += 1;
+            }
+
+            // closing fence
+            i += 1;
+            blocks.push(Block::Code {
+                language: if language.is_empty() { None } else { Some(language.to_string()) },
+                content: content.join("\n"),
+            });
+        }
+
+        else if let Some(level) = heading_level(trimmed) {
+            blocks.push(Block::Heading(level, trimmed[level..].trim().trim_end_matches('#').trim().to_string()));
+            i += 1;
+        }
+
+        else if is_rule(trimmed) {
+            blocks.push(Block::Rule);
+            i += 1;
+        }
+
+        else if trimmed.starts_with('>') {
+            let mut quote = vec![];
+
+            while i < lines.len() && lines[i].trim().starts_with('>') {
+                quote.push(lines[i].trim()[1..].trim());
+                i += 1;
+            }
+
+            blocks.push(Block::Quote(quote.join(" ")));
+        }
+
+        else if trimmed.starts_with('|') {
+            let mut rows = vec![];
+
+            while i < lines.len() && lines[i].trim().starts_with('|') {
+                rows.push(split_table_row(lines[i].trim()));
+                i += 1;
+            }
+
+            let is_separator = |row: &Vec<String>| row.iter().all(
+                |cell| !cell.is_empty() && cell.chars().all(|c| c == '-' || c == ':')
+            );
+
+            if rows.len() > 1 && is_separator(&rows[1]) {
+                let header = rows.remove(0);
+                rows.remove(0);
+                blocks.push(Block::Table { header: Some(header), rows });
+            }
+
+            else {
+                blocks.push(Block::Table { header: None, rows });
+            }
+        }
+
+        else if let Some((indent, number, text)) = list_item(line) {
+            let mut text = vec![text.to_string()];
+            i += 1;
+
+            // continuation lines
+            while i < lines.len() && !lines[i].trim().is_empty() && list_item(lines[i]).is_none() && lines[i].starts_with(' ') {
+                text.push(lines[i].trim().to_string());
+                i += 1;
+            }
+
+            blocks.push(Block::ListItem { indent, number, text: text.join(" ") });
+        }
+
+        else {
+            let mut paragraph = vec![];
+
+            while i < lines.len() {
+                let trimmed = lines[i].trim();
+
+                if trimmed.is_empty()
+                    || trimmed.starts_with("```")
+                    || trimmed.starts_with("~~~")
+                    || trimmed.starts_with('>')
+                    || trimmed.starts_with('|')
+                    || heading_level(trimmed).is_some()
+                    || (is_rule(trimmed) && !paragraph.is_empty())
+                    || list_item(lines[i]).is_some() {
+                    break;
+                }
+
+                paragraph.push(trimmed);
+                i += 1;
+            }
+
+            blocks.push(Block::Paragraph(paragraph.join(" ")));
+        }
+    }
+
+    blocks
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+
+    if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+        Some(level)
+    }
+
+    else {
+        None
+    }
+}
+
+fn is_rule(line: &str) -> bool {
+    let line = line.replace(' ', "");
+
+    line.len() >= 3 && ["-", "*", "_"].iter().any(|c| line.chars().all(|ch| ch.to_string() == *c))
+}
+
+// It returns (indent, number, text).
+fn list_item(line: &str) -> Option<(usize, Option<String>, &str)> {
+    let indent = line.chars().take_while(|c| *c == ' ').count();
+    let rest = &line[indent..];
+
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(text) = rest.strip_prefix(bullet) {
+            return Some((indent, None, text.trim()));
+        }
+    }
+
+    let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+
+    if digits > 0 && (rest[digits..].starts_with(". ") || rest[digits..].starts_with(") ")) {
+        return Some((indent, Some(rest[..digits].to_string()), rest[(digits + 2)..].trim()));
+    }
+
+    None
+}
+
+fn split_table_row(line: &str) -> Vec<String> {
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+
+    line.split('|').map(|cell| cell.trim().to_string()).collect()
+}
+
+#[derive(Clone, Default)]
+struct InlineStyle {
+    bold: bool,
+    italic: bool,
+    code: bool,
+    link: Option<String>,
+}
+
+fn parse_inline(s: &str) -> Vec<(String, InlineStyle)> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut spans = vec![];
+    let mut buffer = String::new();
+    let mut style = InlineStyle::default();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        let next = chars.get(i + 1).copied();
+        let prev_is_alnum = i > 0 && chars[i - 1].is_alphanumeric();
+
+        match ch {
+            '\\' if next.map(|c| c.is_ascii_punctuation()).unwrap_or(false) => {
+                buffer.push(chars[i + 1]);
+                i += 2;
+            },
+            '`' => match (i + 1..chars.len()).find(|j| chars[*j] == '`') {
+                Some(end) => {
+                    flush(&mut spans, &mut buffer, &style);
+                    spans.push((chars[(i + 1)..end].iter().collect(), InlineStyle { code: true, ..style.clone() }));
+                    i = end + 1;
+                },
+                None => {
+                    buffer.push(ch);
+                    i += 1;
+                },
+            },
+            '*' | '_' if next == Some(ch) && is_delimiter(&chars, i, 2, style.bold) => {
+                flush(&mut spans, &mut buffer, &style);
+                style.bold = !style.bold;
+                i += 2;
+            },
+            '*' | '_' if (ch == '*' || !prev_is_alnum || style.italic) && is_delimiter(&chars, i, 1, style.italic) => {
+                flush(&mut spans, &mut buffer, &style);
+                style.italic = !style.italic;
+                i += 1;
+            },
+            '[' => match parse_link(&chars, i) {
+                Some((text, url, end)) => {
+                    flush(&mut spans, &mut buffer, &style);
+                    spans.push((text, InlineStyle { link: Some(url), ..style.clone() }));
+                    i = end;
+                },
+                None => {
+                    buffer.push(ch);
+                    i += 1;
+                },
+            },
+            _ => {
+                buffer.push(ch);
+                i += 1;
+            },
+        }
+    }
+
+    flush(&mut spans, &mut buffer, &style);
+    spans
+}
+
+// A delimiter of `len` chars at `i` must touch the text that it emphasizes (e.g. "*a*", not
+// "a * b"), and an opening delimiter must have a closing one.
+fn is_delimiter(chars: &[char], i: usize, len: usize, closing: bool) -> bool {
+    if closing {
+        return i > 0 && !chars[i - 1].is_whitespace();
+    }
+
+    if !chars.get(i + len).map(|ch| !ch.is_whitespace()).unwrap_or(false) {
+        return false;
+    }
+
+    let delimiter = &chars[i..(i + len)];
+    ((i + len + 1)..chars.len()).any(|j| chars[j..].starts_with(delimiter) && !chars[j - 1].is_whitespace())
+}
+
+fn flush(spans: &mut Vec<(String, InlineStyle)>, buffer: &mut String, style: &InlineStyle) {
+    if !buffer.is_empty() {
+        spans.push((std::mem::take(buffer), style.clone()));
+    }
+}
+
+// `[text](url)` -> (text, url, index after `)`)
+fn parse_link(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    let close_bracket = (start + 1..chars.len()).find(|i| chars[*i] == ']')?;
+
+    if chars.get(close_bracket + 1) != Some(&'(') {
+        return None;
+    }
+
+    let close_paren = (close_bracket + 2..chars.len()).find(|i| chars[*i] == ')')?;
+
+    Some((
+        chars[(start + 1)..close_bracket].iter().collect(),
+        chars[(close_bracket + 2)..close_paren].iter().collect(),
+        close_paren + 1,
+    ))
+}
+
+struct Layout {
+    graphics: Vec<Graphic>,
+    color: Color,
+    font_size: f32,
+
+    // top of the next line
+    y: f32,
+    bottom: f32,
+}
+
+impl Layout {
+    fn block(&mut self, block: &Block, x: f32, w: f32) {
+        let size = self.font_size;
+
+        match block {
+            Block::Heading(level, text) => {
+                let size = size * match level {
+                    1 => 1.8,
+                    2 => 1.5,
+                    3 => 1.25,
+                    _ => 1.1,
+                };
+                self.y += size * 0.3;
+                let spans = parse_inline(text).into_iter().map(
+                    |(s, style)| (s, InlineStyle { bold: true, ..style })
+                ).collect::<Vec<_>>();
+                self.inline(&spans, x, w, size, HEADING);
+
+                if *level < 3 {
+                    self.rect(x, self.y, w, 1.0, GRAY);
+                }
+
+                self.y += size * 0.5;
+            },
+            Block::Paragraph(text) => {
+                self.inline(&parse_inline(text), x, w, size, self.color);
+                self.y += size * 0.6;
+            },
+            Block::ListItem { indent, number, text } => {
+                let item_x = x + (*indent / 2) as f32 * size * 1.1 + size * 0.55;
+
+                match number {
+                    Some(n) => {
+                        self.chars(&format!("{n}."), item_x, self.y + size * 0.85, size, self.color);
+                    },
+                    None => {
+                        self.graphics.push(Graphic::Ellipse {
+                            x: item_x + size * 0.3,
+                            y: self.y + size * 0.55,
+                            rx: size * 0.15,
+                            ry: size * 0.15,
+                            thickness: None,
+                            color: self.color,
+                        });
+                    },
+                }
+
+                let marker_width = size * 0.55 * (number.as_ref().map(|n| n.len()).unwrap_or(0) + 2) as f32;
+                self.inline(&parse_inline(text), item_x + marker_width, x + w - item_x - marker_width, size, self.color);
+                self.y += size * 0.2;
+            },
+            Block::Quote(text) => {
+                let top = self.y;
+                let dimmed = Color { a: self.color.a * 0.75, ..self.color };
+                self.inline(&parse_inline(text), x + size * 1.1, w - size * 1.1, size, dimmed);
+                self.rect(x + size * 0.3, top, size * 0.2, self.y - top, GRAY);
+                self.y += size * 0.6;
+            },
+            Block::Code { language, content } => {
+                let line_height = size * 1.1;
+                let line_count = content.lines().count().max(1);
+                let padding = size * 0.5;
+                self.rect(x, self.y, w, line_count as f32 * line_height + padding * 2.0, CODE_BG);
+                self.y += padding;
+
+                let parsed = parse_ansi(content, self.color);
+                let colors = match language.as_ref().and_then(|l| Syntax::from_language(l)) {
+                    // The code block has no escape sequences.
+                    Some(syntax) if parsed.text.len() == content.len() => syntax.highlight(content, self.color),
+                    _ => parsed.colors,
+                };
+                let max_len = ((w - padding * 2.0) / (size * 0.55)) as usize;
+                let mut curr_x = x + padding;
+                let mut curr_len = 0;
+
+                for (i, ch) in parsed.text.chars().enumerate() {
+                    if ch == '\n' {
+                        curr_x = x + padding;
+                        curr_len = 0;
+                        self.y += line_height;
+
+                        if self.y > self.bottom {
+                            return;
+                        }
+
+                        continue;
+                    }
+
+                    if curr_len < max_len {
+                        if let Some(background) = parsed.backgrounds[i] {
+                            self.rect(curr_x, self.y, size * 0.55, line_height, background);
+                        }
+
+                        self.push_char(ch, curr_x, self.y + size * 0.85, size, colors[i]);
+                    }
+
+                    curr_x += size * 0.55;
+                    curr_len += 1;
+                }
+
+                self.y += line_height + padding + size * 0.6;
+            },
+            Block::Table { header, rows } => {
+                self.table(header.as_ref(), rows, x, w);
+                self.y += size * 0.6;
+            },
+            Block::Rule => {
+                self.y += size * 0.5;
+                self.rect(x, self.y, w, 1.0, GRAY);
+                self.y += size * 0.5;
+            },
+        }
+    }
+
+    // It renders inline spans with word wrap, and moves `self.y` to the next line.
+    fn inline(&mut self, spans: &[(String, InlineStyle)], x: f32, w: f32, size: f32, color: Color) {
+        let char_width = size * 0.55;
+        let line_height = size * 1.1;
+        let right = x + w;
+        let mut curr_x = x;
+
+        for (text, style) in spans.iter() {
+            let color = if style.code {
+                CODE
+            } else if style.link.is_some() {
+                LINK
+            } else if style.italic {
+                ITALIC
+            } else {
+                color
+            };
+
+            for word in split_words(text) {
+                let word_width = word.chars().count() as f32 * char_width;
+
+                if word == " " && curr_x == x {
+                    continue;
+                }
+
+                if curr_x + word_width > right && curr_x > x {
+                    curr_x = x;
+                    self.y += line_height;
+
+                    if word == " " {
+                        continue;
+                    }
+                }
+
+                for ch in word.chars() {
+                    // a word that's longer than a line
+                    if curr_x + char_width > right && curr_x > x {
+                        curr_x = x;
+                        self.y += line_height;
+                    }
+
+                    if style.code {
+                        self.rect(curr_x, self.y, char_width, line_height, CODE_BG);
+                    }
+
+                    if ch != ' ' {
+                        self.push_char(ch, curr_x, self.y + size * 0.85, size, color);
+
+                        // There's no bold font, so it draws the character twice.
+                        if style.bold {
+                            self.push_char(ch, curr_x + size * 0.05, self.y + size * 0.85, size, color);
+                        }
+                    }
+
+                    if let Some(url) = &style.link {
+                        self.rect(curr_x, self.y + size, char_width, 1.0, color);
+                        self.graphics.push(Graphic::Clickable {
+                            x: curr_x,
+                            y: self.y,
+                            w: char_width,
+                            h: line_height,
+                            action: ClickAction::Open(url.to_string()),
+                        });
+                    }
+
+                    curr_x += char_width;
+                }
+            }
+        }
+
+        self.y += line_height;
+    }
+
+    fn table(&mut self, header: Option<&Vec<String>>, rows: &[Vec<String>], x: f32, w: f32) {
+        let size = self.font_size;
+        let char_width = size * 0.55;
+        let row_height = size * 1.6;
+        let column_count = header.iter().copied().chain(rows.iter()).map(|row| row.len()).max().unwrap_or(0);
+
+        if column_count == 0 {
+            return;
+        }
+
+        // in characters
+        let mut widths = vec![0; column_count];
+
+        for row in header.iter().copied().chain(rows.iter()) {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(plain_text(cell).chars().count());
+            }
+        }
+
+        let max_total = ((w / char_width) as usize).saturating_sub(column_count * 2);
+        let total = widths.iter().sum::<usize>();
+
+        if total > max_total {
+            for width in widths.iter_mut() {
+                *width = (*width * max_total / total).max(4);
+            }
+        }
+
+        let table_width = widths.iter().map(|w| (w + 2) as f32 * char_width).sum::<f32>();
+        let top = self.y;
+
+        for (row_index, row) in header.iter().copied().chain(rows.iter()).enumerate() {
+            let is_header = header.is_some() && row_index == 0;
+
+            if is_header {
+                self.rect(x, self.y, table_width, row_height, CODE_BG);
+            }
+
+            let mut curr_x = x;
+
+            for (i, width) in widths.iter().enumerate() {
+                let cell = plain_text(row.get(i).map(|c| c.as_str()).unwrap_or(""));
+                let cell = if cell.chars().count() > *width {
+                    format!("{}...", cell.chars().take(width.saturating_sub(3)).collect::<String>())
+                } else {
+                    cell
+                };
+
+                self.chars(&cell, curr_x + char_width, self.y + size * 1.15, size, if is_header { HEADING } else { self.color });
+                curr_x += (width + 2) as f32 * char_width;
+            }
+
+            self.y += row_height;
+            self.rect(x, self.y, table_width, 1.0, GRAY);
+
+            if self.y > self.bottom {
+                break;
+            }
+        }
+
+        self.rect(x, top, table_width, 1.0, GRAY);
+        self.rect(x, top, 1.0, self.y - top, GRAY);
+        let mut curr_x = x;
+
+        for width in widths.iter() {
+            curr_x += (width + 2) as f32 * char_width;
+            self.rect(curr_x, top, 1.0, self.y - top, GRAY);
+        }
+    }
+
+    fn chars(&mut self, s: &str, x: f32, baseline: f32, size: f32, color: Color) {
+        let mut curr_x = x;
+
+        for ch in s.chars() {
+            if ch != ' ' {
+                self.push_char(ch, curr_x, baseline, size, color);
+            }
+
+            curr_x += size * 0.55;
+        }
+    }
+
+    fn push_char(&mut self, ch: char, x: f32, y: f32, size: f32, color: Color) {
+        self.graphics.push(Graphic::Char { ch, x, y, size, color });
+    }
+
+    fn rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: Color) {
+        self.graphics.push(Graphic::Rect { x, y, w, h, radius: None, thickness: None, color });
+    }
+}
+
+// "Hello, world!" -> ["Hello,", " ", "world!"]
+fn split_words(s: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut buffer = String::new();
+
+    for ch in s.chars() {
+        if ch == ' ' {
+            if !buffer.is_empty() {
+                words.push(std::mem::take(&mut buffer));
+            }
+
+            words.push(String::from(" "));
+        }
+
+        else {
+            buffer.push(ch);
+        }
+    }
+
+    if !buffer.is_empty() {
+        words.push(buffer);
+    }
+
+    words
+}
+
+fn plain_text(s: &str) -> String {
+    parse_inline(s).into_iter().map(|(s, _)| s).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (text, style), where the style is like "bi" (bold and italic), "c" (code) or "l:url"
+    fn spans(s: &str) -> Vec<(String, String)> {
+        parse_inline(s).into_iter().map(
+            |(text, style)| {
+                let mut flags = String::new();
+
+                if style.bold { flags.push('b'); }
+                if style.italic { flags.push('i'); }
+                if style.code { flags.push('c'); }
+                if let Some(url) = &style.link { flags.push_str(&format!("l:{url}")); }
+
+                (text, flags)
+            }
+        ).collect()
+    }
+
+    fn span(text: &str, style: &str) -> (String, String) {
+        (text.to_string(), style.to_string())
+    }
+
+    #[test]
+    fn emphasis() {
+        assert_eq!(spans("*italic* and **bold**"), vec![span("italic", "i"), span(" and ", ""), span("bold", "b")]);
+        assert_eq!(spans("_italic_ and __bold__"), vec![span("italic", "i"), span(" and ", ""), span("bold", "b")]);
+        assert_eq!(spans("**bold *both***"), vec![span("bold ", "b"), span("both", "bi")]);
+    }
+
+    #[test]
+    fn lone_delimiters() {
+        assert_eq!(spans("a * b"), vec![span("a * b", "")]);
+        assert_eq!(spans("2 * 3 = 6, 2 ** 3 = 8"), vec![span("2 * 3 = 6, 2 ** 3 = 8", "")]);
+        assert_eq!(spans("*not closed"), vec![span("*not closed", "")]);
+        assert_eq!(spans("*a *"), vec![span("*a *", "")]);
+        assert_eq!(spans("a **b"), vec![span("a **b", "")]);
+        assert_eq!(spans("snake_case_name"), vec![span("snake_case_name", "")]);
+        assert_eq!(spans("a * b *c*"), vec![span("a * b ", ""), span("c", "i")]);
+    }
+
+    #[test]
+    fn code_links_and_escapes() {
+        assert_eq!(spans("run `cargo *test*` now"), vec![span("run ", ""), span("cargo *test*", "c"), span(" now", "")]);
+        assert_eq!(spans("see [docs](https://a.b/c) *here*"), vec![span("see ", ""), span("docs", "l:https://a.b/c"), span(" ", ""), span("here", "i")]);
+        assert_eq!(spans(r"\*not italic\*"), vec![span("*not italic*", "")]);
+        assert_eq!(spans("[not a link] `unclosed"), vec![span("[not a link] `unclosed", "")]);
+    }
+
+    #[test]
+    fn block_helpers() {
+        assert_eq!(heading_level("## title"), Some(2));
+        assert_eq!(heading_level("#hashtag"), None);
+        assert_eq!(heading_level("####### too deep"), None);
+
+        assert!(is_rule("---"));
+        assert!(is_rule("* * *"));
+        assert!(!is_rule("--"));
+        assert!(!is_rule("-*-"));
+
+        assert_eq!(list_item("  - item"), Some((2, None, "item")));
+        assert_eq!(list_item("12. item"), Some((0, Some(String::from("12")), "item")));
+        assert_eq!(list_item("12.5 is not a list"), None);
+        assert_eq!(split_table_row("| a | b |"), vec![String::from("a"), String::from("b")]);
+    }
+}
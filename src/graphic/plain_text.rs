@@ -0,0 +1,109 @@
+use super::Graphic;
+
+// (ch, x, size)
+type LineChar = (char, f32, f32);
+
+/// It extracts the text of `Graphic::Char`s in reading order (top to bottom, left to right).
+/// The chars on the same baseline make a line, and the gaps between them become spaces
+/// (some helpers don't draw spaces).
+///
+/// If `rect` is set, only the chars whose center is in the rect are extracted.
+pub fn graphics_to_text(graphics: &[Graphic], rect: Option<[f32; 4]>) -> String {
+    let mut chars = graphics.iter().filter_map(
+        |graphic| match graphic {
+            Graphic::Char { ch, x, y, size, .. } => Some((*ch, *x, *y, *size)),
+            _ => None,
+        }
+    ).filter(
+        |(_, x, y, size)| match rect {
+            // (x, y) is the bottom-left of the char.
+            Some([rx, ry, rw, rh]) => {
+                let (cx, cy) = (x + size * 0.275, y - size * 0.35);
+                rx <= cx && cx <= rx + rw && ry <= cy && cy <= ry + rh
+            },
+            None => true,
+        }
+    ).collect::<Vec<_>>();
+
+    chars.sort_by(|(_, x1, y1, _), (_, x2, y2, _)| y1.total_cmp(y2).then(x1.total_cmp(x2)));
+
+    // (y, chars)
+    let mut lines: Vec<(f32, Vec<LineChar>)> = vec![];
+
+    for (ch, x, y, size) in chars.into_iter() {
+        match lines.last_mut() {
+            Some((line_y, line)) if (y - *line_y).abs() < size * 0.5 => { line.push((ch, x, size)); },
+            _ => { lines.push((y, vec![(ch, x, size)])); },
+        }
+    }
+
+    let mut result = vec![];
+
+    for (_, mut line) in lines.into_iter() {
+        // chars on the same baseline are not sorted by x if their y values are slightly different
+        line.sort_by(|(_, x1, _), (_, x2, _)| x1.total_cmp(x2));
+        let mut s = String::new();
+        let mut prev: Option<(char, f32)> = None;
+
+        for (ch, x, size) in line.into_iter() {
+            let advance = size * 0.55;
+
+            if let Some((prev_ch, prev_x)) = prev {
+                // Some helpers draw a char twice with a small offset to make it bold
+                // (e.g. `Markdown`). It's the same char, so it's copied once.
+                if prev_ch == ch && x - prev_x < advance * 0.5 {
+                    continue;
+                }
+
+                let spaces = ((x - prev_x) / advance).round() as i32 - 1;
+
+                for _ in 0..spaces.clamp(0, 256) {
+                    s.push(' ');
+                }
+            }
+
+            s.push(ch);
+            prev = Some((ch, x));
+        }
+
+        result.push(s.trim_end().to_string());
+    }
+
+    result.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphic::Markdown;
+    use macroquad::color::Color;
+
+    const WHITE: Color = Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
+
+    fn chars(s: &str, x: f32, y: f32) -> Vec<Graphic> {
+        s.chars().enumerate().filter(|(_, ch)| *ch != ' ').map(
+            |(i, ch)| Graphic::Char { ch, x: x + i as f32 * 11.0, y, size: 20.0, color: WHITE }
+        ).collect()
+    }
+
+    #[test]
+    fn lines_and_spaces() {
+        let mut graphics = chars("world", 0.0, 40.0);
+        graphics.extend(chars("hello,  there", 0.0, 20.0));
+
+        assert_eq!(graphics_to_text(&graphics, None), "hello,  there\nworld");
+        assert_eq!(graphics_to_text(&graphics, Some([0.0, 0.0, 900.0, 25.0])), "hello,  there");
+        assert_eq!(graphics_to_text(&[], None), "");
+    }
+
+    #[test]
+    fn repeated_chars_are_kept() {
+        assert_eq!(graphics_to_text(&chars("aaa  bb", 0.0, 20.0), None), "aaa  bb");
+    }
+
+    #[test]
+    fn bold_markdown_is_copied_once() {
+        let graphics = Markdown::new("# Heading\n\nsome **bold** text", 14.0, WHITE, [0.0, 0.0, 900.0, 600.0]).render();
+        assert_eq!(graphics_to_text(&graphics, None), "Heading\nsome bold text");
+    }
+}
@@ -0,0 +1,288 @@
+use super::Graphic;
+use macroquad::color::Color;
+
+/// It converts a canvas (900x600) to an svg document. `Graphic::Clickable`s are invisible,
+/// so they're skipped. Images are embedded as data URIs, so the document is self-contained.
+///
+/// It doesn't need a window (or a GPU), so you can use it in a test or a script.
+pub fn graphics_to_svg(graphics: &[Graphic]) -> String {
+    let mut lines = vec![
+        String::from(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 900 600" width="900" height="600" font-family="monospace">"#),
+        String::from(r#"<rect x="0" y="0" width="900" height="600" fill="rgb(0,0,0)"/>"#),
+    ];
+
+    for graphic in graphics.iter() {
+        let line = match graphic {
+            Graphic::Rect { x, y, w, h, radius, thickness, color } => {
+                let radius = match radius {
+                    Some(r) => format!(r#" rx="{r}" ry="{r}""#),
+                    None => String::new(),
+                };
+
+                format!(r#"<rect x="{x}" y="{y}" width="{w}" height="{h}"{radius} {}/>"#, paint(*color, *thickness))
+            },
+            Graphic::Ellipse { x, y, rx, ry, thickness, color } => {
+                format!(r#"<ellipse cx="{x}" cy="{y}" rx="{rx}" ry="{ry}" {}/>"#, paint(*color, *thickness))
+            },
+            Graphic::Triangle { p1, p2, p3, color } => {
+                format!(r#"<polygon points="{}" {}/>"#, points(&[*p1, *p2, *p3]), paint(*color, None))
+            },
+            Graphic::Line { p1: (x1, y1), p2: (x2, y2), thickness, dash, color } => {
+                format!(r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" {}{}/>"#, paint(*color, Some(*thickness)), dash_array(*dash))
+            },
+            Graphic::Polyline { points: ps, thickness, dash, color } => {
+                format!(r#"<polyline points="{}" {}{}/>"#, points(ps), paint(*color, Some(*thickness)), dash_array(*dash))
+            },
+            Graphic::Polygon { points: ps, thickness, color } => {
+                format!(r#"<polygon points="{}" {}/>"#, points(ps), paint(*color, *thickness))
+            },
+            Graphic::Char { ch, .. } if ch.is_whitespace() => { continue; },
+            Graphic::Char { ch, x, y, size, color } => {
+                format!(r#"<text x="{x}" y="{y}" font-size="{size}" {}>{}</text>"#, paint(*color, None), escape_xml(&ch.to_string()))
+            },
+            Graphic::ImageFile { path, x, y, w, h } | Graphic::Image { path, x, y, w, h } => {
+                format!(r#"<image x="{x}" y="{y}" width="{w}" height="{h}" preserveAspectRatio="none" href="{}"/>"#, escape_xml(&image_href(path)))
+            },
+            Graphic::Clickable { .. } => { continue; },
+        };
+
+        lines.push(line);
+    }
+
+    lines.push(String::from("</svg>"));
+    lines.join("\n")
+}
+
+/// It escapes `&`, `<`, `>`, `"` and `'`. It's also used for html.
+pub(crate) fn escape_xml(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+
+    for ch in s.chars() {
+        match ch {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            _ => result.push(ch),
+        }
+    }
+
+    result
+}
+
+// If `thickness` is set, it only draws the outline (see `Graphic`).
+fn paint(color: Color, thickness: Option<f32>) -> String {
+    let rgb = css_rgb(color);
+    let opacity = if color.a < 1.0 { format!(r#" opacity="{}""#, color.a.clamp(0.0, 1.0)) } else { String::new() };
+
+    match thickness {
+        Some(t) => format!(r#"fill="none" stroke="{rgb}" stroke-width="{t}" stroke-linecap="round" stroke-linejoin="round"{opacity}"#),
+        None => format!(r#"fill="{rgb}"{opacity}"#),
+    }
+}
+
+/// `color` without the alpha, like "rgb(255,0,0)". It's also used for html.
+pub(crate) fn css_rgb(color: Color) -> String {
+    format!(
+        "rgb({},{},{})",
+        (color.r.clamp(0.0, 1.0) * 255.0).round(),
+        (color.g.clamp(0.0, 1.0) * 255.0).round(),
+        (color.b.clamp(0.0, 1.0) * 255.0).round(),
+    )
+}
+
+fn dash_array(dash: Option<(f32, f32)>) -> String {
+    match dash {
+        Some((dash, gap)) => format!(r#" stroke-dasharray="{dash} {gap}""#),
+        None => String::new(),
+    }
+}
+
+fn points(points: &[(f32, f32)]) -> String {
+    points.iter().map(|(x, y)| format!("{x},{y}")).collect::<Vec<_>>().join(" ")
+}
+
+// If the image can't be read, it falls back to the path.
+fn image_href(path: &str) -> String {
+    let mime = match path.rsplit('.').next().map(|ext| ext.to_ascii_lowercase()).as_deref() {
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("bmp") => "image/bmp",
+        Some("svg") => "image/svg+xml",
+        Some("webp") => "image/webp",
+        _ => "application/octet-stream",
+    };
+
+    match std::fs::read(path) {
+        Ok(bytes) => format!("data:{mime};base64,{}", base64(&bytes)),
+        Err(_) => path.to_string(),
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                result.push(TABLE[(n >> (18 - i * 6)) as usize & 63] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphic::ClickAction;
+
+    const RED: Color = Color { r: 1.0, g: 0.0, b: 0.0, a: 1.0 };
+
+    // the lines between the background and `</svg>`
+    fn body(graphics: &[Graphic]) -> Vec<String> {
+        let svg = graphics_to_svg(graphics);
+        let lines = svg.lines().map(|line| line.to_string()).collect::<Vec<_>>();
+
+        assert!(lines[0].starts_with("<svg "));
+        assert_eq!(lines[lines.len() - 1], "</svg>");
+        lines[2..(lines.len() - 1)].to_vec()
+    }
+
+    #[test]
+    fn empty_canvas() {
+        assert_eq!(body(&[]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn rect() {
+        assert_eq!(
+            body(&[
+                Graphic::Rect { x: 1.0, y: 2.0, w: 3.0, h: 4.0, radius: None, thickness: None, color: RED },
+                Graphic::Rect { x: 1.0, y: 2.0, w: 3.0, h: 4.0, radius: Some(5.0), thickness: Some(2.0), color: Color { a: 0.5, ..RED } },
+            ]),
+            vec![
+                r#"<rect x="1" y="2" width="3" height="4" fill="rgb(255,0,0)"/>"#,
+                r#"<rect x="1" y="2" width="3" height="4" rx="5" ry="5" fill="none" stroke="rgb(255,0,0)" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" opacity="0.5"/>"#,
+            ],
+        );
+    }
+
+    #[test]
+    fn ellipse() {
+        assert_eq!(
+            body(&[Graphic::Ellipse { x: 10.0, y: 20.0, rx: 3.0, ry: 4.5, thickness: None, color: RED }]),
+            vec![r#"<ellipse cx="10" cy="20" rx="3" ry="4.5" fill="rgb(255,0,0)"/>"#],
+        );
+    }
+
+    #[test]
+    fn triangle() {
+        assert_eq!(
+            body(&[Graphic::Triangle { p1: (0.0, 0.0), p2: (10.0, 0.0), p3: (0.0, 10.0), color: RED }]),
+            vec![r#"<polygon points="0,0 10,0 0,10" fill="rgb(255,0,0)"/>"#],
+        );
+    }
+
+    #[test]
+    fn line() {
+        assert_eq!(
+            body(&[
+                Graphic::Line { p1: (0.0, 0.0), p2: (10.0, 10.0), thickness: 1.0, dash: None, color: RED },
+                Graphic::Line { p1: (0.0, 0.0), p2: (10.0, 10.0), thickness: 1.0, dash: Some((4.0, 2.0)), color: RED },
+            ]),
+            vec![
+                r#"<line x1="0" y1="0" x2="10" y2="10" fill="none" stroke="rgb(255,0,0)" stroke-width="1" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                r#"<line x1="0" y1="0" x2="10" y2="10" fill="none" stroke="rgb(255,0,0)" stroke-width="1" stroke-linecap="round" stroke-linejoin="round" stroke-dasharray="4 2"/>"#,
+            ],
+        );
+    }
+
+    #[test]
+    fn polyline_and_polygon() {
+        assert_eq!(
+            body(&[
+                Graphic::Polyline { points: vec![(0.0, 0.0), (5.0, 5.0), (10.0, 0.0)], thickness: 2.0, dash: Some((3.0, 3.0)), color: RED },
+                Graphic::Polygon { points: vec![(0.0, 0.0), (5.0, 5.0), (10.0, 0.0)], thickness: None, color: RED },
+                Graphic::Polygon { points: vec![(0.0, 0.0), (5.0, 5.0), (10.0, 0.0)], thickness: Some(1.5), color: RED },
+            ]),
+            vec![
+                r#"<polyline points="0,0 5,5 10,0" fill="none" stroke="rgb(255,0,0)" stroke-width="2" stroke-linecap="round" stroke-linejoin="round" stroke-dasharray="3 3"/>"#,
+                r#"<polygon points="0,0 5,5 10,0" fill="rgb(255,0,0)"/>"#,
+                r#"<polygon points="0,0 5,5 10,0" fill="none" stroke="rgb(255,0,0)" stroke-width="1.5" stroke-linecap="round" stroke-linejoin="round"/>"#,
+            ],
+        );
+    }
+
+    #[test]
+    fn chars() {
+        assert_eq!(
+            body(&[
+                Graphic::Char { ch: 'a', x: 1.0, y: 2.0, size: 14.0, color: RED },
+                Graphic::Char { ch: ' ', x: 1.0, y: 2.0, size: 14.0, color: RED },
+                Graphic::Char { ch: '\n', x: 1.0, y: 2.0, size: 14.0, color: RED },
+                Graphic::Char { ch: '<', x: 1.0, y: 2.0, size: 14.0, color: RED },
+            ]),
+            vec![
+                r#"<text x="1" y="2" font-size="14" fill="rgb(255,0,0)">a</text>"#,
+                r#"<text x="1" y="2" font-size="14" fill="rgb(255,0,0)">&lt;</text>"#,
+            ],
+        );
+    }
+
+    #[test]
+    fn images() {
+        let path = std::env::temp_dir().join(format!("shev-svg-test-{:x}.png", rand::random::<u64>()));
+        std::fs::write(&path, b"foo").unwrap();
+        let path = path.to_str().unwrap().to_string();
+        let result = body(&[
+            Graphic::ImageFile { path: path.clone(), x: 0.0, y: 0.0, w: 10.0, h: 10.0 },
+            Graphic::Image { path: String::from("no/such/file?a=1&b=2.png"), x: 0.0, y: 0.0, w: 10.0, h: 10.0 },
+        ]);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                r#"<image x="0" y="0" width="10" height="10" preserveAspectRatio="none" href="data:image/png;base64,Zm9v"/>"#,
+                r#"<image x="0" y="0" width="10" height="10" preserveAspectRatio="none" href="no/such/file?a=1&amp;b=2.png"/>"#,
+            ],
+        );
+    }
+
+    #[test]
+    fn clickables_are_skipped() {
+        assert_eq!(
+            body(&[Graphic::Clickable { x: 0.0, y: 0.0, w: 10.0, h: 10.0, action: ClickAction::Custom(String::from("foo")) }]),
+            Vec::<String>::new(),
+        );
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(escape_xml(""), "");
+        assert_eq!(escape_xml("plain text"), "plain text");
+        assert_eq!(escape_xml(r#"<a href="x">Tom & Jerry's</a>"#), "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;");
+        assert_eq!(escape_xml("한글 ✓"), "한글 ✓");
+    }
+
+    #[test]
+    fn base64_tails() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+        assert_eq!(base64(b"fooba"), "Zm9vYmE=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64(&[0, 255, 254]), "AP/+");
+    }
+}
@@ -0,0 +1,447 @@
+use macroquad::color::Color;
+
+const KEYWORD: Color = Color { r: 0.8, g: 0.5, b: 0.9, a: 1.0 };
+const STRING: Color = Color { r: 0.6, g: 0.85, b: 0.5, a: 1.0 };
+const NUMBER: Color = Color { r: 0.9, g: 0.7, b: 0.4, a: 1.0 };
+const COMMENT: Color = Color { r: 0.5, g: 0.5, b: 0.5, a: 1.0 };
+const TYPE: Color = Color { r: 0.4, g: 0.8, b: 0.8, a: 1.0 };
+const FUNCTION: Color = Color { r: 0.5, g: 0.7, b: 1.0, a: 1.0 };
+const KEY: Color = Color { r: 0.5, g: 0.7, b: 1.0, a: 1.0 };
+const SECTION: Color = Color { r: 0.9, g: 0.8, b: 0.4, a: 1.0 };
+
+/// A very simple tokenizer that colors source code. It doesn't parse anything,
+/// so it's fast enough to highlight a whole file every time the canvas is rendered.
+///
+/// Use `Syntax::from_language` for the built-in languages, or `Syntax::custom` for your own.
+/// `Syntax::highlight` returns a color map that you can give to `TextBox::with_color_map`.
+#[derive(Clone, Debug)]
+pub struct Syntax {
+    keywords: Vec<String>,
+
+    /// `true`, `false`, `None`, ...
+    literals: Vec<String>,
+    line_comment: Option<String>,
+    block_comment: Option<(String, String)>,
+    quotes: Vec<char>,
+
+    /// `"""` or `'''`
+    multiline_quotes: Vec<String>,
+
+    /// Rust has `'a'` and `'a`. If it's set, a quote followed by an identifier
+    /// is a lifetime, not a string.
+    char_literals: bool,
+
+    /// If it's set, identifiers that start with an uppercase letter are types.
+    capitalized_types: bool,
+
+    /// An identifier followed by `(` (or `!` in Rust) is a function call.
+    functions: bool,
+    keys: KeyStyle,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum KeyStyle {
+    None,
+
+    // `"key": value`
+    Json,
+
+    // `key = value` and `[section]`
+    Toml,
+}
+
+impl Syntax {
+    /// It supports "rust", "python", "json" and "toml". You can also use file
+    /// extensions ("rs", "py").
+    pub fn from_language(language: &str) -> Option<Syntax> {
+        match language.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Some(Syntax {
+                keywords: to_strings(&[
+                    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                    "enum", "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match",
+                    "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static",
+                    "struct", "super", "trait", "type", "unsafe", "use", "where", "while",
+                ]),
+                literals: to_strings(&["true", "false"]),
+                line_comment: Some(String::from("//")),
+                block_comment: Some((String::from("/*"), String::from("*/"))),
+                quotes: vec!['"', '\''],
+                multiline_quotes: vec![],
+                char_literals: true,
+                capitalized_types: true,
+                functions: true,
+                keys: KeyStyle::None,
+            }),
+            "python" | "py" => Some(Syntax {
+                keywords: to_strings(&[
+                    "and", "as", "assert", "async", "await", "break", "class", "continue", "def",
+                    "del", "elif", "else", "except", "finally", "for", "from", "global", "if",
+                    "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise",
+                    "return", "try", "while", "with", "yield",
+                ]),
+                literals: to_strings(&["True", "False", "None"]),
+                line_comment: Some(String::from("#")),
+                block_comment: None,
+                quotes: vec!['"', '\''],
+                multiline_quotes: to_strings(&["\"\"\"", "'''"]),
+                char_literals: false,
+                capitalized_types: true,
+                functions: true,
+                keys: KeyStyle::None,
+            }),
+            "json" => Some(Syntax {
+                keywords: vec![],
+                literals: to_strings(&["true", "false", "null"]),
+                line_comment: None,
+                block_comment: None,
+                quotes: vec!['"'],
+                multiline_quotes: vec![],
+                char_literals: false,
+                capitalized_types: false,
+                functions: false,
+                keys: KeyStyle::Json,
+            }),
+            "toml" => Some(Syntax {
+                keywords: vec![],
+                literals: to_strings(&["true", "false"]),
+                line_comment: Some(String::from("#")),
+                block_comment: None,
+                quotes: vec!['"', '\''],
+                multiline_quotes: to_strings(&["\"\"\"", "'''"]),
+                char_literals: false,
+                capitalized_types: false,
+                functions: false,
+                keys: KeyStyle::Toml,
+            }),
+            _ => None,
+        }
+    }
+
+    /// A C-like language with your own keywords: `//` and `/* */` comments,
+    /// `"` and `'` strings, and function calls.
+    pub fn custom(keywords: &[&str]) -> Syntax {
+        Syntax {
+            keywords: to_strings(keywords),
+            literals: vec![],
+            line_comment: Some(String::from("//")),
+            block_comment: Some((String::from("/*"), String::from("*/"))),
+            quotes: vec!['"', '\''],
+            multiline_quotes: vec![],
+            char_literals: false,
+            capitalized_types: false,
+            functions: true,
+            keys: KeyStyle::None,
+        }
+    }
+
+    pub fn with_literals(&mut self, literals: &[&str]) -> &mut Self {
+        self.literals = to_strings(literals);
+        self
+    }
+
+    pub fn with_line_comment(&mut self, line_comment: Option<&str>) -> &mut Self {
+        self.line_comment = line_comment.map(|c| c.to_string());
+        self
+    }
+
+    pub fn with_block_comment(&mut self, block_comment: Option<(&str, &str)>) -> &mut Self {
+        self.block_comment = block_comment.map(|(start, end)| (start.to_string(), end.to_string()));
+        self
+    }
+
+    /// It returns a color for each character of `s`. Characters that are not highlighted
+    /// (punctuations, plain identifiers, ...) get `default_color`.
+    pub fn highlight(&self, s: &str, default_color: Color) -> Vec<Color> {
+        self.highlight_tokens(s).into_iter().map(|color| color.unwrap_or(default_color)).collect()
+    }
+
+    /// Like `highlight`, but the characters that are not highlighted are `None`.
+    pub(crate) fn highlight_tokens(&self, s: &str) -> Vec<Option<Color>> {
+        let chars: Vec<char> = s.chars().collect();
+        let mut colors = vec![None; chars.len()];
+        let mut at_line_start = true;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let ch = chars[i];
+
+            if ch == '\n' {
+                at_line_start = true;
+                i += 1;
+                continue;
+            }
+
+            if ch.is_whitespace() {
+                i += 1;
+                continue;
+            }
+
+            let line_start = at_line_start;
+            at_line_start = false;
+
+            if let Some(line_comment) = &self.line_comment && starts_with(&chars, i, line_comment) {
+                let end = find_char(&chars, i, '\n');
+                fill(&mut colors, i, end, COMMENT);
+                i = end;
+                continue;
+            }
+
+            if let Some((start, end)) = &self.block_comment && starts_with(&chars, i, start) {
+                let end = find_str(&chars, i + start.chars().count(), end);
+                fill(&mut colors, i, end, COMMENT);
+                i = end;
+                continue;
+            }
+
+            if let KeyStyle::Toml = self.keys && line_start {
+                if ch == '[' {
+                    let end = (find_char(&chars, i, ']') + 1).min(find_char(&chars, i, '\n'));
+                    fill(&mut colors, i, end, SECTION);
+                    i = end;
+                    continue;
+                }
+
+                let line_end = find_char(&chars, i, '\n');
+
+                if let Some(eq) = (i..line_end).find(|j| chars[*j] == '=') {
+                    fill(&mut colors, i, eq, KEY);
+                    i = eq;
+                    continue;
+                }
+            }
+
+            if let Some(quote) = self.multiline_quotes.iter().find(|q| starts_with(&chars, i, q)) {
+                let end = find_str(&chars, i + quote.chars().count(), quote);
+                fill(&mut colors, i, end, STRING);
+                i = end;
+                continue;
+            }
+
+            if self.quotes.contains(&ch) {
+                // lifetime
+                if self.char_literals && ch == '\'' && !is_char_literal(&chars, i) {
+                    let end = find_identifier_end(&chars, i + 1);
+                    fill(&mut colors, i, end, TYPE);
+                    i = end;
+                    continue;
+                }
+
+                let end = find_string_end(&chars, i);
+                let color = match self.keys {
+                    KeyStyle::Json if next_non_whitespace(&chars, end) == Some(':') => KEY,
+                    _ => STRING,
+                };
+                fill(&mut colors, i, end, color);
+                i = end;
+                continue;
+            }
+
+            if ch.is_ascii_digit() || (ch == '-' && chars.get(i + 1).map(|c| c.is_ascii_digit()).unwrap_or(false)) {
+                let mut end = i + 1;
+
+                while end < chars.len() && (chars[end].is_ascii_alphanumeric() || chars[end] == '.' || chars[end] == '_') {
+                    end += 1;
+                }
+
+                fill(&mut colors, i, end, NUMBER);
+                i = end;
+                continue;
+            }
+
+            if ch.is_alphabetic() || ch == '_' {
+                let end = find_identifier_end(&chars, i);
+                let word = chars[i..end].iter().collect::<String>();
+                let color = if self.keywords.contains(&word) {
+                    Some(KEYWORD)
+                } else if self.literals.contains(&word) {
+                    Some(NUMBER)
+                } else if self.functions && matches!(chars.get(end), Some('(') | Some('!')) {
+                    Some(FUNCTION)
+                } else if self.capitalized_types && ch.is_uppercase() {
+                    Some(TYPE)
+                } else {
+                    None
+                };
+
+                if let Some(color) = color {
+                    fill(&mut colors, i, end, color);
+                }
+
+                i = end;
+                continue;
+            }
+
+            i += 1;
+        }
+
+        colors
+    }
+}
+
+fn to_strings(s: &[&str]) -> Vec<String> {
+    s.iter().map(|s| s.to_string()).collect()
+}
+
+fn fill(colors: &mut [Option<Color>], start: usize, end: usize, color: Color) {
+    for c in colors[start..end].iter_mut() {
+        *c = Some(color);
+    }
+}
+
+fn starts_with(chars: &[char], index: usize, s: &str) -> bool {
+    s.chars().enumerate().all(|(i, ch)| chars.get(index + i) == Some(&ch))
+}
+
+// It returns the index of `ch`, or `chars.len()` if there's no `ch`.
+fn find_char(chars: &[char], start: usize, ch: char) -> usize {
+    (start..chars.len()).find(|i| chars[*i] == ch).unwrap_or(chars.len())
+}
+
+// It returns the index right after `s`, or `chars.len()` if there's no `s`.
+fn find_str(chars: &[char], start: usize, s: &str) -> usize {
+    (start..chars.len()).find(|i| starts_with(chars, *i, s)).map(|i| i + s.chars().count()).unwrap_or(chars.len())
+}
+
+fn find_identifier_end(chars: &[char], start: usize) -> usize {
+    (start..chars.len()).find(|i| !(chars[*i].is_alphanumeric() || chars[*i] == '_')).unwrap_or(chars.len())
+}
+
+// `chars[start]` is the opening quote. It returns the index right after the closing quote.
+// A string literal cannot span multiple lines, unless the newline is escaped.
+fn find_string_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => { i += 2; },
+            '\n' => { return i; },
+            c if c == quote => { return i + 1; },
+            _ => { i += 1; },
+        }
+    }
+
+    chars.len()
+}
+
+// `'a'`, `'\n'` and `'\u{1b}'` are char literals, and `'a` is a lifetime.
+fn is_char_literal(chars: &[char], start: usize) -> bool {
+    match chars.get(start + 1) {
+        Some('\\') => true,
+        Some(_) => chars.get(start + 2) == Some(&'\''),
+        None => false,
+    }
+}
+
+fn next_non_whitespace(chars: &[char], start: usize) -> Option<char> {
+    chars[start..].iter().find(|ch| !ch.is_whitespace()).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use macroquad::color::WHITE;
+
+    // highlighted runs of `s`: the same color in a row is a single token
+    fn tokens(syntax: &Syntax, s: &str) -> Vec<(String, Color)> {
+        let mut result: Vec<(String, Color)> = vec![];
+        let mut prev = None;
+
+        for (ch, color) in s.chars().zip(syntax.highlight_tokens(s)) {
+            match color {
+                Some(color) if prev == Some(color) => { result.last_mut().unwrap().0.push(ch); },
+                Some(color) => { result.push((ch.to_string(), color)); },
+                None => {},
+            }
+
+            prev = color;
+        }
+
+        result
+    }
+
+    fn token(s: &str, color: Color) -> (String, Color) {
+        (s.to_string(), color)
+    }
+
+    #[test]
+    fn rust() {
+        let rust = Syntax::from_language("rs").unwrap();
+
+        assert_eq!(
+            tokens(&rust, "pub fn foo() -> Vec<u8> { bar(1, \"a\\\"b\") } // done"),
+            vec![
+                token("pub", KEYWORD),
+                token("fn", KEYWORD),
+                token("foo", FUNCTION),
+                token("Vec", TYPE),
+                token("bar", FUNCTION),
+                token("1", NUMBER),
+                token("\"a\\\"b\"", STRING),
+                token("// done", COMMENT),
+            ],
+        );
+        assert_eq!(
+            tokens(&rust, "let c = 'a'; let n = '\\n'; fn f<'a>(x: &'a str) {}"),
+            vec![
+                token("let", KEYWORD),
+                token("'a'", STRING),
+                token("let", KEYWORD),
+                token("'\\n'", STRING),
+                token("fn", KEYWORD),
+                token("'a", TYPE),
+                token("'a", TYPE),
+            ],
+        );
+        assert_eq!(
+            tokens(&rust, "/* a\nb */ println!(true, -3.5e2)"),
+            vec![
+                token("/* a\nb */", COMMENT),
+                token("println", FUNCTION),
+                token("true", NUMBER),
+                token("-3.5e2", NUMBER),
+            ],
+        );
+    }
+
+    #[test]
+    fn strings_end_at_newlines() {
+        let python = Syntax::from_language("python").unwrap();
+
+        assert_eq!(
+            tokens(&python, "x = \"abc\nif y"),
+            vec![token("\"abc", STRING), token("if", KEYWORD)],
+        );
+        assert_eq!(
+            tokens(&python, "\"\"\"a\nb\"\"\" None # c"),
+            vec![token("\"\"\"a\nb\"\"\"", STRING), token("None", NUMBER), token("# c", COMMENT)],
+        );
+    }
+
+    #[test]
+    fn keys() {
+        let json = Syntax::from_language("json").unwrap();
+        let toml = Syntax::from_language("toml").unwrap();
+
+        assert_eq!(
+            tokens(&json, "{\"a\" : \"b\", \"c\": [1, null]}"),
+            vec![token("\"a\"", KEY), token("\"b\"", STRING), token("\"c\"", KEY), token("1", NUMBER), token("null", NUMBER)],
+        );
+        assert_eq!(
+            tokens(&toml, "[package]\nname = \"x\" # y\n  [[bin]]"),
+            vec![token("[package]", SECTION), token("name ", KEY), token("\"x\"", STRING), token("# y", COMMENT), token("[[bin]", SECTION)],
+        );
+    }
+
+    #[test]
+    fn custom() {
+        let mut syntax = Syntax::custom(&["let"]);
+        syntax.with_literals(&["nil"]).with_line_comment(Some("--")).with_block_comment(None);
+
+        assert_eq!(
+            tokens(&syntax, "let Foo = nil /* -- x"),
+            vec![token("let", KEYWORD), token("nil", NUMBER), token("-- x", COMMENT)],
+        );
+        assert_eq!(syntax.highlight("a nil", WHITE), vec![WHITE, WHITE, NUMBER, NUMBER, NUMBER]);
+    }
+}
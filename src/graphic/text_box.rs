@@ -1,12 +1,27 @@
-use super::Graphic;
+use super::{CANVAS_HEIGHT, Graphic};
+use super::ansi::parse_ansi;
+use super::syntax::Syntax;
+use crate::transform::move_rel;
 use macroquad::color::Color;
+use std::ops::RangeInclusive;
 
 pub struct TextBox {
     s: String,
     font_size: f32,
     color: ColorMap,
+    background: Option<Vec<Option<Color>>>,
+    underline: Option<Vec<bool>>,
     rect: [f32; 4],
     padding: [f32; 4],
+
+    /// If it's set, there's a line-number gutter on the left side of the box,
+    /// and the first line is numbered with this value.
+    line_numbers: Option<usize>,
+
+    /// The line numbers here are the ones in the gutter (see `line_numbers`),
+    /// not the indexes of the lines.
+    highlights: Vec<(RangeInclusive<usize>, Color)>,
+    focused_line: Option<usize>,
 }
 
 enum ColorMap {
@@ -25,9 +40,40 @@ impl TextBox {
             s: s.to_string(),
             font_size,
             color: ColorMap::Simple(color),
+            background: None,
+            underline: None,
             rect,
             padding: [0.0; 4],
+            line_numbers: None,
+            highlights: vec![],
+            focused_line: None,
+        }
+    }
+
+    /// It parses ANSI escape sequences in `s` and renders the text like a terminal does.
+    /// It supports 8/16/256/24-bit foreground and background colors, bold, dim, underline,
+    /// inverse and reset. The other escape sequences (e.g. cursor movements) are removed.
+    ///
+    /// `color` is the default foreground color.
+    pub fn from_ansi(
+        s: &str,
+        font_size: f32,
+        color: Color,
+        rect: [f32; 4],
+    ) -> TextBox {
+        let parsed = parse_ansi(s, color);
+        let mut result = TextBox::new(&parsed.text, font_size, color, rect);
+        result.color = ColorMap::Each(parsed.colors);
+
+        if parsed.backgrounds.iter().any(|bg| bg.is_some()) {
+            result.background = Some(parsed.backgrounds);
+        }
+
+        if parsed.underlines.iter().any(|u| *u) {
+            result.underline = Some(parsed.underlines);
         }
+
+        result
     }
 
     pub fn with_color_map(&mut self, color_map: Vec<Color>) -> &mut Self {
@@ -35,64 +81,241 @@ impl TextBox {
         self
     }
 
+    /// It colors the text with `syntax`. Characters that are not highlighted
+    /// keep their colors (e.g. the colors from `from_ansi` or `with_color_map`).
+    pub fn with_syntax(&mut self, syntax: &Syntax) -> &mut Self {
+        let colors = syntax.highlight_tokens(&self.s).into_iter().enumerate().map(
+            |(i, color)| match (color, &self.color) {
+                (Some(color), _) => color,
+                (None, ColorMap::Simple(c)) => *c,
+                (None, ColorMap::Each(cs)) => cs.get(i).copied().unwrap_or(Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 }),
+            }
+        ).collect();
+
+        self.color = ColorMap::Each(colors);
+        self
+    }
+
+    /// Like `with_color_map`, but for the background of each character.
+    /// `None` means no background. If the map is shorter than the text, the rest has no background.
+    pub fn with_background_map(&mut self, background_map: Vec<Option<Color>>) -> &mut Self {
+        self.background = Some(background_map);
+        self
+    }
+
+    /// It adds a gutter with line numbers. The first line is numbered `start_line`.
+    /// If you're rendering a snippet of a file, set `start_line` to the line
+    /// number of the snippet in the file, so that the numbers match the compiler errors.
+    pub fn with_line_numbers(&mut self, start_line: usize) -> &mut Self {
+        self.line_numbers = Some(start_line);
+        self
+    }
+
+    /// It draws a background color behind the lines. You can call this multiple times
+    /// to highlight multiple ranges. If ranges overlap, the later one is drawn on top.
+    ///
+    /// The line numbers are the ones in the gutter. If there's no gutter, the first line is 1.
+    pub fn highlight_lines(&mut self, lines: RangeInclusive<usize>, color: Color) -> &mut Self {
+        self.highlights.push((lines, color));
+        self
+    }
+
+    /// If the line is below the canvas (900x600), it moves the entire text box up so that
+    /// the line is visible when the entry is opened. The user can still see the lines
+    /// above by moving the camera.
+    pub fn scroll_to_line(&mut self, line: usize) -> &mut Self {
+        self.focused_line = Some(line);
+        self
+    }
+
     pub fn render(&self) -> Vec<Graphic> {
         let [x, y, w, h] = self.rect;
         let [top, bottom, left, right] = self.padding;
+        let [x, y, w, h] = [x + left, y + top, w - left - right, h - top - bottom];
+        let first_line = self.line_numbers.unwrap_or(1);
+        let char_width = self.font_size * 0.55;
+        let line_height = self.font_size * 1.1;
 
-        text_box(
+        let mut result = vec![];
+        let gutter_width = match self.line_numbers {
+            Some(_) => {
+                let last_line = first_line + self.s.chars().filter(|ch| *ch == '\n').count();
+                (last_line.to_string().len() + 2) as f32 * char_width
+            },
+            None => 0.0,
+        };
+        let text = text_box(
             &self.s,
             self.font_size,
-            &self.color,
-            [x + left, y + top, w - left - right, h - top - bottom],
-        )
+            &CharStyles {
+                color: &self.color,
+                background: &self.background,
+                underline: &self.underline,
+            },
+            [x + gutter_width, y, w - gutter_width, h],
+        );
+        let line_count = text.line_count;
+
+        for (lines, color) in self.highlights.iter() {
+            for i in 0..line_count {
+                if lines.contains(&(first_line + i)) {
+                    result.push(Graphic::Rect {
+                        x,
+                        y: y + i as f32 * line_height - self.font_size * 0.85,
+                        w,
+                        h: line_height,
+                        radius: None,
+                        thickness: None,
+                        color: *color,
+                    });
+                }
+            }
+        }
+
+        if self.line_numbers.is_some() {
+            let gutter_color = Color { r: 0.5, g: 0.5, b: 0.5, a: 1.0 };
+
+            for i in 0..line_count {
+                let line_no = (first_line + i).to_string();
+                let mut curr_x = x + gutter_width - (line_no.len() + 1) as f32 * char_width;
+
+                for ch in line_no.chars() {
+                    result.push(Graphic::Char {
+                        ch,
+                        x: curr_x,
+                        y: y + i as f32 * line_height,
+                        size: self.font_size,
+                        color: gutter_color,
+                    });
+                    curr_x += char_width;
+                }
+            }
+
+            result.push(Graphic::Rect {
+                x: x + gutter_width - char_width * 0.5,
+                y: y - self.font_size * 0.85,
+                w: 1.0,
+                h: line_count as f32 * line_height,
+                radius: None,
+                thickness: None,
+                color: gutter_color,
+            });
+        }
+
+        result.extend(text.graphics);
+
+        if let Some(line) = self.focused_line && line >= first_line {
+            // If the line is already visible, we don't have to scroll.
+            let line_y = y + (line - first_line) as f32 * line_height;
+
+            // It moves the line to the upper third of the canvas.
+            if line_y > CANVAS_HEIGHT - line_height {
+                move_rel(&mut result, 0.0, CANVAS_HEIGHT / 3.0 - line_y);
+            }
+        }
+
+        result
     }
 }
 
+struct RenderedText {
+    graphics: Vec<Graphic>,
+    line_count: usize,
+}
+
+struct CharStyles<'a> {
+    color: &'a ColorMap,
+    background: &'a Option<Vec<Option<Color>>>,
+    underline: &'a Option<Vec<bool>>,
+}
+
+#[derive(Clone, Copy)]
+struct CharStyle {
+    color: Color,
+    background: Option<Color>,
+    underline: bool,
+}
+
 fn text_box(
     s: &str,
     font_size: f32,
-    color: &ColorMap,
+    styles: &CharStyles,
     rect: [f32; 4],
-) -> Vec<Graphic> {
+) -> RenderedText {
     let [x, y, w, h] = rect;
     let mut result = vec![];
     let max_x = (w / (font_size * 0.55) - 1.0).max(4.0) as usize - 4;
     let max_y = (h / (font_size * 1.1) - 1.0).max(1.0) as usize - 1;
-    let (s, colors) = break_lines_and_apply_colors(s, color, max_x, max_y);
+    let (s, styles) = break_lines_and_apply_styles(s, styles, max_x, max_y);
     let mut curr_y = y;
     let mut curr_x = x;
+    let mut line_count = 1;
 
-    for (ch, color) in s.chars().zip(colors.iter()) {
+    for (ch, style) in s.chars().zip(styles.iter()) {
         if ch == '\n' {
             curr_x = x;
             curr_y += font_size * 1.1;
+            line_count += 1;
             continue;
         }
 
+        if let Some(background) = style.background {
+            result.push(Graphic::Rect {
+                x: curr_x,
+                y: curr_y - font_size * 0.85,
+                w: font_size * 0.55,
+                h: font_size * 1.1,
+                radius: None,
+                thickness: None,
+                color: background,
+            });
+        }
+
         result.push(Graphic::Char {
             ch,
             x: curr_x,
             y: curr_y,
             size: font_size,
-            color: *color,
+            color: style.color,
         });
+
+        if style.underline {
+            result.push(Graphic::Rect {
+                x: curr_x,
+                y: curr_y + font_size * 0.15,
+                w: font_size * 0.55,
+                h: (font_size * 0.07).max(1.0),
+                radius: None,
+                thickness: None,
+                color: style.color,
+            });
+        }
+
         curr_x += font_size * 0.55;
     }
 
-    result
+    RenderedText {
+        graphics: result,
+        line_count: line_count.min(max_y + 1),
+    }
 }
 
-fn break_lines_and_apply_colors(s: &str, color: &ColorMap, max_x: usize, max_y: usize) -> (String, Vec<Color>) {
+fn break_lines_and_apply_styles(s: &str, styles: &CharStyles, max_x: usize, max_y: usize) -> (String, Vec<CharStyle>) {
     let mut curr_x = 0;
     let mut curr_y = 0;
     let mut chars = vec![];
-    let mut colors = vec![];
+    let mut result = vec![];
     let mut line_broken = false;
 
     for (i, ch) in s.chars().enumerate() {
-        let color = match color {
-            ColorMap::Simple(c) => *c,
-            ColorMap::Each(cs) => cs[i],
+        let style = CharStyle {
+            color: match styles.color {
+                ColorMap::Simple(c) => *c,
+                ColorMap::Each(cs) => cs[i],
+            },
+            // The background and underline maps may be shorter than the text.
+            background: styles.background.as_ref().and_then(|bg| bg.get(i).copied().flatten()),
+            underline: styles.underline.as_ref().and_then(|u| u.get(i).copied()).unwrap_or(false),
         };
 
         if ch == '\n' {
@@ -100,7 +323,7 @@ fn break_lines_and_apply_colors(s: &str, color: &ColorMap, max_x: usize, max_y:
             curr_y += 1;
             line_broken = false;
             chars.push(ch);
-            colors.push(color);
+            result.push(style);
 
             if curr_y > max_y {
                 break;
@@ -115,7 +338,7 @@ fn break_lines_and_apply_colors(s: &str, color: &ColorMap, max_x: usize, max_y:
             if curr_x > max_x {
                 for _ in 0..3 {
                     chars.push('.');
-                    colors.push(color);
+                    result.push(style);
                 }
 
                 line_broken = true;
@@ -123,10 +346,10 @@ fn break_lines_and_apply_colors(s: &str, color: &ColorMap, max_x: usize, max_y:
             }
 
             chars.push(ch);
-            colors.push(color);
+            result.push(style);
             curr_x += 1;
         }
     }
 
-    (chars.into_iter().collect(), colors)
+    (chars.into_iter().collect(), result)
 }
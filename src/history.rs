@@ -0,0 +1,434 @@
+use crate::entry::{Entries, Entry, EntryFlag, Transition};
+use crate::graphic::{ClickAction, GRAY, GREEN, Graphic, RED, WHITE, flag_color, push_text, truncate};
+use macroquad::color::Color;
+use std::collections::{HashMap, HashSet};
+
+// (run, cursor, entry): an entry in a run
+type Point<'a> = (&'a Entries, usize, &'a Entry);
+
+pub fn is_tmp_entries(id: &str) -> bool {
+    id.starts_with("@@tmp-")
+}
+
+/// A tmp entries (e.g. a filter view) is removed when the user leaves it, so we have to
+/// find the original entries that it came from.
+pub fn original_entries_id(entries_map: &HashMap<String, Entries>, id: &str) -> String {
+    let mut id = id.to_string();
+
+    while is_tmp_entries(&id) {
+        match entries_map.get(&id).and_then(|entries| entries.transition.as_ref()) {
+            Some(transition) if transition.id != id => { id = transition.id.to_string(); },
+            _ => break,
+        }
+    }
+
+    id
+}
+
+/// It returns the runs that `curr_entries_id` is compared with, from the oldest to the newest.
+/// If `curr_entries_id` is a run of a suite, they are the runs of the suite. Otherwise,
+/// it's every `Entries` (except tmp ones), sorted by id (see `Entries::run`).
+pub fn runs<'a>(entries_map: &'a HashMap<String, Entries>, curr_entries_id: &str) -> Vec<&'a Entries> {
+    let curr_entries_id = original_entries_id(entries_map, curr_entries_id);
+    let suite = entries_map.get(&curr_entries_id).and_then(|entries| entries.run.as_ref()).map(|run| run.suite.to_string());
+    let mut runs: Vec<&Entries> = entries_map.values().filter(
+        |entries| !is_tmp_entries(&entries.id) && match &suite {
+            Some(suite) => entries.run.as_ref().map(|run| &run.suite == suite).unwrap_or(false),
+            None => true,
+        }
+    ).collect();
+
+    runs.sort_by_key(|entries| (entries.run.as_ref().map(|run| run.order), entries.id.to_string()));
+    runs
+}
+
+/// It creates a tmp `Entries` that has an entry per run, and each entry is the entry
+/// named `name` in that run. The canvas draws the timeline of the flags.
+///
+/// The timeline is only meaningful if the runs have `Entries::run`. Otherwise, they're
+/// sorted by id, and the canvas says so.
+pub fn history_entries(
+    entries_map: &HashMap<String, Entries>,
+    curr_entries_id: &str,
+    name: &str,
+) -> Result<(Entries, usize), String> {
+    let original_id = original_entries_id(entries_map, curr_entries_id);
+    let mut points = vec![];
+
+    for run in runs(entries_map, curr_entries_id).into_iter() {
+        if let Some((cursor, entry)) = run.iter().enumerate().find(|(_, e)| e.name == name) {
+            points.push((run, cursor, entry));
+        }
+    }
+
+    if points.len() < 2 {
+        return Err(format!("There's no other run that has `{name}`."));
+    }
+
+    let curr_index = points.iter().position(|(run, _, _)| run.id == original_id).unwrap_or(points.len() - 1);
+    let entries = points.iter().enumerate().map(
+        |(i, (run, _, entry))| Entry {
+            name: run_title(run),
+            transition1: Some(Transition {
+                id: run.id.to_string(),
+                description: Some(String::from("go to this run")),
+            }),
+            flag: entry.flag,
+            canvas: Some(render_timeline(name, &points, i)),
+            ..Entry::default()
+        }
+    ).collect();
+
+    Ok((
+        Entries {
+            id: format!("@@tmp-{:x}", rand::random::<u64>()),
+            title: Some(format!("History of {name}")),
+            entries,
+            transition: Some(Transition {
+                id: original_id,
+                description: Some(String::from("exit history view")),
+            }),
+            ..Entries::default()
+        },
+        curr_index,
+    ))
+}
+
+/// It creates a tmp `Entries` of the entries whose flag flips between red and green
+/// across the runs, sorted by the number of flips. The canvas is the same as the history view.
+///
+/// The flips depend on the order of the runs, so the current `Entries` must have `Entries::run`.
+/// Then every compared run has it (see `runs`).
+pub fn flaky_entries(
+    entries_map: &HashMap<String, Entries>,
+    curr_entries_id: &str,
+) -> Result<Entries, String> {
+    let original_id = original_entries_id(entries_map, curr_entries_id);
+
+    if entries_map.get(&original_id).and_then(|entries| entries.run.as_ref()).is_none() {
+        return Err(String::from("These entries are not a run of a suite (`Entries::run`), so the runs can't be ordered."));
+    }
+
+    let runs = runs(entries_map, curr_entries_id);
+
+    if runs.len() < 2 {
+        return Err(String::from("There's only one run. You need at least 2 runs to find flaky entries."));
+    }
+
+    // name -> points, in run order
+    let mut points_by_name: HashMap<&str, Vec<Point>> = HashMap::new();
+
+    for run in runs.iter() {
+        let mut seen = HashSet::new();
+
+        for (cursor, entry) in run.iter().enumerate() {
+            // If names are not unique, only the first one is used.
+            if seen.insert(entry.name.as_str()) {
+                points_by_name.entry(entry.name.as_str()).or_default().push((run, cursor, entry));
+            }
+        }
+    }
+
+    let mut flaky: Vec<(&str, usize, Vec<Point>)> = points_by_name.into_iter().map(
+        |(name, points)| (name, count_flips(&points.iter().map(|(_, _, entry)| entry.flag).collect::<Vec<_>>()), points)
+    ).filter(
+        |(_, flips, _)| *flips > 0
+    ).collect();
+
+    if flaky.is_empty() {
+        return Err(format!("There's no flaky entry in {} runs.", runs.len()));
+    }
+
+    flaky.sort_by(|(name1, flips1, _), (name2, flips2, _)| flips2.cmp(flips1).then(name1.cmp(name2)));
+
+    let entries = flaky.iter().map(
+        |(name, flips, points)| Entry {
+            name: name.to_string(),
+            // It's shown in the top bar.
+            content: Some(format!("flipped {flips} time{} in {} runs", if *flips == 1 { "" } else { "s" }, points.len())),
+            flag: points.last().map(|(_, _, entry)| entry.flag).unwrap_or(EntryFlag::None),
+            canvas: Some(render_timeline(name, points, points.len() - 1)),
+            ..Entry::default()
+        }
+    ).collect();
+
+    Ok(Entries {
+        id: format!("@@tmp-{:x}", rand::random::<u64>()),
+        title: Some(format!("Flaky tests ({} runs)", runs.len())),
+        entries,
+        transition: Some(Transition {
+            id: original_id,
+            description: Some(String::from("exit flaky tests view")),
+        }),
+        render_top_bar_extra_message: Some(|entry, _| entry.content.clone()),
+        ..Entries::default()
+    })
+}
+
+// It counts how many times the flag changes between red and green. The other flags are ignored.
+fn count_flips(flags: &[EntryFlag]) -> usize {
+    let flags: Vec<EntryFlag> = flags.iter().copied().filter(|f| matches!(f, EntryFlag::Red | EntryFlag::Green)).collect();
+
+    flags.windows(2).filter(|w| w[0] != w[1]).count()
+}
+
+// It draws the flags of `points` on a horizontal line, and `selected` is circled.
+fn render_timeline(name: &str, points: &[Point], selected: usize) -> Vec<Graphic> {
+    let runs: Vec<(&str, String, usize, EntryFlag)> = points.iter().map(
+        |(run, cursor, entry)| (run.id.as_str(), run_title(run), *cursor, entry.flag)
+    ).collect();
+    let flags: Vec<EntryFlag> = runs.iter().map(|(_, _, _, flag)| *flag).collect();
+    let mut graphics = vec![];
+
+    let (left, right, axis_y) = (60.0, 840.0, 300.0);
+    let spacing = if runs.len() > 1 { (right - left) / (runs.len() - 1) as f32 } else { 0.0 };
+    let x_of = |i: usize| if runs.len() > 1 { left + spacing * i as f32 } else { (left + right) / 2.0 };
+    let radius = (spacing * 0.4).clamp(2.0, 8.0);
+
+    push_text(&mut graphics, name, 30.0, 50.0, 21.0, WHITE);
+    push_text(
+        &mut graphics,
+        &format!(
+            "{} runs: {} passed, {} failed, {} other",
+            runs.len(),
+            flags.iter().filter(|f| **f == EntryFlag::Green).count(),
+            flags.iter().filter(|f| **f == EntryFlag::Red).count(),
+            flags.iter().filter(|f| **f != EntryFlag::Green && **f != EntryFlag::Red).count(),
+        ),
+        30.0,
+        80.0,
+        16.0,
+        GRAY,
+    );
+
+    graphics.push(Graphic::Line {
+        p1: (left - 20.0, axis_y),
+        p2: (right + 20.0, axis_y),
+        thickness: 2.0,
+        dash: None,
+        color: GRAY,
+    });
+
+    let (last_passing, first_failing) = find_regression(&flags);
+
+    for (index, label, color, label_y) in [
+        (last_passing, "last passing", GREEN, axis_y - 60.0),
+        (first_failing, "first failing", RED, axis_y + 70.0),
+    ] {
+        if let Some(index) = index {
+            let x = x_of(index);
+            graphics.push(Graphic::Line {
+                p1: (x, axis_y - 45.0),
+                p2: (x, axis_y + 45.0),
+                thickness: 1.5,
+                dash: Some((5.0, 4.0)),
+                color,
+            });
+            centered_text(&mut graphics, label, x, label_y, 14.0, color);
+            centered_text(&mut graphics, &truncate(&runs[index].1, 24), x, label_y + 18.0, 12.0, GRAY);
+        }
+    }
+
+    for (i, (id, _, cursor, flag)) in runs.iter().enumerate() {
+        let x = x_of(i);
+
+        graphics.push(Graphic::Ellipse {
+            x,
+            y: axis_y,
+            rx: radius,
+            ry: radius,
+            thickness: None,
+            color: flag_color(*flag),
+        });
+
+        if i == selected {
+            graphics.push(Graphic::Ellipse {
+                x,
+                y: axis_y,
+                rx: radius + 5.0,
+                ry: radius + 5.0,
+                thickness: Some(2.0),
+                color: WHITE,
+            });
+        }
+
+        graphics.push(Graphic::Clickable {
+            x: x - (spacing / 2.0).clamp(radius, 20.0),
+            y: axis_y - 20.0,
+            w: (spacing / 2.0).clamp(radius, 20.0) * 2.0,
+            h: 40.0,
+            action: ClickAction::Transit {
+                id: id.to_string(),
+                cursor: Some(*cursor),
+            },
+        });
+    }
+
+    if let Some((_, first, _, _)) = runs.first() {
+        push_text(&mut graphics, &truncate(first, 30), left - 20.0, axis_y + 150.0, 12.0, GRAY);
+    }
+
+    if let Some((_, last, _, _)) = runs.last() {
+        let last = truncate(last, 30);
+        push_text(&mut graphics, &last, right + 20.0 - last.chars().count() as f32 * 6.6, axis_y + 150.0, 12.0, GRAY);
+    }
+
+    if let Some((_, title, _, flag)) = runs.get(selected) {
+        push_text(&mut graphics, &format!("selected: {title} ({})", flag_to_str(*flag)), 30.0, 520.0, 16.0, WHITE);
+    }
+
+    push_text(&mut graphics, "Click a point to open the entry in that run.", 30.0, 550.0, 14.0, GRAY);
+
+    if points.iter().any(|(run, _, _)| run.run.is_none()) {
+        push_text(&mut graphics, "The runs are sorted by id, because they don't have `Entries::run`.", 30.0, 110.0, 14.0, RED);
+    }
+
+    graphics
+}
+
+// It returns (last passing run, first failing run) of the latest regression.
+// If the entry has never passed, there's no last passing run.
+fn find_regression(flags: &[EntryFlag]) -> (Option<usize>, Option<usize>) {
+    let last_red = flags.iter().rposition(|f| *f == EntryFlag::Red);
+
+    match last_red {
+        Some(last_red) => match flags[..last_red].iter().rposition(|f| *f == EntryFlag::Green) {
+            Some(last_green) => (
+                Some(last_green),
+                flags[last_green..].iter().position(|f| *f == EntryFlag::Red).map(|i| i + last_green),
+            ),
+            None => (None, flags.iter().position(|f| *f == EntryFlag::Red)),
+        },
+        None => (None, None),
+    }
+}
+
+fn run_title(run: &Entries) -> String {
+    run.title.clone().unwrap_or_else(|| run.id.to_string())
+}
+
+fn flag_to_str(flag: EntryFlag) -> &'static str {
+    match flag {
+        EntryFlag::None => "none",
+        EntryFlag::Red => "red",
+        EntryFlag::Green => "green",
+        EntryFlag::Blue => "blue",
+    }
+}
+
+fn centered_text(graphics: &mut Vec<Graphic>, s: &str, x: f32, y: f32, size: f32, color: Color) {
+    push_text(graphics, s, x - s.chars().count() as f32 * size * 0.275, y, size, color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::SuiteRun;
+    use EntryFlag::{Blue, Green, None as NoFlag, Red};
+
+    fn run(id: &str, order: Option<i64>, entries: &[(&str, EntryFlag)]) -> Entries {
+        Entries {
+            id: id.to_string(),
+            entries: entries.iter().map(
+                |(name, flag)| Entry { name: name.to_string(), flag: *flag, ..Entry::default() }
+            ).collect(),
+            run: order.map(|order| SuiteRun { suite: String::from("suite"), order }),
+            ..Entries::default()
+        }
+    }
+
+    fn entries_map(runs: Vec<Entries>) -> HashMap<String, Entries> {
+        runs.into_iter().map(|entries| (entries.id.to_string(), entries)).collect()
+    }
+
+    fn ids(runs: &[&Entries]) -> Vec<String> {
+        runs.iter().map(|entries| entries.id.to_string()).collect()
+    }
+
+    #[test]
+    fn regression() {
+        assert_eq!(find_regression(&[Green, Green, Red, Red]), (Some(1), Some(2)));
+        assert_eq!(find_regression(&[Red, Green, NoFlag, Red, Green]), (Some(1), Some(3)));
+        assert_eq!(find_regression(&[Red, Blue, Red]), (None, Some(0)));
+        assert_eq!(find_regression(&[Green, Blue]), (None, None));
+        assert_eq!(find_regression(&[]), (None, None));
+    }
+
+    #[test]
+    fn tmp_entries_and_runs() {
+        let mut tmp1 = run("@@tmp-1", None, &[]);
+        tmp1.transition = Some(Transition { id: String::from("b"), description: None });
+        let mut tmp2 = run("@@tmp-2", None, &[]);
+        tmp2.transition = Some(Transition { id: String::from("@@tmp-1"), description: None });
+
+        let entries_map = entries_map(vec![
+            run("b", Some(1), &[]),
+            run("a", Some(2), &[]),
+            run("c", Some(0), &[]),
+            run("other", None, &[]),
+            tmp1,
+            tmp2,
+        ]);
+
+        assert!(is_tmp_entries("@@tmp-linked-1"));
+        assert_eq!(original_entries_id(&entries_map, "@@tmp-2"), "b");
+        assert_eq!(original_entries_id(&entries_map, "@@tmp-gone"), "@@tmp-gone");
+        assert_eq!(ids(&runs(&entries_map, "@@tmp-2")), vec!["c", "b", "a"]);
+        assert_eq!(ids(&runs(&entries_map, "other")), vec!["other", "c", "b", "a"]);
+    }
+
+    #[test]
+    fn history() {
+        let entries_map = entries_map(vec![
+            run("r1", Some(1), &[("x", Green), ("y", Green)]),
+            run("r2", Some(2), &[("x", Red)]),
+            run("r3", Some(3), &[("y", Red), ("x", Green)]),
+        ]);
+
+        let (entries, cursor) = history_entries(&entries_map, "r2", "x").unwrap();
+        assert!(is_tmp_entries(&entries.id));
+        assert_eq!(cursor, 1);
+        assert_eq!(entries.entries.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(), vec!["r1", "r2", "r3"]);
+        assert_eq!(entries.entries.iter().map(|entry| entry.flag).collect::<Vec<_>>(), vec![Green, Red, Green]);
+        assert_eq!(entries.transition.map(|transition| transition.id), Some(String::from("r2")));
+
+        // "r2" doesn't have "y", so the newest one is selected.
+        assert_eq!(history_entries(&entries_map, "r2", "y").unwrap().1, 1);
+        assert!(history_entries(&entries_map, "r1", "z").is_err());
+    }
+
+    #[test]
+    fn flips() {
+        assert_eq!(count_flips(&[Green, Red, Green]), 2);
+        assert_eq!(count_flips(&[Green, NoFlag, Blue, Green, Red]), 1);
+        assert_eq!(count_flips(&[Red, Red]), 0);
+        assert_eq!(count_flips(&[]), 0);
+    }
+
+    #[test]
+    fn flaky() {
+        let entries_map = entries_map(vec![
+            run("r1", Some(1), &[("x", Green), ("y", Green), ("z", Red), ("x", Red)]),
+            run("r2", Some(2), &[("x", Red), ("y", Green), ("z", Green)]),
+            run("r3", Some(3), &[("x", Green), ("y", Green)]),
+        ]);
+
+        let flaky = flaky_entries(&entries_map, "r3").unwrap();
+        assert!(is_tmp_entries(&flaky.id));
+        assert_eq!(flaky.entries.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(), vec!["x", "z"]);
+        assert_eq!(flaky.entries[0].content.as_deref(), Some("flipped 2 times in 3 runs"));
+        assert_eq!(flaky.entries[1].content.as_deref(), Some("flipped 1 time in 2 runs"));
+        assert_eq!(flaky.entries[1].flag, Green);
+    }
+
+    #[test]
+    fn no_flaky_entries() {
+        let stable = entries_map(vec![run("r1", Some(1), &[("x", Green)]), run("r2", Some(2), &[("x", Green)])]);
+        let unordered = entries_map(vec![run("r1", None, &[("x", Green)]), run("r2", None, &[("x", Red)])]);
+        let single = entries_map(vec![run("r1", Some(1), &[("x", Green)])]);
+
+        assert!(flaky_entries(&stable, "r1").is_err());
+        assert!(flaky_entries(&unordered, "r1").is_err());
+        assert!(flaky_entries(&single, "r1").is_err());
+    }
+}
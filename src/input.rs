@@ -1,6 +1,7 @@
 use macroquad::input::{
     KeyCode,
     MouseButton,
+    get_char_pressed,
     get_keys_down,
     get_keys_pressed,
     get_keys_released,
@@ -22,9 +23,20 @@ pub struct Input {
     pub down_keys: HashSet<KeyCode>,
     pub pressed_keys: HashSet<KeyCode>,
     pub released_keys: HashSet<KeyCode>,
+
+    /// The characters that the user typed in this frame (for text prompts).
+    pub typed_chars: Vec<char>,
 }
 
 pub fn get_input() -> Input {
+    let mut typed_chars = vec![];
+
+    while let Some(ch) = get_char_pressed() {
+        if !ch.is_control() {
+            typed_chars.push(ch);
+        }
+    }
+
     Input {
         mouse_pos: mouse_position(),
         mouse_wheel: mouse_wheel(),
@@ -46,5 +58,6 @@ pub fn get_input() -> Input {
         down_keys: get_keys_down(),
         pressed_keys: get_keys_pressed(),
         released_keys: get_keys_released(),
+        typed_chars,
     }
 }
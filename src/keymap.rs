@@ -0,0 +1,635 @@
+use crate::input::Input;
+use macroquad::input::KeyCode;
+use std::fmt;
+
+/// Everything that the user can do with a keyboard.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Command {
+    /// It's triggered when the key is released, not pressed. If the help message is on,
+    /// it closes the help message instead.
+    Quit,
+    ToggleHelp,
+
+    /// If the user holds the key, the cursor keeps moving.
+    NextEntry,
+    PrevEntry,
+
+    /// 1 ~ 9. It jumps to (n - 1) / 8 of the list.
+    QuickJump(usize),
+    ExpandSideBar,
+    ShrinkSideBar,
+
+    /// The camera commands are triggered every frame while the key is held.
+    /// If Shift is held too, the camera moves (or zooms) faster.
+    MoveCameraUp,
+    MoveCameraLeft,
+    MoveCameraDown,
+    MoveCameraRight,
+    ZoomIn,
+    ZoomOut,
+    ResetCamera,
+    NextEntryState,
+    PrevEntryState,
+
+    /// `Entries::transition`
+    Transit,
+
+    /// `Entry::transition1`
+    Transit1,
+
+    /// `Entry::transition2`
+    Transit2,
+
+    /// 1 ~ 9. `Entries::filters[n - 1]`
+    Filter(usize),
+    ShowHistory,
+    FindFlakyEntries,
+    ToggleDashboard,
+    OpenPalette,
+
+    /// It opens a prompt that fuzzy-matches `Entry::name`.
+    SearchEntry,
+
+    /// Browser-style back/forward across `Entries`. It restores the cursor, the entry
+    /// state and the camera too. The side buttons of the mouse are not supported (see
+    /// "Known issues" in README.md).
+    GoBack,
+    GoForward,
+
+    /// Pins are (`Entries::id`, `Entry::name`), and they're collected in the "Pinned" entries.
+    TogglePin,
+    ShowPinned,
+
+    /// It opens a text-input overlay for the note and the issue ids of the entry.
+    EditAnnotation,
+
+    /// It shows the entries that share an issue with the current entry.
+    ShowIssue,
+
+    /// 1 ~ 9. It puts `Config::triage_labels[n - 1]` on the entry. If the entry already
+    /// has the label, it's removed.
+    SetTriageLabel(usize),
+    ClearTriageLabel,
+
+    /// It filters the entries that have the same triage label as the current entry
+    /// (or no label, if the current entry has none).
+    FilterByTriageLabel,
+
+    /// They move the cursor and select the entries between the cursor and where the
+    /// selection started. Like `NextEntry`, the cursor keeps moving while the key is held.
+    ExtendSelectionDown,
+    ExtendSelectionUp,
+
+    /// It selects/unselects the current entry. Ctrl+click does the same.
+    ToggleSelection,
+    SelectAll,
+
+    /// `Quit` clears the selection too, if there is one.
+    ClearSelection,
+
+    /// It creates a tmp entries with the selected entries.
+    ShowSelection,
+
+    /// It copies the names of the selected entries (or the current entry) to the clipboard,
+    /// one per line.
+    CopyEntryName,
+
+    /// `Entry::content` of the current entry
+    CopyEntryContent,
+
+    /// The text of the canvas (see `graphics_to_text`). The user can also drag the mouse
+    /// on the canvas to copy the text in an area.
+    CopyCanvasText,
+
+    /// It writes the current `Entries` (or every `Entries`) to an html file in
+    /// `Config::export_dir` (see `export_html`).
+    ExportHtml,
+    ExportAllHtml,
+
+    /// It writes the shown canvas (or the dashboard) to an svg file in `Config::export_dir`
+    /// (see `graphics_to_svg`).
+    ExportCanvasSvg,
+
+    /// 1 ~ 9. `Entries::actions[n - 1]`
+    RunEntryAction(usize),
+}
+
+impl Command {
+    /// Every command, in the order of the help message.
+    pub fn all() -> Vec<Command> {
+        let mut result = vec![
+            Command::Quit,
+            Command::ToggleHelp,
+            Command::NextEntry,
+            Command::PrevEntry,
+        ];
+        result.extend((1..=9).map(Command::QuickJump));
+        result.extend([
+            Command::ExpandSideBar,
+            Command::ShrinkSideBar,
+            Command::MoveCameraUp,
+            Command::MoveCameraLeft,
+            Command::MoveCameraDown,
+            Command::MoveCameraRight,
+            Command::ZoomIn,
+            Command::ZoomOut,
+            Command::ResetCamera,
+            Command::NextEntryState,
+            Command::PrevEntryState,
+            Command::Transit,
+            Command::Transit1,
+            Command::Transit2,
+        ]);
+        result.extend((1..=9).map(Command::Filter));
+        result.extend([
+            Command::ShowHistory,
+            Command::FindFlakyEntries,
+            Command::ToggleDashboard,
+            Command::OpenPalette,
+            Command::SearchEntry,
+            Command::GoBack,
+            Command::GoForward,
+            Command::TogglePin,
+            Command::ShowPinned,
+            Command::EditAnnotation,
+            Command::ShowIssue,
+        ]);
+        result.extend((1..=9).map(Command::SetTriageLabel));
+        result.extend([
+            Command::ClearTriageLabel,
+            Command::FilterByTriageLabel,
+            Command::ExtendSelectionDown,
+            Command::ExtendSelectionUp,
+            Command::ToggleSelection,
+            Command::SelectAll,
+            Command::ClearSelection,
+            Command::ShowSelection,
+            Command::CopyEntryName,
+            Command::CopyEntryContent,
+            Command::CopyCanvasText,
+            Command::ExportHtml,
+            Command::ExportAllHtml,
+            Command::ExportCanvasSvg,
+        ]);
+        result.extend((1..=9).map(Command::RunEntryAction));
+        result
+    }
+
+    /// The name in keymap files (e.g. "next_entry", "filter_3").
+    pub fn name(&self) -> String {
+        match self {
+            Command::Quit => String::from("quit"),
+            Command::ToggleHelp => String::from("toggle_help"),
+            Command::NextEntry => String::from("next_entry"),
+            Command::PrevEntry => String::from("prev_entry"),
+            Command::QuickJump(n) => format!("quick_jump_{n}"),
+            Command::ExpandSideBar => String::from("expand_side_bar"),
+            Command::ShrinkSideBar => String::from("shrink_side_bar"),
+            Command::MoveCameraUp => String::from("move_camera_up"),
+            Command::MoveCameraLeft => String::from("move_camera_left"),
+            Command::MoveCameraDown => String::from("move_camera_down"),
+            Command::MoveCameraRight => String::from("move_camera_right"),
+            Command::ZoomIn => String::from("zoom_in"),
+            Command::ZoomOut => String::from("zoom_out"),
+            Command::ResetCamera => String::from("reset_camera"),
+            Command::NextEntryState => String::from("next_entry_state"),
+            Command::PrevEntryState => String::from("prev_entry_state"),
+            Command::Transit => String::from("transit"),
+            Command::Transit1 => String::from("transit1"),
+            Command::Transit2 => String::from("transit2"),
+            Command::Filter(n) => format!("filter_{n}"),
+            Command::ShowHistory => String::from("show_history"),
+            Command::FindFlakyEntries => String::from("find_flaky_entries"),
+            Command::ToggleDashboard => String::from("toggle_dashboard"),
+            Command::OpenPalette => String::from("open_palette"),
+            Command::SearchEntry => String::from("search_entry"),
+            Command::GoBack => String::from("go_back"),
+            Command::GoForward => String::from("go_forward"),
+            Command::TogglePin => String::from("toggle_pin"),
+            Command::ShowPinned => String::from("show_pinned"),
+            Command::EditAnnotation => String::from("edit_annotation"),
+            Command::ShowIssue => String::from("show_issue"),
+            Command::SetTriageLabel(n) => format!("set_triage_label_{n}"),
+            Command::ClearTriageLabel => String::from("clear_triage_label"),
+            Command::FilterByTriageLabel => String::from("filter_by_triage_label"),
+            Command::ExtendSelectionDown => String::from("extend_selection_down"),
+            Command::ExtendSelectionUp => String::from("extend_selection_up"),
+            Command::ToggleSelection => String::from("toggle_selection"),
+            Command::SelectAll => String::from("select_all"),
+            Command::ClearSelection => String::from("clear_selection"),
+            Command::ShowSelection => String::from("show_selection"),
+            Command::CopyEntryName => String::from("copy_entry_name"),
+            Command::CopyEntryContent => String::from("copy_entry_content"),
+            Command::CopyCanvasText => String::from("copy_canvas_text"),
+            Command::ExportHtml => String::from("export_html"),
+            Command::ExportAllHtml => String::from("export_all_html"),
+            Command::ExportCanvasSvg => String::from("export_canvas_svg"),
+            Command::RunEntryAction(n) => format!("run_entry_action_{n}"),
+        }
+    }
+
+    /// It's shown in the command palette.
+    pub fn description(&self) -> String {
+        match self {
+            Command::Quit => String::from("Quit"),
+            Command::ToggleHelp => String::from("Show/hide help message"),
+            Command::NextEntry => String::from("Jump to next entry"),
+            Command::PrevEntry => String::from("Jump to prev entry"),
+            Command::QuickJump(n) => format!("Quick jump to {}%", (n - 1) * 100 / 8),
+            Command::ExpandSideBar => String::from("Expand side-bar"),
+            Command::ShrinkSideBar => String::from("Shrink side-bar"),
+            Command::MoveCameraUp => String::from("Move camera up"),
+            Command::MoveCameraLeft => String::from("Move camera left"),
+            Command::MoveCameraDown => String::from("Move camera down"),
+            Command::MoveCameraRight => String::from("Move camera right"),
+            Command::ZoomIn => String::from("Zoom in"),
+            Command::ZoomOut => String::from("Zoom out"),
+            Command::ResetCamera => String::from("Reset camera"),
+            Command::NextEntryState => String::from("Go to next entry state"),
+            Command::PrevEntryState => String::from("Go to prev entry state"),
+            Command::Transit => String::from("Transit to the parent entries"),
+            Command::Transit1 => String::from("Transit to the first transition of this entry"),
+            Command::Transit2 => String::from("Transit to the second transition of this entry"),
+            Command::Filter(n) => format!("Apply filter {n}"),
+            Command::ShowHistory => String::from("See the history of this entry across runs"),
+            Command::FindFlakyEntries => String::from("Find flaky entries across runs"),
+            Command::ToggleDashboard => String::from("Show/hide the summary of the entries"),
+            Command::OpenPalette => String::from("Open command palette"),
+            Command::SearchEntry => String::from("Search entries by name"),
+            Command::GoBack => String::from("Go back"),
+            Command::GoForward => String::from("Go forward"),
+            Command::TogglePin => String::from("Pin/unpin this entry"),
+            Command::ShowPinned => String::from("Show pinned entries"),
+            Command::EditAnnotation => String::from("Edit the note and the issues of this entry"),
+            Command::ShowIssue => String::from("Show the entries linked to the issues of this entry"),
+            Command::SetTriageLabel(n) => format!("Set triage label {n}"),
+            Command::ClearTriageLabel => String::from("Clear the triage label of this entry"),
+            Command::FilterByTriageLabel => String::from("Filter the entries with the same triage label"),
+            Command::ExtendSelectionDown => String::from("Extend the selection down"),
+            Command::ExtendSelectionUp => String::from("Extend the selection up"),
+            Command::ToggleSelection => String::from("Select/unselect this entry"),
+            Command::SelectAll => String::from("Select all entries"),
+            Command::ClearSelection => String::from("Clear the selection"),
+            Command::ShowSelection => String::from("Show the selected entries"),
+            Command::CopyEntryName => String::from("Copy the names of the selected entries"),
+            Command::CopyEntryContent => String::from("Copy the content of this entry"),
+            Command::CopyCanvasText => String::from("Copy the text of the canvas"),
+            Command::ExportHtml => String::from("Export these entries to an html file"),
+            Command::ExportAllHtml => String::from("Export every entries to an html file"),
+            Command::ExportCanvasSvg => String::from("Export the canvas to an svg file"),
+            Command::RunEntryAction(n) => format!("Run action {n}"),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Command> {
+        Command::all().into_iter().find(|command| command.name() == name)
+    }
+
+    /// The camera commands are triggered while the key is held, not when it's pressed.
+    pub fn is_continuous(&self) -> bool {
+        matches!(
+            self,
+            Command::MoveCameraUp
+            | Command::MoveCameraLeft
+            | Command::MoveCameraDown
+            | Command::MoveCameraRight
+            | Command::ZoomIn
+            | Command::ZoomOut
+        )
+    }
+}
+
+/// A key with modifiers. It's written like "Ctrl+Shift+P" or "J".
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct KeyChord {
+    pub key: KeyCode,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyChord {
+    pub fn new(key: KeyCode) -> KeyChord {
+        KeyChord { key, ctrl: false, shift: false, alt: false }
+    }
+
+    pub fn ctrl(key: KeyCode) -> KeyChord {
+        KeyChord { key, ctrl: true, shift: false, alt: false }
+    }
+
+    pub fn shift(key: KeyCode) -> KeyChord {
+        KeyChord { key, ctrl: false, shift: true, alt: false }
+    }
+
+    pub fn alt(key: KeyCode) -> KeyChord {
+        KeyChord { key, ctrl: false, shift: false, alt: true }
+    }
+
+    pub fn parse(s: &str) -> Result<KeyChord, String> {
+        let mut chord = KeyChord::new(KeyCode::Unknown);
+        let mut key = None;
+
+        for token in s.split('+').map(|token| token.trim()) {
+            match token.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => { chord.ctrl = true; },
+                "shift" => { chord.shift = true; },
+                "alt" | "option" => { chord.alt = true; },
+                _ => match KEY_NAMES.iter().find(|(name, _)| name.eq_ignore_ascii_case(token)) {
+                    Some((_, key_code)) if key.is_none() => { key = Some(*key_code); },
+                    Some(_) => { return Err(format!("`{s}` has more than one key.")); },
+                    None => { return Err(format!("Unknown key `{token}` in `{s}`.")); },
+                },
+            }
+        }
+
+        chord.key = key.ok_or_else(|| format!("`{s}` has no key."))?;
+        Ok(chord)
+    }
+
+    pub(crate) fn is_pressed(&self, input: &Input) -> bool {
+        input.pressed_keys.contains(&self.key) && self.modifiers_match(input, false)
+    }
+
+    fn modifiers_match(&self, input: &Input, ignore_shift: bool) -> bool {
+        let (ctrl, shift, alt) = modifiers(input);
+        self.ctrl == ctrl && self.alt == alt && (ignore_shift || self.shift == shift)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let key = KEY_NAMES.iter().find(|(_, key_code)| *key_code == self.key).map(|(name, _)| *name).unwrap_or("?");
+
+        write!(
+            f,
+            "{}{}{}{key}",
+            if self.ctrl { "Ctrl+" } else { "" },
+            if self.shift { "Shift+" } else { "" },
+            if self.alt { "Alt+" } else { "" },
+        )
+    }
+}
+
+/// It maps key chords to commands. A chord has at most one command, but a command can
+/// have multiple chords.
+///
+/// `Keymap::default()` has the default key bindings. You can override them with a keymap
+/// file (see `Keymap::load`). Each line of the file is a chord and a command name, like
+///
+/// ```text
+/// # vim-like navigation
+/// J next_entry
+/// K prev_entry
+///
+/// # Mac intercepts Ctrl+arrows
+/// Shift+Left transit1
+/// Ctrl+Left none
+/// ```
+///
+/// `none` removes the binding, and a line `clear` removes every binding above it.
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    bindings: Vec<(KeyChord, Command)>,
+}
+
+impl Keymap {
+    pub fn empty() -> Keymap {
+        Keymap { bindings: vec![] }
+    }
+
+    /// It reads a keymap file and applies it to the default keymap.
+    pub fn from_file(path: &str) -> Result<Keymap, String> {
+        let s = std::fs::read_to_string(path).map_err(|e| format!("Failed to read `{path}`: {e:?}"))?;
+        let mut keymap = Keymap::default();
+        keymap.load(&s)?;
+        Ok(keymap)
+    }
+
+    /// It applies the lines of a keymap file to `self`.
+    pub fn load(&mut self, s: &str) -> Result<(), String> {
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line == "clear" {
+                self.bindings.clear();
+                continue;
+            }
+
+            let (chord, command) = line.split_once(char::is_whitespace).ok_or_else(
+                || format!("line {}: expected `<chord> <command>`, got `{line}`", i + 1)
+            )?;
+            let chord = KeyChord::parse(chord).map_err(|e| format!("line {}: {e}", i + 1))?;
+
+            match command.trim() {
+                "none" => { self.unbind(chord); },
+                command => match Command::from_name(command) {
+                    Some(command) => { self.bind(chord, command); },
+                    None => { return Err(format!("line {}: unknown command `{command}`", i + 1)); },
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    /// If `chord` already has a command, it's replaced.
+    pub fn bind(&mut self, chord: KeyChord, command: Command) -> &mut Self {
+        self.unbind(chord);
+        self.bindings.push((chord, command));
+        self
+    }
+
+    pub fn unbind(&mut self, chord: KeyChord) -> &mut Self {
+        self.bindings.retain(|(c, _)| *c != chord);
+        self
+    }
+
+    pub fn chords(&self, command: Command) -> Vec<KeyChord> {
+        self.bindings.iter().filter(|(_, c)| *c == command).map(|(chord, _)| *chord).collect()
+    }
+
+    /// It joins the chords of the commands with "/" (e.g. "W/A/S/D"). It's empty if none
+    /// of the commands is bound.
+    pub fn describe(&self, commands: &[Command]) -> String {
+        commands.iter().flat_map(|command| self.chords(*command)).map(|chord| chord.to_string()).collect::<Vec<_>>().join("/")
+    }
+
+    /// The commands that are triggered in this frame, except the continuous ones.
+    pub(crate) fn triggered(&self, input: &Input) -> Vec<Command> {
+        self.bindings.iter().filter(
+            |(chord, command)| !command.is_continuous() && chord.modifiers_match(input, false) && match command {
+                Command::Quit => input.released_keys.contains(&chord.key),
+                _ => input.pressed_keys.contains(&chord.key),
+            }
+        ).map(|(_, command)| *command).collect()
+    }
+
+    /// Is one of the chords of `command` held? Shift is ignored because it
+    /// means "faster" for the continuous commands.
+    pub(crate) fn is_held(&self, command: Command, input: &Input) -> bool {
+        self.bindings.iter().any(
+            |(chord, c)| *c == command && input.down_keys.contains(&chord.key) && chord.modifiers_match(input, true)
+        )
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        let mut keymap = Keymap::empty();
+        let num_keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9];
+
+        for (chord, command) in [
+            (KeyChord::new(KeyCode::Escape), Command::Quit),
+            (KeyChord::new(KeyCode::H), Command::ToggleHelp),
+            (KeyChord::new(KeyCode::Down), Command::NextEntry),
+            (KeyChord::new(KeyCode::Up), Command::PrevEntry),
+            (KeyChord::new(KeyCode::Left), Command::ExpandSideBar),
+            (KeyChord::new(KeyCode::Right), Command::ShrinkSideBar),
+            (KeyChord::new(KeyCode::W), Command::MoveCameraUp),
+            (KeyChord::new(KeyCode::A), Command::MoveCameraLeft),
+            (KeyChord::new(KeyCode::S), Command::MoveCameraDown),
+            (KeyChord::new(KeyCode::D), Command::MoveCameraRight),
+            (KeyChord::new(KeyCode::Z), Command::ZoomIn),
+            (KeyChord::new(KeyCode::X), Command::ZoomOut),
+            (KeyChord::new(KeyCode::Space), Command::ResetCamera),
+            (KeyChord::new(KeyCode::M), Command::NextEntryState),
+            (KeyChord::new(KeyCode::N), Command::PrevEntryState),
+            (KeyChord::ctrl(KeyCode::Up), Command::Transit),
+            (KeyChord::ctrl(KeyCode::Left), Command::Transit1),
+            (KeyChord::ctrl(KeyCode::Right), Command::Transit2),
+            (KeyChord::new(KeyCode::T), Command::ShowHistory),
+            (KeyChord::new(KeyCode::F), Command::FindFlakyEntries),
+            (KeyChord::new(KeyCode::I), Command::ToggleDashboard),
+            (KeyChord::ctrl(KeyCode::P), Command::OpenPalette),
+            (KeyChord::new(KeyCode::Slash), Command::SearchEntry),
+            (KeyChord::alt(KeyCode::Left), Command::GoBack),
+            (KeyChord::alt(KeyCode::Right), Command::GoForward),
+            (KeyChord::new(KeyCode::P), Command::TogglePin),
+            (KeyChord::shift(KeyCode::P), Command::ShowPinned),
+            (KeyChord::new(KeyCode::E), Command::EditAnnotation),
+            (KeyChord::shift(KeyCode::E), Command::ShowIssue),
+            (KeyChord::shift(KeyCode::Key0), Command::ClearTriageLabel),
+            (KeyChord::new(KeyCode::L), Command::FilterByTriageLabel),
+            (KeyChord::shift(KeyCode::Down), Command::ExtendSelectionDown),
+            (KeyChord::shift(KeyCode::Up), Command::ExtendSelectionUp),
+            (KeyChord::ctrl(KeyCode::Space), Command::ToggleSelection),
+            (KeyChord::ctrl(KeyCode::A), Command::SelectAll),
+            (KeyChord::new(KeyCode::V), Command::ShowSelection),
+            (KeyChord::ctrl(KeyCode::C), Command::CopyEntryName),
+            (KeyChord { shift: true, ..KeyChord::ctrl(KeyCode::C) }, Command::CopyEntryContent),
+            (KeyChord::alt(KeyCode::C), Command::CopyCanvasText),
+            (KeyChord::ctrl(KeyCode::E), Command::ExportHtml),
+            (KeyChord { shift: true, ..KeyChord::ctrl(KeyCode::E) }, Command::ExportAllHtml),
+            (KeyChord::alt(KeyCode::E), Command::ExportCanvasSvg),
+        ] {
+            keymap.bind(chord, command);
+        }
+
+        for (i, key) in num_keys.iter().enumerate() {
+            keymap.bind(KeyChord::new(*key), Command::QuickJump(i + 1));
+            keymap.bind(KeyChord::ctrl(*key), Command::Filter(i + 1));
+            keymap.bind(KeyChord::shift(*key), Command::SetTriageLabel(i + 1));
+            keymap.bind(KeyChord::alt(*key), Command::RunEntryAction(i + 1));
+        }
+
+        keymap
+    }
+}
+
+/// (ctrl, shift, alt)
+pub(crate) fn modifiers(input: &Input) -> (bool, bool, bool) {
+    (
+        input.down_keys.contains(&KeyCode::LeftControl) || input.down_keys.contains(&KeyCode::RightControl),
+        input.down_keys.contains(&KeyCode::LeftShift) || input.down_keys.contains(&KeyCode::RightShift),
+        input.down_keys.contains(&KeyCode::LeftAlt) || input.down_keys.contains(&KeyCode::RightAlt),
+    )
+}
+
+const KEY_NAMES: [(&str, KeyCode); 69] = [
+    ("A", KeyCode::A), ("B", KeyCode::B), ("C", KeyCode::C), ("D", KeyCode::D),
+    ("E", KeyCode::E), ("F", KeyCode::F), ("G", KeyCode::G), ("H", KeyCode::H),
+    ("I", KeyCode::I), ("J", KeyCode::J), ("K", KeyCode::K), ("L", KeyCode::L),
+    ("M", KeyCode::M), ("N", KeyCode::N), ("O", KeyCode::O), ("P", KeyCode::P),
+    ("Q", KeyCode::Q), ("R", KeyCode::R), ("S", KeyCode::S), ("T", KeyCode::T),
+    ("U", KeyCode::U), ("V", KeyCode::V), ("W", KeyCode::W), ("X", KeyCode::X),
+    ("Y", KeyCode::Y), ("Z", KeyCode::Z),
+    ("0", KeyCode::Key0), ("1", KeyCode::Key1), ("2", KeyCode::Key2), ("3", KeyCode::Key3),
+    ("4", KeyCode::Key4), ("5", KeyCode::Key5), ("6", KeyCode::Key6), ("7", KeyCode::Key7),
+    ("8", KeyCode::Key8), ("9", KeyCode::Key9),
+    ("F1", KeyCode::F1), ("F2", KeyCode::F2), ("F3", KeyCode::F3), ("F4", KeyCode::F4),
+    ("F5", KeyCode::F5), ("F6", KeyCode::F6), ("F7", KeyCode::F7), ("F8", KeyCode::F8),
+    ("F9", KeyCode::F9), ("F10", KeyCode::F10), ("F11", KeyCode::F11), ("F12", KeyCode::F12),
+    ("Up", KeyCode::Up), ("Down", KeyCode::Down), ("Left", KeyCode::Left), ("Right", KeyCode::Right),
+    ("Space", KeyCode::Space), ("Enter", KeyCode::Enter), ("Escape", KeyCode::Escape), ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace), ("Delete", KeyCode::Delete), ("Home", KeyCode::Home), ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp), ("PageDown", KeyCode::PageDown),
+    ("Minus", KeyCode::Minus), ("Equal", KeyCode::Equal), ("Comma", KeyCode::Comma), ("Period", KeyCode::Period),
+    ("Slash", KeyCode::Slash), ("LeftBracket", KeyCode::LeftBracket), ("RightBracket", KeyCode::RightBracket),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_chord() {
+        assert_eq!(KeyChord::parse("J"), Ok(KeyChord::new(KeyCode::J)));
+        assert_eq!(KeyChord::parse("ctrl+p"), Ok(KeyChord::ctrl(KeyCode::P)));
+        assert_eq!(KeyChord::parse("Shift + Left"), Ok(KeyChord::shift(KeyCode::Left)));
+        assert_eq!(KeyChord::parse("Option+F5"), Ok(KeyChord::alt(KeyCode::F5)));
+        assert_eq!(
+            KeyChord::parse("Alt+Shift+Control+PageDown"),
+            Ok(KeyChord { key: KeyCode::PageDown, ctrl: true, shift: true, alt: true }),
+        );
+
+        assert!(KeyChord::parse("Ctrl").is_err());
+        assert!(KeyChord::parse("").is_err());
+        assert!(KeyChord::parse("A+B").is_err());
+        assert!(KeyChord::parse("Ctrl+Hyper").is_err());
+    }
+
+    #[test]
+    fn display_chord() {
+        assert_eq!(KeyChord::new(KeyCode::Key1).to_string(), "1");
+        assert_eq!(KeyChord::ctrl(KeyCode::P).to_string(), "Ctrl+P");
+        assert_eq!(KeyChord { key: KeyCode::E, ctrl: true, shift: true, alt: true }.to_string(), "Ctrl+Shift+Alt+E");
+        assert_eq!(KeyChord::new(KeyCode::Kp0).to_string(), "?");
+
+        for (name, _) in KEY_NAMES.iter() {
+            let chord = KeyChord { key: KeyChord::parse(name).unwrap().key, ctrl: true, shift: false, alt: true };
+            assert_eq!(KeyChord::parse(&chord.to_string()), Ok(chord));
+        }
+    }
+
+    #[test]
+    fn command_names() {
+        for command in Command::all() {
+            assert_eq!(Command::from_name(&command.name()), Some(command));
+        }
+    }
+
+    #[test]
+    fn load_keymap() {
+        let mut keymap = Keymap::default();
+        keymap.load("
+            # vim-like navigation
+            J next_entry
+            Down none
+            Ctrl+Down next_entry
+        ").unwrap();
+
+        assert_eq!(keymap.describe(&[Command::NextEntry]), "J/Ctrl+Down");
+        assert_eq!(keymap.chords(Command::PrevEntry), vec![KeyChord::new(KeyCode::Up)]);
+
+        keymap.load("clear\nK prev_entry").unwrap();
+        assert_eq!(keymap.describe(&[Command::NextEntry, Command::PrevEntry]), "K");
+
+        assert!(keymap.load("J").is_err());
+        assert!(keymap.load("J no_such_command").is_err());
+        assert!(keymap.load("Hyper+J next_entry").is_err());
+    }
+}
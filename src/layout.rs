@@ -0,0 +1,256 @@
+use crate::graphic::{CANVAS_HEIGHT, CANVAS_WIDTH};
+
+/// Where the side bar (the list of entries) is.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SideBarPosition {
+    /// `Right` if the window is landscape, and `Bottom` if it's portrait.
+    Auto,
+    Right,
+    Left,
+    Bottom,
+}
+
+/// The borders that the user can drag.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Splitter {
+    /// between the canvas and the side bar
+    SideBar,
+
+    /// under the top bar
+    TopBar,
+}
+
+/// The rects of the top bar, the side bar and the canvas, in the design space.
+///
+/// The shorter side of the design space is always 720, and the longer side follows the
+/// aspect ratio of the window (e.g. 1080x720 for a 3:2 window, and 720x1280 for a 9:16 one),
+/// so there are no black bars.
+#[derive(Clone, Copy, Debug)]
+pub struct Layout {
+    pub side_bar_position: SideBarPosition,
+    pub wide_side_bar: bool,
+    pub top_bar: [f32; 4],
+    pub side_bar: [f32; 4],
+    pub canvas: [f32; 4],
+}
+
+pub const DEFAULT_TOP_BAR_HEIGHT: f32 = 120.0;
+pub const MIN_TOP_BAR_HEIGHT: f32 = 60.0;
+pub const MAX_TOP_BAR_HEIGHT: f32 = 240.0;
+
+// The side bar and the canvas can't be smaller than this.
+const MIN_SIDE_BAR_SIZE: f32 = 120.0;
+const MIN_CANVAS_SIZE: f32 = 200.0;
+
+/// It returns the size of the design space for the real window size.
+pub fn design_size(window_width: f32, window_height: f32) -> (f32, f32) {
+    if window_width <= 0.0 || window_height <= 0.0 {
+        (1080.0, 720.0)
+    }
+
+    else if window_width >= window_height {
+        (720.0 * window_width / window_height, 720.0)
+    }
+
+    else {
+        (720.0, 720.0 * window_height / window_width)
+    }
+}
+
+impl Layout {
+    /// If `side_bar_size` is set (the user has dragged the splitter), it's used instead of
+    /// the preset size of `wide_side_bar`. It's the width of the side bar, or the height if
+    /// the side bar is at the bottom.
+    pub fn new(
+        screen_width: f32,
+        screen_height: f32,
+        side_bar_position: SideBarPosition,
+        wide_side_bar: bool,
+        side_bar_size: Option<f32>,
+        top_bar_height: f32,
+    ) -> Layout {
+        let top_bar_height = top_bar_height.clamp(MIN_TOP_BAR_HEIGHT, MAX_TOP_BAR_HEIGHT);
+
+        let side_bar_position = match side_bar_position {
+            SideBarPosition::Auto if screen_width < screen_height => SideBarPosition::Bottom,
+            SideBarPosition::Auto => SideBarPosition::Right,
+            p => p,
+        };
+
+        let (top_bar, side_bar, canvas) = match side_bar_position {
+            SideBarPosition::Right | SideBarPosition::Left => {
+                let preset = if wide_side_bar { 480.0 } else { 180.0 };
+                let side_bar_width = side_bar_size.unwrap_or(preset).max(MIN_SIDE_BAR_SIZE).min(screen_width - MIN_CANVAS_SIZE).max(0.0);
+                let rest = screen_width - side_bar_width;
+                let (side_bar_x, rest_x) = if side_bar_position == SideBarPosition::Right {
+                    (rest, 0.0)
+                } else {
+                    (0.0, side_bar_width)
+                };
+
+                (
+                    [rest_x, 0.0, rest, top_bar_height],
+                    [side_bar_x, 0.0, side_bar_width, screen_height],
+                    [rest_x, top_bar_height, rest, screen_height - top_bar_height],
+                )
+            },
+            SideBarPosition::Bottom => {
+                let preset = if wide_side_bar { screen_height * 0.5 } else { 240.0 };
+                let side_bar_height = side_bar_size.unwrap_or(preset).max(MIN_SIDE_BAR_SIZE).min(screen_height - top_bar_height - MIN_CANVAS_SIZE).max(0.0);
+                let canvas_height = screen_height - top_bar_height - side_bar_height;
+
+                (
+                    [0.0, 0.0, screen_width, top_bar_height],
+                    [0.0, top_bar_height + canvas_height, screen_width, side_bar_height],
+                    [0.0, top_bar_height, screen_width, canvas_height],
+                )
+            },
+            SideBarPosition::Auto => unreachable!(),
+        };
+
+        Layout {
+            side_bar_position,
+            wide_side_bar,
+            top_bar,
+            side_bar,
+            canvas,
+        }
+    }
+
+    /// The camera position is mapped to this point of the screen.
+    pub fn canvas_center(&self) -> (f32, f32) {
+        let [x, y, w, h] = self.canvas;
+        (x + w / 2.0, y + h / 2.0)
+    }
+
+    /// If the camera zoom is 1, the 900x600 canvas fits the canvas rect.
+    pub fn canvas_scale(&self) -> f32 {
+        let [_, _, w, h] = self.canvas;
+        (w / CANVAS_WIDTH).min(h / CANVAS_HEIGHT).max(0.01)
+    }
+
+    /// The area where the user can grab the splitter.
+    pub fn splitter(&self, splitter: Splitter) -> [f32; 4] {
+        match splitter {
+            Splitter::SideBar => {
+                let [x, y, w, h] = self.side_bar;
+
+                match self.side_bar_position {
+                    SideBarPosition::Right => [x - 4.0, y, 8.0, h],
+                    SideBarPosition::Left => [x + w - 4.0, y, 8.0, h],
+                    _ => [x, y - 4.0, w, 8.0],
+                }
+            },
+            Splitter::TopBar => {
+                let [x, y, w, h] = self.top_bar;
+                [x, y + h - 4.0, w, 8.0]
+            },
+        }
+    }
+
+    /// It returns the size of the side bar (or the height of the top bar) if the user drags
+    /// the splitter to `mouse_pos`. `Layout::new` clamps the size.
+    pub fn drag(&self, splitter: Splitter, (mouse_x, mouse_y): (f32, f32), (screen_width, screen_height): (f32, f32)) -> f32 {
+        match (splitter, self.side_bar_position) {
+            (Splitter::SideBar, SideBarPosition::Left) => mouse_x,
+            (Splitter::SideBar, SideBarPosition::Bottom) => screen_height - mouse_y,
+            (Splitter::SideBar, _) => screen_width - mouse_x,
+            (Splitter::TopBar, _) => mouse_y.clamp(MIN_TOP_BAR_HEIGHT, MAX_TOP_BAR_HEIGHT),
+        }
+    }
+
+    /// Is the side bar closer to the wide preset than to the narrow one?
+    pub fn is_side_bar_wide(&self) -> bool {
+        let [_, y, w, h] = self.side_bar;
+
+        match self.side_bar_position {
+            SideBarPosition::Bottom => h * 2.0 >= 240.0 + (y + h) * 0.5,
+            _ => w * 2.0 >= 180.0 + 480.0,
+        }
+    }
+
+    /// The button that toggles `wide_side_bar`. It's on the border between the side bar and the canvas.
+    pub fn toggle_button(&self) -> [f32; 4] {
+        let [x, y, w, h] = self.side_bar;
+
+        match self.side_bar_position {
+            SideBarPosition::Right => [x - 16.0, y + h / 2.0 - 16.0, 32.0, 32.0],
+            SideBarPosition::Left => [x + w - 16.0, y + h / 2.0 - 16.0, 32.0, 32.0],
+            _ => [x + w / 2.0 - 16.0, y - 16.0, 32.0, 32.0],
+        }
+    }
+
+    /// The arrow on the toggle button. It points the direction that the border will move.
+    pub fn toggle_button_arrow(&self) -> [(f32, f32); 3] {
+        let [x, y, _, _] = self.toggle_button();
+        let (l, c, r) = (6.0, 16.0, 26.0);
+
+        // Does the side bar grow toward the left/top of the screen?
+        let grows_backward = matches!(self.side_bar_position, SideBarPosition::Right | SideBarPosition::Bottom);
+        let backward = grows_backward != self.wide_side_bar;
+
+        match (self.side_bar_position, backward) {
+            (SideBarPosition::Bottom, true) => [(x + l, y + r), (x + r, y + r), (x + c, y + l)],
+            (SideBarPosition::Bottom, false) => [(x + l, y + l), (x + r, y + l), (x + c, y + r)],
+            (_, true) => [(x + r, y + l), (x + r, y + r), (x + l, y + c)],
+            (_, false) => [(x + l, y + l), (x + l, y + r), (x + r, y + c)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(screen_width: f32, screen_height: f32, side_bar_position: SideBarPosition, wide_side_bar: bool) -> Layout {
+        Layout::new(screen_width, screen_height, side_bar_position, wide_side_bar, None, DEFAULT_TOP_BAR_HEIGHT)
+    }
+
+    #[test]
+    fn design_sizes() {
+        assert_eq!(design_size(1920.0, 1080.0), (1280.0, 720.0));
+        assert_eq!(design_size(1080.0, 1920.0), (720.0, 1280.0));
+        assert_eq!(design_size(0.0, 100.0), (1080.0, 720.0));
+    }
+
+    #[test]
+    fn auto_position() {
+        assert_eq!(layout(1080.0, 720.0, SideBarPosition::Auto, false).side_bar_position, SideBarPosition::Right);
+        assert_eq!(layout(720.0, 1280.0, SideBarPosition::Auto, false).side_bar_position, SideBarPosition::Bottom);
+        assert_eq!(layout(720.0, 1280.0, SideBarPosition::Left, false).side_bar_position, SideBarPosition::Left);
+    }
+
+    #[test]
+    fn rects() {
+        let right = layout(1080.0, 720.0, SideBarPosition::Right, false);
+        assert_eq!(right.top_bar, [0.0, 0.0, 900.0, 120.0]);
+        assert_eq!(right.side_bar, [900.0, 0.0, 180.0, 720.0]);
+        assert_eq!(right.canvas, [0.0, 120.0, 900.0, 600.0]);
+        assert_eq!(right.canvas_scale(), 1.0);
+        assert_eq!(right.canvas_center(), (450.0, 420.0));
+
+        let left = layout(1080.0, 720.0, SideBarPosition::Left, true);
+        assert_eq!(left.side_bar, [0.0, 0.0, 480.0, 720.0]);
+        assert_eq!(left.canvas, [480.0, 120.0, 600.0, 600.0]);
+
+        let bottom = layout(720.0, 1280.0, SideBarPosition::Bottom, false);
+        assert_eq!(bottom.side_bar, [0.0, 1040.0, 720.0, 240.0]);
+        assert_eq!(bottom.canvas, [0.0, 120.0, 720.0, 920.0]);
+        assert_eq!(bottom.canvas_scale(), 0.8);
+    }
+
+    #[test]
+    fn the_canvas_is_never_too_small() {
+        let right = layout(600.0, 720.0, SideBarPosition::Right, true);
+        assert_eq!(right.side_bar[2], 400.0);
+        assert_eq!(right.canvas[2], 200.0);
+
+        let bottom = layout(720.0, 400.0, SideBarPosition::Bottom, true);
+        assert_eq!(bottom.side_bar[3], 80.0);
+        assert_eq!(bottom.canvas[3], 200.0);
+
+        let tiny = layout(100.0, 100.0, SideBarPosition::Right, false);
+        assert_eq!(tiny.side_bar[2], 0.0);
+        assert!(tiny.canvas_scale() > 0.0);
+    }
+}
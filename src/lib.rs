@@ -10,24 +10,57 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 mod action;
+mod annotation;
 mod cache;
 mod config;
 mod entry;
+mod entry_action;
+mod export;
 mod filter;
+mod fuzzy;
 mod graphic;
+mod history;
 mod input;
+mod keymap;
+mod layout;
+mod navigation;
+mod palette;
+mod pin;
+mod search;
 mod state;
 mod transform;
+mod triage;
 
 use action::Action;
+use annotation::Annotations;
 use cache::{RenderCache, TextureCache};
 pub use macroquad::color::Color;
 pub use config::Config;
-pub use entry::{Entries, Entry, EntryFlag, EntryState, Transition};
+pub use entry::{CanvasState, Entries, Entry, EntryFlag, EntryState, SuiteRun, Transition};
+pub use entry_action::{ActionOutput, EntryAction};
+pub use export::{entries_to_html, export_html, export_svg};
 pub use filter::Filter;
-pub use graphic::{Graphic, TextBox};
+pub use keymap::{Command, KeyChord, Keymap};
+pub use layout::SideBarPosition;
+pub use triage::TriageLabel;
+pub use graphic::{
+    Chart,
+    ClickAction,
+    Graphic,
+    JsonTree,
+    Markdown,
+    Syntax,
+    TextBox,
+    graphics_to_svg,
+    graphics_to_text,
+    render_dashboard,
+    render_json_tree,
+};
 use graphic::hide_off_screen;
 use input::get_input;
+use navigation::Navigation;
+use palette::Palette;
+use pin::Pins;
 use state::State;
 use transform::{fit_graphics_to_screen, fit_input_to_screen};
 
@@ -63,70 +96,255 @@ async fn run_inner(
     };
     let mut state = State {
         curr_entries_id: initial_entries_id.to_string(),
+        original_entries_id: initial_entries_id.to_string(),
         cursor: 0,
+        selection: vec![],
+        selection_anchor: None,
+        text_selection: None,
+        running_actions: vec![],
         entry_state: EntryState(0),
         wide_side_bar: false,
+        side_bar_size: None,
+        top_bar_height: layout::DEFAULT_TOP_BAR_HEIGHT,
+        dragging: None,
+        screen_size: (1080.0, 720.0),
+        side_bar_position: conf.side_bar_position,
         hovered_entry: None,
         show_help: false,
+        keymap: conf.keymap.clone(),
+        palette: None,
+        search: None,
+        pins: Pins::new(conf.pins_path.clone()),
+        annotations: Annotations::new(conf.annotations_path.clone()),
+        annotation_editor: None,
+        triage_labels: conf.triage_labels.clone(),
+        scopes: entries_map.iter().map(|(id, entries)| (id.to_string(), annotation::scope(entries))).collect(),
+        show_dashboard: false,
         camera_pos: (450.0, 300.0),
         camera_zoom: 1.0,
         popup: None,
         scrolling_with_arrow_keys: 0,
+        canvas_states: HashMap::new(),
         cache: RenderCache::new(),
     };
     let mut cursor_cache = HashMap::new();
+
+    for result in [state.pins.load(), state.annotations.load()] {
+        if let Err(e) = result {
+            state.show_popup(&e);
+        }
+    }
+
+    let mut navigation = Navigation::new();
     let font = load_ttf_font_from_bytes(include_bytes!("../resources/SpaceMono-Regular.ttf")).unwrap();
 
     loop {
         let (s_w, s_h) = (screen_width(), screen_height());
+        let (d_w, d_h) = layout::design_size(s_w, s_h);
+        state.screen_size = (d_w, d_h);
         let mut input = get_input();
-        fit_input_to_screen(&mut input, 1080.0, 720.0, s_w, s_h);
+        fit_input_to_screen(&mut input, d_w, d_h, s_w, s_h);
         let frame_started_at = Instant::now();
 
-        match state.frame(&entries, &input).await {
-            Action::None => {},
-            Action::Transit { id, cursor } => {
-                // `Action::Transit` can never transit to a tmp entries,
-                // so it's safe to remove all the tmp entries here.
-                for tmp_id in tmp_entries_ids.drain(..) {
-                    cursor_cache.remove(&tmp_id);
-                    entries_map.remove(&tmp_id);
-                }
-
-                cursor_cache.insert(state.curr_entries_id.to_string(), state.cursor);
-                entries = entries_map.get(&id).unwrap();
-                state.curr_entries_id = id.to_string();
-
-                if let Some(cursor) = cursor {
-                    state.cursor = cursor;
-                } else if let Some(cursor) = cursor_cache.get(&id) {
-                    state.cursor = *cursor;
-                } else {
-                    state.cursor = 0;
-                }
-            },
-            Action::TransitToTmpEntries { entries: new_entries, cursor } => {
-                cursor_cache.insert(state.curr_entries_id.to_string(), state.cursor);
-                state.curr_entries_id = new_entries.id.to_string();
-                tmp_entries_ids.push(new_entries.id.to_string());
-                entries_map.insert(new_entries.id.to_string(), new_entries);
-                entries = entries_map.get(&state.curr_entries_id).unwrap();
-
-                if let Some(cursor) = cursor {
-                    state.cursor = cursor;
-                } else {
-                    state.cursor = 0;
-                }
-            },
-            Action::Quit => {
-                break;
-            },
+        let mut action = state.frame(entries, &input).await;
+
+        // An action may lead to another one. For example, `Action::ShowHistory` creates
+        // a tmp entries and then transits to it.
+        loop {
+            action = match action {
+                Action::None => {
+                    break;
+                },
+                Action::ShowHistory { name } => match history::history_entries(&entries_map, &state.curr_entries_id, &name) {
+                    Ok((entries, cursor)) => {
+                        Action::TransitToTmpEntries { entries: Box::new(entries), cursor: Some(cursor) }
+                    },
+                    Err(e) => {
+                        state.show_popup(&e);
+                        Action::None
+                    },
+                },
+                Action::ShowFlakyEntries => match history::flaky_entries(&entries_map, &state.curr_entries_id) {
+                    Ok(entries) => {
+                        Action::TransitToTmpEntries { entries: Box::new(entries), cursor: None }
+                    },
+                    Err(e) => {
+                        state.show_popup(&e);
+                        Action::None
+                    },
+                },
+                Action::OpenPalette => {
+                    state.palette = Some(Palette::new(palette::palette_items(&entries_map, entries, &state)));
+                    Action::None
+                },
+                Action::TogglePin => {
+                    let keys = state.target_entry_keys(&entries_map, entries);
+
+                    match state.pins.toggle_all(&keys) {
+                        Ok(message) | Err(message) => { state.show_popup(&message); },
+                    }
+
+                    Action::None
+                },
+                Action::ShowPinned => match pin::pinned_entries(&entries_map, &state.pins, &state.original_entries_id) {
+                    Ok(entries) => Action::TransitToTmpEntries { entries: Box::new(entries), cursor: None },
+                    Err(e) => {
+                        state.show_popup(&e);
+                        Action::None
+                    },
+                },
+                Action::EditAnnotation => {
+                    let targets = state.annotation_targets(&entries_map, entries);
+
+                    match state.annotations.editor(targets) {
+                        Ok(editor) => { state.annotation_editor = Some(editor); },
+                        Err(e) => { state.show_popup(&e); },
+                    }
+
+                    Action::None
+                },
+                Action::ShowIssue { issue } => {
+                    let issues = match issue {
+                        Some(issue) => vec![issue],
+                        None => entries.get(state.cursor).and_then(|entry| state.annotation_of(entries, entry)).map(|a| a.issues.clone()).unwrap_or_default(),
+                    };
+
+                    if issues.is_empty() {
+                        state.show_popup("This entry has no issue!");
+                        Action::None
+                    }
+
+                    else {
+                        match annotation::issue_entries(&entries_map, &state.annotations, &issues, &state.original_entries_id) {
+                            Ok(entries) => Action::TransitToTmpEntries { entries: Box::new(entries), cursor: None },
+                            Err(e) => {
+                                state.show_popup(&e);
+                                Action::None
+                            },
+                        }
+                    }
+                },
+                Action::SetTriageLabel { label } => {
+                    let targets = state.annotation_targets(&entries_map, entries);
+                    let label = label.map(|label| state.triage_labels[label].name.to_string());
+                    state.cache.scroll_bar_label_colors.clear();
+
+                    match state.annotations.toggle_label(&targets, label) {
+                        Ok(message) | Err(message) => { state.show_popup(&message); },
+                    }
+
+                    Action::None
+                },
+                Action::ExportHtml { all } => {
+                    let (exported, name) = if all {
+                        let mut exported = entries_map.values().filter(|entries| !history::is_tmp_entries(&entries.id)).collect::<Vec<_>>();
+                        exported.sort_by_key(|entries| (entries.run.as_ref().map(|run| run.order), entries.id.to_string()));
+                        (exported, String::from("shev-report"))
+                    } else {
+                        (vec![entries], entries.title.as_ref().unwrap_or(&state.original_entries_id).to_string())
+                    };
+                    let path = export::export_path(conf.export_dir.as_deref(), &name, "html");
+
+                    match export::export_html(&exported, &path) {
+                        Ok(()) => { state.show_popup(&format!("Exported to `{path}`.")); },
+                        Err(e) => { state.show_popup(&e); },
+                    }
+
+                    Action::None
+                },
+                Action::ExportCanvasSvg => {
+                    let title = entries.title.as_ref().unwrap_or(&state.original_entries_id).to_string();
+                    let name = match entries.get(state.cursor) {
+                        _ if state.show_dashboard => format!("{title}-dashboard"),
+                        Some(entry) if entries.entry_state_count > 1 => format!("{title}-{}-state{}", entry.name, state.entry_state.0),
+                        Some(entry) => format!("{title}-{}", entry.name),
+                        None => format!("{title}-dashboard"),
+                    };
+                    let path = export::export_path(conf.export_dir.as_deref(), &name, "svg");
+
+                    match export::export_svg(&state.shown_canvas(entries), &path) {
+                        Ok(()) => { state.show_popup(&format!("Exported to `{path}`.")); },
+                        Err(e) => { state.show_popup(&e); },
+                    }
+
+                    Action::None
+                },
+                // `Graphic::Clickable` can point to any id.
+                Action::Transit { id, .. } if !entries_map.contains_key(&id) => {
+                    state.show_popup(&format!("There's no entries `{id}`!"));
+                    Action::None
+                },
+                Action::Transit { id, cursor } => {
+                    navigation.push(state.place());
+                    cursor_cache.insert(state.curr_entries_id.to_string(), state.cursor);
+                    state.reset_entries_state();
+                    state.curr_entries_id = id.to_string();
+                    remove_unreachable_tmp_entries(&mut tmp_entries_ids, &mut entries_map, &mut cursor_cache, &mut state.canvas_states, &navigation, &id);
+                    entries = entries_map.get(&id).unwrap();
+
+                    if let Some(cursor) = cursor {
+                        state.cursor = cursor;
+                    } else if let Some(cursor) = cursor_cache.get(&id) {
+                        state.cursor = *cursor;
+                    } else {
+                        state.cursor = 0;
+                    }
+
+                    state.cursor = state.cursor.min(entries.len().saturating_sub(1));
+                    Action::None
+                },
+                Action::TransitToTmpEntries { entries: new_entries, cursor } => {
+                    navigation.push(state.place());
+                    cursor_cache.insert(state.curr_entries_id.to_string(), state.cursor);
+                    state.reset_entries_state();
+                    state.curr_entries_id = new_entries.id.to_string();
+                    tmp_entries_ids.push(new_entries.id.to_string());
+                    entries_map.insert(new_entries.id.to_string(), *new_entries);
+                    remove_unreachable_tmp_entries(&mut tmp_entries_ids, &mut entries_map, &mut cursor_cache, &mut state.canvas_states, &navigation, &state.curr_entries_id);
+                    entries = entries_map.get(&state.curr_entries_id).unwrap();
+
+                    if let Some(cursor) = cursor {
+                        state.cursor = cursor;
+                    } else {
+                        state.cursor = 0;
+                    }
+
+                    state.cursor = state.cursor.min(entries.len().saturating_sub(1));
+                    Action::None
+                },
+                Action::GoBack | Action::GoForward => {
+                    let curr_place = state.place();
+                    let place = if let Action::GoBack = action {
+                        navigation.back(curr_place)
+                    } else {
+                        navigation.forward(curr_place)
+                    };
+
+                    match place {
+                        Some(place) => {
+                            cursor_cache.insert(state.curr_entries_id.to_string(), state.cursor);
+                            state.restore_place(place);
+                            remove_unreachable_tmp_entries(&mut tmp_entries_ids, &mut entries_map, &mut cursor_cache, &mut state.canvas_states, &navigation, &state.curr_entries_id);
+                            entries = entries_map.get(&state.curr_entries_id).unwrap();
+                        },
+                        None => {
+                            state.show_popup(if let Action::GoBack = action { "There's no place to go back to!" } else { "There's no place to go forward to!" });
+                        },
+                    }
+
+                    Action::None
+                },
+                Action::Quit => {
+                    return;
+                },
+            };
         }
 
+        state.original_entries_id = history::original_entries_id(&entries_map, &state.curr_entries_id);
         state.update_cache(entries, &mut texture_cache).await;
         let mut graphics = state.render(&input, entries, &conf);
-        hide_off_screen(&mut graphics, 1080.0, 720.0);
-        fit_graphics_to_screen(&mut graphics, 1080.0, 720.0, s_w, s_h);
+        hide_off_screen(&mut graphics, d_w, d_h);
+        fit_graphics_to_screen(&mut graphics, d_w, d_h, s_w, s_h);
         graphic::render(&graphics, &font, &mut texture_cache, (s_w, s_h)).await;
 
         next_frame().await;
@@ -137,3 +355,24 @@ async fn run_inner(
         }
     }
 }
+
+// Tmp entries are removed when they're neither the current entries nor in the navigation history.
+fn remove_unreachable_tmp_entries(
+    tmp_entries_ids: &mut Vec<String>,
+    entries_map: &mut HashMap<String, Entries>,
+    cursor_cache: &mut HashMap<String, usize>,
+    canvas_states: &mut HashMap<(String, usize, EntryState), CanvasState>,
+    navigation: &Navigation,
+    curr_entries_id: &str,
+) {
+    tmp_entries_ids.retain(|tmp_id| {
+        if tmp_id == curr_entries_id || navigation.contains(tmp_id) {
+            true
+        } else {
+            cursor_cache.remove(tmp_id);
+            canvas_states.retain(|(id, _, _), _| id != tmp_id);
+            entries_map.remove(tmp_id);
+            false
+        }
+    });
+}
@@ -0,0 +1,110 @@
+use crate::entry::EntryState;
+
+// The oldest places are dropped if the history gets longer than this.
+const MAX_HISTORY: usize = 256;
+
+/// Where the user was. Going back restores all of these.
+#[derive(Clone, Debug)]
+pub struct Place {
+    pub entries_id: String,
+    pub cursor: usize,
+    pub entry_state: EntryState,
+    pub camera_pos: (f32, f32),
+    pub camera_zoom: f32,
+}
+
+/// Browser-style back/forward history. The main loop pushes the current place
+/// every time it transits to another `Entries`.
+pub struct Navigation {
+    back: Vec<Place>,
+    forward: Vec<Place>,
+}
+
+impl Navigation {
+    pub fn new() -> Navigation {
+        Navigation {
+            back: vec![],
+            forward: vec![],
+        }
+    }
+
+    /// It's called when the user leaves `place`. It clears the forward history,
+    /// like a browser does.
+    pub fn push(&mut self, place: Place) {
+        self.back.push(place);
+        self.forward.clear();
+
+        if self.back.len() > MAX_HISTORY {
+            self.back.remove(0);
+        }
+    }
+
+    /// It returns the place to go back to, and remembers `curr` for `forward`.
+    pub fn back(&mut self, curr: Place) -> Option<Place> {
+        let place = self.back.pop()?;
+        self.forward.push(curr);
+        Some(place)
+    }
+
+    pub fn forward(&mut self, curr: Place) -> Option<Place> {
+        let place = self.forward.pop()?;
+        self.back.push(curr);
+        Some(place)
+    }
+
+    /// Tmp entries are kept alive while they're in the history.
+    pub fn contains(&self, entries_id: &str) -> bool {
+        self.back.iter().chain(self.forward.iter()).any(|place| place.entries_id == entries_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place(entries_id: &str, cursor: usize) -> Place {
+        Place {
+            entries_id: entries_id.to_string(),
+            cursor,
+            entry_state: EntryState(0),
+            camera_pos: (0.0, 0.0),
+            camera_zoom: 1.0,
+        }
+    }
+
+    fn id_and_cursor(place: Option<Place>) -> Option<(String, usize)> {
+        place.map(|place| (place.entries_id, place.cursor))
+    }
+
+    #[test]
+    fn back_and_forward() {
+        let mut navigation = Navigation::new();
+        assert!(navigation.back(place("a", 0)).is_none());
+
+        navigation.push(place("a", 1));
+        navigation.push(place("b", 2));
+        assert_eq!(id_and_cursor(navigation.back(place("c", 3))), Some((String::from("b"), 2)));
+        assert_eq!(id_and_cursor(navigation.back(place("b", 2))), Some((String::from("a"), 1)));
+        assert!(navigation.back(place("a", 1)).is_none());
+        assert!(navigation.contains("c"));
+
+        assert_eq!(id_and_cursor(navigation.forward(place("a", 1))), Some((String::from("b"), 2)));
+
+        // A new transition clears the forward history.
+        navigation.push(place("b", 2));
+        assert!(navigation.forward(place("d", 0)).is_none());
+        assert!(!navigation.contains("c"));
+    }
+
+    #[test]
+    fn old_places_are_dropped() {
+        let mut navigation = Navigation::new();
+
+        for i in 0..(MAX_HISTORY + 10) {
+            navigation.push(place(&i.to_string(), i));
+        }
+
+        assert!(!navigation.contains("9"));
+        assert!(navigation.contains("10"));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::entry::Entries;
+use crate::fuzzy::fuzzy_search;
+use crate::history::is_tmp_entries;
+use crate::keymap::{Command, Keymap};
+use crate::state::State;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug)]
+pub enum PaletteAction {
+    Command(Command),
+
+    /// `Entries::id`
+    GoTo(String),
+
+    /// an issue id (see `Annotation::issues`)
+    ShowIssue(String),
+
+    /// `None` is the entries without a label.
+    FilterByTriageLabel(Option<String>),
+
+    /// An index of `Entries::actions`. The palette lists all of them, not only the first 9.
+    RunEntryAction(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct PaletteItem {
+    pub label: String,
+
+    /// The key chords of the command (e.g. "Ctrl+1"). It's empty if there's none.
+    pub keys: String,
+    pub action: PaletteAction,
+}
+
+/// A fuzzy-searchable list of everything the user can do. The user opens it with
+/// `Command::OpenPalette`, types a query, and selects an item with Up/Down and Enter.
+pub struct Palette {
+    pub query: String,
+
+    /// It's an index of the matched items, not of all the items.
+    pub cursor: usize,
+    items: Vec<PaletteItem>,
+    matches: Vec<usize>,
+}
+
+impl Palette {
+    pub fn new(items: Vec<PaletteItem>) -> Palette {
+        let mut palette = Palette {
+            query: String::new(),
+            cursor: 0,
+            items,
+            matches: vec![],
+        };
+        palette.update_matches();
+        palette
+    }
+
+    pub fn push_char(&mut self, ch: char) {
+        self.query.push(ch);
+        self.update_matches();
+    }
+
+    pub fn pop_char(&mut self) {
+        self.query.pop();
+        self.update_matches();
+    }
+
+    pub fn move_cursor(&mut self, down: bool) {
+        if self.matches.is_empty() {
+            return;
+        }
+
+        self.cursor = if down {
+            (self.cursor + 1) % self.matches.len()
+        } else {
+            (self.cursor + self.matches.len() - 1) % self.matches.len()
+        };
+    }
+
+    pub fn selected(&self) -> Option<&PaletteItem> {
+        self.matches.get(self.cursor).map(|i| &self.items[*i])
+    }
+
+    /// The items that match the query, the best match first.
+    pub fn matches(&self) -> impl Iterator<Item = &PaletteItem> {
+        self.matches.iter().map(|i| &self.items[*i])
+    }
+
+    pub fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn update_matches(&mut self) {
+        self.matches = fuzzy_search(&self.query, self.items.iter().map(|item| item.label.as_str()));
+        self.cursor = 0;
+    }
+}
+
+/// The transitions of the current entry, the filters, the actions, the built-in commands, every
+/// (non-tmp) `Entries` in the map and the issues, in this order.
+pub fn palette_items(
+    entries_map: &HashMap<String, Entries>,
+    entries: &Entries,
+    state: &State,
+) -> Vec<PaletteItem> {
+    let mut items = vec![];
+    let entry = entries.get(state.cursor);
+    let keymap = &state.keymap;
+
+    for (command, transition) in [
+        (Command::Transit, entries.transition.as_ref()),
+        (Command::Transit1, entry.and_then(|entry| entry.transition1.as_ref())),
+        (Command::Transit2, entry.and_then(|entry| entry.transition2.as_ref())),
+    ] {
+        if let Some(transition) = transition {
+            items.push(command_item(
+                format!("Transit: {}", transition.description.as_ref().unwrap_or(&transition.id)),
+                command,
+                keymap,
+            ));
+        }
+    }
+
+    for (i, filter) in entries.filters.iter().enumerate().take(9) {
+        items.push(command_item(format!("Filter: {}", filter.name), Command::Filter(i + 1), keymap));
+    }
+
+    for (i, action) in entries.actions.iter().enumerate() {
+        items.push(PaletteItem {
+            label: format!("Action: {}", action.name),
+            keys: action.describe_keys(i, keymap),
+            action: PaletteAction::RunEntryAction(i),
+        });
+    }
+
+    for label in state.triage_labels.iter().map(|label| Some(label.name.to_string())).chain([None]) {
+        items.push(PaletteItem {
+            label: format!("Filter: {}", label.as_deref().unwrap_or("untriaged")),
+            keys: String::new(),
+            action: PaletteAction::FilterByTriageLabel(label),
+        });
+    }
+
+    for command in Command::all() {
+        match command {
+            // They're listed above, with better descriptions.
+            Command::Transit | Command::Transit1 | Command::Transit2 | Command::Filter(_) | Command::RunEntryAction(_) => {},
+            Command::OpenPalette => {},
+            Command::SetTriageLabel(n) => {
+                if let Some(label) = state.triage_labels.get(n - 1) {
+                    items.push(command_item(format!("Set triage label: {}", label.name), command, keymap));
+                }
+            },
+
+            // It doesn't make sense to move the camera for a single frame.
+            _ if command.is_continuous() => {},
+            _ => {
+                items.push(command_item(command.description(), command, keymap));
+            },
+        }
+    }
+
+    let mut ids = entries_map.keys().filter(|id| !is_tmp_entries(id)).collect::<Vec<_>>();
+    ids.sort();
+
+    for id in ids {
+        let label = match &entries_map[id].title {
+            Some(title) => format!("Go to: {title} ({id})"),
+            None => format!("Go to: {id}"),
+        };
+
+        items.push(PaletteItem {
+            label,
+            keys: String::new(),
+            action: PaletteAction::GoTo(id.to_string()),
+        });
+    }
+
+    for (issue, links) in state.annotations.issues() {
+        items.push(PaletteItem {
+            label: format!("Issue: {issue} ({} entr{})", links.len(), if links.len() == 1 { "y" } else { "ies" }),
+            keys: String::new(),
+            action: PaletteAction::ShowIssue(issue),
+        });
+    }
+
+    items
+}
+
+fn command_item(label: String, command: Command, keymap: &Keymap) -> PaletteItem {
+    PaletteItem {
+        label,
+        keys: keymap.describe(&[command]),
+        action: PaletteAction::Command(command),
+    }
+}
@@ -0,0 +1,309 @@
+use crate::entry::{Entries, Entry, EntryState, Transition};
+use crate::graphic::{ClickAction, GRAY, Graphic, WHITE, push_text};
+use macroquad::color::Color;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+pub const PIN_COLOR: Color = Color { r: 0.95, g: 0.8, b: 0.4, a: 1.0 };
+
+// The entries of a linked entries (e.g. the "Pinned" entries) stand for entries of other `Entries`.
+const LINKED_ENTRIES_PREFIX: &str = "@@tmp-linked-";
+
+/// The entries that the user pinned, in the order they were pinned. A pin is
+/// (`Entries::id`, `Entry::name`), so it's still valid after the engine restarts.
+///
+/// If `path` is set, the pins are saved to the file (json) every time they change.
+pub struct Pins {
+    path: Option<String>,
+    pins: Vec<(String, String)>,
+}
+
+impl Pins {
+    pub fn new(path: Option<String>) -> Pins {
+        Pins { path, pins: vec![] }
+    }
+
+    /// It's fine if the file doesn't exist. If the file is broken, it returns an error
+    /// and stops saving the pins, so that the file is not overwritten.
+    pub fn load(&mut self) -> Result<(), String> {
+        let Some(path) = &self.path else { return Ok(()); };
+
+        let Ok(s) = std::fs::read_to_string(path) else { return Ok(()); };
+
+        match parse_pins(&s) {
+            Some(pins) => {
+                self.pins = pins;
+                Ok(())
+            },
+            None => {
+                let e = format!("Failed to load pins from `{path}`. Pins won't be saved.");
+                self.path = None;
+                Err(e)
+            },
+        }
+    }
+
+    pub fn contains(&self, entries_id: &str, name: &str) -> bool {
+        self.pins.iter().any(|(id, n)| id == entries_id && n == name)
+    }
+
+    /// It returns whether the entry is pinned after the toggle.
+    pub fn toggle(&mut self, entries_id: &str, name: &str) -> Result<bool, String> {
+        let pinned = if self.contains(entries_id, name) {
+            self.pins.retain(|(id, n)| !(id == entries_id && n == name));
+            false
+        } else {
+            self.pins.push((entries_id.to_string(), name.to_string()));
+            true
+        };
+
+        self.save()?;
+        Ok(pinned)
+    }
+
+    /// If some of `keys` (`Entries::id`, `Entry::name`) are not pinned, it pins them.
+    /// Otherwise, it unpins all of them. It returns the message for the popup.
+    ///
+    /// A pin must point to an entry of a non-tmp entries, because tmp entries don't survive.
+    /// The caller has to filter them out (see `State::target_entry_keys`).
+    pub fn toggle_all(&mut self, keys: &[(String, String)]) -> Result<String, String> {
+        if keys.is_empty() {
+            return Err(String::from("This entry can't be pinned."));
+        }
+
+        let pin = keys.iter().any(|(entries_id, name)| !self.contains(entries_id, name));
+
+        for (entries_id, name) in keys.iter() {
+            if self.contains(entries_id, name) != pin {
+                self.toggle(entries_id, name)?;
+            }
+        }
+
+        Ok(match keys {
+            [(_, name)] => format!("{} `{name}`.", if pin { "Pinned" } else { "Unpinned" }),
+            _ => format!("{} {} entries.", if pin { "Pinned" } else { "Unpinned" }, keys.len()),
+        })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else { return Ok(()); };
+        let pins = self.pins.iter().map(
+            |(entries_id, name)| json!({ "entries": entries_id, "name": name })
+        ).collect::<Vec<_>>();
+
+        std::fs::write(path, serde_json::to_string_pretty(&pins).unwrap()).map_err(|e| format!("Failed to save pins to `{path}`: {e:?}"))
+    }
+}
+
+fn parse_pins(s: &str) -> Option<Vec<(String, String)>> {
+    let pins: Value = serde_json::from_str(s).ok()?;
+
+    pins.as_array()?.iter().map(
+        |pin| Some((pin["entries"].as_str()?.to_string(), pin["name"].as_str()?.to_string()))
+    ).collect()
+}
+
+pub fn is_linked_entries(id: &str) -> bool {
+    id.starts_with(LINKED_ENTRIES_PREFIX)
+}
+
+/// It returns the (entries id, entry name) that `entry` stands for. `original_entries_id` is
+/// the id of the entries that the current (maybe tmp) entries came from (see
+/// `history::original_entries_id`). An entry in a linked entries (e.g. the "Pinned" entries)
+/// stands for the entry that it's linked to.
+pub fn entry_key<'a>(original_entries_id: &'a str, entries: &Entries, entry: &'a Entry) -> (&'a str, &'a str) {
+    match &entry.transition1 {
+        Some(transition) if is_linked_entries(&entries.id) => (&transition.id, &entry.name),
+        _ => (original_entries_id, &entry.name),
+    }
+}
+
+/// The cursor of the entry that `entry` stands for, in its `Entries`. It's `None` if
+/// `entries` is not a linked entries.
+pub fn linked_cursor(entries: &Entries, entry: &Entry) -> Option<usize> {
+    if !is_linked_entries(&entries.id) {
+        return None;
+    }
+
+    let content: Value = serde_json::from_str(entry.content.as_ref()?).ok()?;
+    content["cursor"].as_u64().map(|cursor| cursor as usize)
+}
+
+/// It creates a tmp `Entries` of the pinned entries. The pins whose entries are gone
+/// are skipped (but not removed).
+pub fn pinned_entries(
+    entries_map: &HashMap<String, Entries>,
+    pins: &Pins,
+    original_entries_id: &str,
+) -> Result<Entries, String> {
+    linked_entries(entries_map, &pins.pins, "Pinned", original_entries_id).ok_or_else(|| String::from("There's no pinned entry!"))
+}
+
+/// It creates a tmp `Entries` whose entries stand for `links` (`Entries::id`, `Entry::name`).
+/// The links to missing entries are skipped. It returns `None` if nothing's left.
+pub fn linked_entries(
+    entries_map: &HashMap<String, Entries>,
+    links: &[(String, String)],
+    title: &str,
+    original_entries_id: &str,
+) -> Option<Entries> {
+    let mut entries = vec![];
+
+    for (entries_id, name) in links.iter() {
+        let Some(linked) = entries_map.get(entries_id) else { continue; };
+        let Some((cursor, entry)) = linked.iter().enumerate().find(|(_, e)| &e.name == name) else { continue; };
+        let linked_title = linked.title.as_ref().unwrap_or(&linked.id);
+
+        entries.push(Entry {
+            name: name.to_string(),
+            content: Some(json!({
+                "entries": entries_id,
+                "title": linked_title,
+                "cursor": cursor,
+                "categories": entry.categories,
+            }).to_string()),
+            categories: vec![linked_title.to_string()],
+            transition1: Some(Transition {
+                id: entries_id.to_string(),
+                description: Some(format!("go to {linked_title}")),
+            }),
+            flag: entry.flag,
+            ..Entry::default()
+        });
+    }
+
+    if entries.is_empty() {
+        return None;
+    }
+
+    Some(Entries {
+        id: format!("{LINKED_ENTRIES_PREFIX}{:x}", rand::random::<u64>()),
+        title: Some(format!("{title} ({})", entries.len())),
+        entries,
+        transition: Some(Transition {
+            id: original_entries_id.to_string(),
+            description: Some(format!("exit {} view", title.to_lowercase())),
+        }),
+        render_canvas: render_linked,
+        ..Entries::default()
+    })
+}
+
+// The canvas of the linked entry is rendered by its `Entries`, so
+// this only shows where the entry is from.
+fn render_linked(entry: &Entry, _: EntryState) -> Result<Vec<Graphic>, String> {
+    let content: Value = serde_json::from_str(entry.content.as_ref().ok_or_else(|| String::from("no content"))?).map_err(|e| format!("{e:?}"))?;
+    let entries_id = content["entries"].as_str().unwrap_or("");
+    let title = content["title"].as_str().unwrap_or(entries_id);
+    let cursor = content["cursor"].as_u64().unwrap_or(0) as usize;
+    let categories = content["categories"].as_array().map(
+        |categories| categories.iter().filter_map(|c| c.as_str()).collect::<Vec<_>>().join(", ")
+    ).unwrap_or_default();
+    let mut graphics = vec![];
+
+    push_text(&mut graphics, &entry.name, 30.0, 50.0, 21.0, PIN_COLOR);
+    push_text(&mut graphics, &format!("from {title}"), 30.0, 85.0, 16.0, WHITE);
+
+    if !categories.is_empty() {
+        push_text(&mut graphics, &format!("categories: {categories}"), 30.0, 115.0, 16.0, GRAY);
+    }
+
+    graphics.push(Graphic::Rect {
+        x: 30.0,
+        y: 150.0,
+        w: 240.0,
+        h: 48.0,
+        radius: Some(8.0),
+        thickness: None,
+        color: Color { r: 0.3, g: 0.3, b: 0.45, a: 1.0 },
+    });
+    push_text(&mut graphics, "Open this entry", 58.0, 180.0, 18.0, WHITE);
+    graphics.push(Graphic::Clickable {
+        x: 30.0,
+        y: 150.0,
+        w: 240.0,
+        h: 48.0,
+        action: ClickAction::Transit {
+            id: entries_id.to_string(),
+            cursor: Some(cursor),
+        },
+    });
+
+    Ok(graphics)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(id: &str, names: &[&str]) -> Entries {
+        Entries {
+            id: id.to_string(),
+            entries: names.iter().map(|name| Entry { name: name.to_string(), ..Entry::default() }).collect(),
+            ..Entries::default()
+        }
+    }
+
+    #[test]
+    fn linked_entries_go_to_the_linked_entry() {
+        let entries_map = HashMap::from([
+            (String::from("a"), entries("a", &["a1", "a2", "a3"])),
+            (String::from("b"), entries("b", &["b1"])),
+        ]);
+        let links = [
+            (String::from("a"), String::from("a3")),
+            (String::from("c"), String::from("c1")),
+            (String::from("a"), String::from("a4")),
+            (String::from("b"), String::from("b1")),
+        ];
+        let linked = linked_entries(&entries_map, &links, "Pinned", "a").unwrap();
+
+        assert!(is_linked_entries(&linked.id));
+        assert_eq!(linked.entries.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(), vec!["a3", "b1"]);
+        assert_eq!(linked.entries[0].transition1.as_ref().unwrap().id, "a");
+        assert_eq!(linked_cursor(&linked, &linked.entries[0]), Some(2));
+        assert_eq!(linked_cursor(&linked, &linked.entries[1]), Some(0));
+        assert_eq!(entry_key("a", &linked, &linked.entries[1]), ("b", "b1"));
+
+        let a = &entries_map["a"];
+        assert_eq!(linked_cursor(a, &a.entries[0]), None);
+        assert_eq!(entry_key("a", a, &a.entries[0]), ("a", "a1"));
+        assert!(linked_entries(&entries_map, &links[1..3], "Pinned", "a").is_none());
+    }
+
+    #[test]
+    fn toggle_pins() {
+        let mut pins = Pins::new(None);
+        assert_eq!(pins.toggle("a", "a1"), Ok(true));
+        assert_eq!(pins.toggle("b", "a1"), Ok(true));
+        assert!(pins.contains("a", "a1"));
+        assert_eq!(pins.toggle("a", "a1"), Ok(false));
+        assert!(!pins.contains("a", "a1"));
+        assert!(pins.contains("b", "a1"));
+    }
+
+    #[test]
+    fn toggle_all_pins() {
+        let mut pins = Pins::new(None);
+        let keys = [(String::from("a"), String::from("a1")), (String::from("a"), String::from("a2"))];
+
+        assert_eq!(pins.toggle_all(&keys[..1]), Ok(String::from("Pinned `a1`.")));
+        assert_eq!(pins.toggle_all(&keys), Ok(String::from("Pinned 2 entries.")));
+        assert!(pins.contains("a", "a1") && pins.contains("a", "a2"));
+        assert_eq!(pins.toggle_all(&keys), Ok(String::from("Unpinned 2 entries.")));
+        assert!(!pins.contains("a", "a1") && !pins.contains("a", "a2"));
+        assert!(pins.toggle_all(&[]).is_err());
+    }
+
+    #[test]
+    fn parse_pin_file() {
+        assert_eq!(
+            parse_pins(r#"[{"entries": "a", "name": "a1"}, {"entries": "b", "name": "b1"}]"#),
+            Some(vec![(String::from("a"), String::from("a1")), (String::from("b"), String::from("b1"))]),
+        );
+        assert_eq!(parse_pins("[]"), Some(vec![]));
+        assert_eq!(parse_pins(r#"[{"entries": "a"}]"#), None);
+        assert_eq!(parse_pins("{"), None);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::entry::Entries;
+use crate::fuzzy::fuzzy_search;
+
+/// A type-to-jump prompt. While it's open, the side bar shows the entries whose
+/// names fuzzy-match the query, the best match first.
+pub struct EntrySearch {
+    pub query: String,
+
+    /// It's an index of `matches`, not of the entries.
+    pub cursor: usize,
+
+    /// Indexes of the matched entries.
+    pub matches: Vec<usize>,
+}
+
+impl EntrySearch {
+    pub fn new(entries: &Entries) -> EntrySearch {
+        let mut search = EntrySearch {
+            query: String::new(),
+            cursor: 0,
+            matches: vec![],
+        };
+        search.update_matches(entries);
+        search
+    }
+
+    pub fn push_char(&mut self, ch: char, entries: &Entries) {
+        self.query.push(ch);
+        self.update_matches(entries);
+    }
+
+    pub fn pop_char(&mut self, entries: &Entries) {
+        self.query.pop();
+        self.update_matches(entries);
+    }
+
+    pub fn move_cursor(&mut self, down: bool) {
+        if self.matches.is_empty() {
+            return;
+        }
+
+        self.cursor = if down {
+            (self.cursor + 1) % self.matches.len()
+        } else {
+            (self.cursor + self.matches.len() - 1) % self.matches.len()
+        };
+    }
+
+    /// The index of the selected entry.
+    pub fn selected(&self) -> Option<usize> {
+        self.matches.get(self.cursor).copied()
+    }
+
+    fn update_matches(&mut self, entries: &Entries) {
+        self.matches = fuzzy_search(&self.query, entries.iter().map(|entry| entry.name.as_str()));
+        self.cursor = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::Entry;
+
+    fn entries(names: &[&str]) -> Entries {
+        Entries {
+            entries: names.iter().map(|name| Entry { name: name.to_string(), ..Entry::default() }).collect(),
+            ..Entries::default()
+        }
+    }
+
+    #[test]
+    fn type_to_jump() {
+        let entries = entries(&["tangent", "nested_generic", "foo"]);
+        let mut search = EntrySearch::new(&entries);
+        assert_eq!(search.matches, vec![0, 1, 2]);
+
+        search.push_char('n', &entries);
+        search.push_char('g', &entries);
+        assert_eq!(search.matches, vec![1, 0]);
+        assert_eq!(search.selected(), Some(1));
+
+        search.move_cursor(true);
+        assert_eq!(search.selected(), Some(0));
+        search.move_cursor(true);
+        assert_eq!(search.selected(), Some(1));
+        search.move_cursor(false);
+        assert_eq!(search.selected(), Some(0));
+
+        // Editing the query resets the cursor.
+        search.pop_char(&entries);
+        assert_eq!(search.cursor, 0);
+        assert_eq!(search.query, "n");
+    }
+
+    #[test]
+    fn no_matches() {
+        let entries = entries(&["foo"]);
+        let mut search = EntrySearch::new(&entries);
+        search.push_char('x', &entries);
+        search.move_cursor(true);
+
+        assert_eq!(search.selected(), None);
+    }
+}
@@ -1,39 +1,218 @@
+use crate::annotation::{Annotation, AnnotationEditor, Annotations};
 use crate::cache::RenderCache;
-use crate::entry::EntryState;
+use crate::entry::{CanvasState, Entries, Entry, EntryState};
+use crate::entry_action::RunningAction;
 use crate::graphic::Graphic;
+use crate::history::is_tmp_entries;
+use crate::keymap::Keymap;
+use crate::layout::{Layout, SideBarPosition, Splitter};
+use crate::navigation::Place;
+use crate::palette::Palette;
+use crate::pin::{Pins, entry_key};
+use crate::search::EntrySearch;
+use crate::triage::TriageLabel;
 use macroquad::color::Color;
+use std::collections::HashMap;
 
 mod frame;
 mod render;
 
 pub struct State {
     pub curr_entries_id: String,
+
+    /// If the current entries is a tmp one, it's the entries that it came from
+    /// (see `history::original_entries_id`). It's updated every frame.
+    pub original_entries_id: String,
     pub cursor: usize,
+
+    /// Indexes of the selected entries, sorted. The bulk commands (pin, annotate, ...)
+    /// work on the selected entries, or on the current entry if nothing's selected.
+    /// It's cleared when the current entries changes.
+    pub selection: Vec<usize>,
+
+    /// Where the range selection (Shift+click, Shift+arrows) starts.
+    pub selection_anchor: Option<usize>,
+
+    /// (start, end) of the area that the user is dragging on the canvas, in canvas
+    /// coordinates. The text in the area is copied when the mouse is released.
+    pub text_selection: Option<((f32, f32), (f32, f32))>,
+
+    /// `EntryAction`s that are still running. They're checked every frame.
+    pub running_actions: Vec<RunningAction>,
     pub entry_state: EntryState,
     pub wide_side_bar: bool,
+
+    /// If the user has dragged the splitter, it's used instead of `wide_side_bar`.
+    /// See `Layout::new`.
+    pub side_bar_size: Option<f32>,
+    pub top_bar_height: f32,
+
+    /// The splitter that the user is dragging.
+    pub dragging: Option<Splitter>,
+
+    /// The size of the design space (see `layout::design_size`). It's updated every frame.
+    pub screen_size: (f32, f32),
+    pub side_bar_position: SideBarPosition,
     pub hovered_entry: Option<usize>,
     pub show_help: bool,
+    pub keymap: Keymap,
+
+    /// If it's set, the command palette is open and it takes all the keyboard inputs.
+    pub palette: Option<Palette>,
+
+    /// If it's set, the side bar shows the search results, and the prompt takes all
+    /// the keyboard inputs.
+    pub search: Option<EntrySearch>,
+    pub pins: Pins,
+    pub annotations: Annotations,
+    pub annotation_editor: Option<AnnotationEditor>,
+
+    /// entries id -> scope of annotations (see `annotation::scope`). It doesn't have tmp entries.
+    pub scopes: HashMap<String, String>,
+    pub triage_labels: Vec<TriageLabel>,
+
+    /// If it's set, the canvas shows the summary of the current `Entries`
+    /// (see `render_dashboard`) instead of the current entry.
+    pub show_dashboard: bool,
     pub camera_pos: (f32, f32),
     pub camera_zoom: f32,
     pub popup: Option<(u32, String)>,
 
-    // If you hold Up or Down key for a long time,
+    // If you hold the key of `Command::NextEntry` or `Command::PrevEntry` for a long time,
     // that's the same as pressing the key every frame.
     pub scrolling_with_arrow_keys: i32,
 
+    /// (entries_id, cursor, entry_state) -> CanvasState
+    pub canvas_states: HashMap<(String, usize, EntryState), CanvasState>,
+
     /// I don't want to call `entry_top_bar_message()` and `entry_canvas()` every frame,
     /// so they're cached. They are called only if `Entry` or `EntryState` changes.
     pub cache: RenderCache,
 }
 
 impl State {
+    pub fn place(&self) -> Place {
+        Place {
+            entries_id: self.curr_entries_id.to_string(),
+            cursor: self.cursor,
+            entry_state: self.entry_state,
+            camera_pos: self.camera_pos,
+            camera_zoom: self.camera_zoom,
+        }
+    }
+
+    pub fn restore_place(&mut self, place: Place) {
+        self.curr_entries_id = place.entries_id;
+        self.cursor = place.cursor;
+        self.entry_state = place.entry_state;
+        self.camera_pos = place.camera_pos;
+        self.camera_zoom = place.camera_zoom;
+        self.hovered_entry = None;
+        self.clear_selection();
+    }
+
+    /// The indexes of the entries that the bulk commands work on.
+    pub fn targets(&self) -> Vec<usize> {
+        if self.selection.is_empty() {
+            vec![self.cursor]
+        } else {
+            self.selection.clone()
+        }
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection.clear();
+        self.selection_anchor = None;
+    }
+
+    /// (entries id, entry name) of `targets()` (see `pin::entry_key`). The entries that
+    /// don't exist in a non-tmp entries are skipped, because pins and annotations don't
+    /// survive with tmp entries.
+    pub fn target_entry_keys(&self, entries_map: &HashMap<String, Entries>, entries: &Entries) -> Vec<(String, String)> {
+        self.targets().into_iter().filter_map(|i| {
+            let (entries_id, name) = entry_key(&self.original_entries_id, entries, entries.get(i)?);
+
+            if is_tmp_entries(entries_id) || !entries_map.get(entries_id)?.iter().any(|e| e.name == name) {
+                return None;
+            }
+
+            Some((entries_id.to_string(), name.to_string()))
+        }).collect()
+    }
+
+    /// (scope, entry name) of `target_entry_keys()`, which annotations and triage labels are attached to.
+    pub fn annotation_targets(&self, entries_map: &HashMap<String, Entries>, entries: &Entries) -> Vec<(String, String)> {
+        self.target_entry_keys(entries_map, entries).into_iter().map(
+            |(entries_id, name)| (self.scope_of(&entries_id).to_string(), name)
+        ).collect()
+    }
+
+    pub fn scope_of<'a>(&'a self, entries_id: &'a str) -> &'a str {
+        self.scopes.get(entries_id).map(|scope| scope.as_str()).unwrap_or(entries_id)
+    }
+
+    pub fn annotation_of(&self, entries: &Entries, entry: &Entry) -> Option<&Annotation> {
+        let (entries_id, name) = entry_key(&self.original_entries_id, entries, entry);
+        self.annotations.get(self.scope_of(entries_id), name)
+    }
+
+    pub fn triage_label_of(&self, entries: &Entries, entry: &Entry) -> Option<String> {
+        self.annotation_of(entries, entry)?.label.clone()
+    }
+
     pub fn curr_scroll_bar_colors(&mut self) -> &Vec<Color> {
         // It must be here because `self.update_cache()` is called every frame.
         self.cache.scroll_bar_colors.get(&self.curr_entries_id).unwrap()
     }
 
+    pub fn curr_scroll_bar_label_colors(&mut self) -> &Vec<Option<Color>> {
+        self.cache.scroll_bar_label_colors.get(&self.curr_entries_id).unwrap()
+    }
+
+    /// The dashboard or the canvas of the current entry, whichever is shown.
+    pub fn shown_canvas(&mut self, entries: &Entries) -> Vec<Graphic> {
+        if self.show_dashboard || entries.is_empty() {
+            self.curr_dashboard().unwrap_or(&vec![]).clone()
+        } else {
+            self.curr_canvas().unwrap_or(&vec![]).clone()
+        }
+    }
+
     pub fn curr_canvas(&mut self) -> Option<&Vec<Graphic>> {
         // It must be here because `self.update_cache()` is called every frame.
         self.cache.canvas.get(&(self.curr_entries_id.to_string(), self.cursor, self.entry_state))
     }
+
+    pub fn curr_dashboard(&mut self) -> Option<&Vec<Graphic>> {
+        // It must be here because `self.update_cache()` is called every frame.
+        self.cache.dashboard.get(&self.curr_entries_id)
+    }
+
+    pub fn curr_top_bar_extra_message(&mut self) -> Option<String> {
+        // It must be here because `self.update_cache()` is called every frame.
+        self.cache.top_bar_extra_message.get(&(self.curr_entries_id.to_string(), self.cursor, self.entry_state)).cloned().flatten()
+    }
+
+    pub fn layout(&self) -> Layout {
+        Layout::new(
+            self.screen_size.0,
+            self.screen_size.1,
+            self.side_bar_position,
+            self.wide_side_bar,
+            self.side_bar_size,
+            self.top_bar_height,
+        )
+    }
+
+    /// It's the inverse of what `render_canvas` does to the canvas.
+    pub fn screen_to_canvas(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let layout = self.layout();
+        let (center_x, center_y) = layout.canvas_center();
+        let zoom = self.camera_zoom * layout.canvas_scale();
+
+        (
+            (x - center_x) / zoom + self.camera_pos.0,
+            (y - center_y) / zoom + self.camera_pos.1,
+        )
+    }
 }
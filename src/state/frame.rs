@@ -1,19 +1,25 @@
 use super::State;
 use crate::action::Action;
 use crate::entry::{Entries, Entry, EntryState, Transition};
+use crate::entry_action::{ActionResult, RunningAction};
+use crate::graphic::{ClickAction, Graphic, graphics_to_text};
 use crate::input::Input;
+use crate::keymap::{Command, modifiers};
+use crate::layout::{SideBarPosition, Splitter};
+use crate::palette::PaletteAction;
+use crate::pin::linked_cursor;
+use crate::search::EntrySearch;
 use crate::transform::check_contain;
 use macroquad::input::KeyCode;
+use macroquad::miniquad::window::clipboard_set;
 
 impl State {
     pub async fn frame(&mut self, entries: &Entries, input: &Input) -> Action {
         let original_cursor = self.cursor;
         let mut scroll_up = false;
         let mut scroll_down = false;
-        let num_keys = [KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9];
-        let is_ctrl_down = input.down_keys.contains(&KeyCode::LeftControl) || input.down_keys.contains(&KeyCode::RightControl);
-        let is_shift_down = input.down_keys.contains(&KeyCode::LeftShift) || input.down_keys.contains(&KeyCode::RightShift);
-        let is_alt_down = input.down_keys.contains(&KeyCode::LeftAlt) || input.down_keys.contains(&KeyCode::RightAlt);
+        let (is_ctrl_down, is_shift_down, is_alt_down) = modifiers(input);
+        let commands = self.keymap.triggered(input);
 
         if let Some((life, _)) = &mut self.popup {
             *life -= 1;
@@ -23,61 +29,60 @@ impl State {
             }
         }
 
-        if input.released_keys.contains(&KeyCode::Escape) {
+        if self.palette.is_some() {
+            let action = self.palette_frame(entries, input);
+
+            if self.cursor != original_cursor {
+                self.reset_entry_state();
+            }
+
+            return action;
+        }
+
+        if self.annotation_editor.is_some() {
+            self.annotation_frame(input);
+            return Action::None;
+        }
+
+        if self.search.is_some() {
+            self.search_frame(entries, input);
+
+            if self.cursor != original_cursor {
+                self.reset_entry_state();
+            }
+
+            return Action::None;
+        }
+
+        if let Some(action) = self.poll_entry_actions() {
+            return action;
+        }
+
+        if commands.contains(&Command::Quit) {
             if self.show_help {
                 self.show_help = false;
                 return Action::None;
             }
 
+            if !self.selection.is_empty() {
+                self.clear_selection();
+                return Action::None;
+            }
+
             return Action::Quit;
         }
 
-        if is_ctrl_down && !is_shift_down && !is_alt_down {
-            for (i, num_key) in num_keys[1..].iter().enumerate() {
-                if input.pressed_keys.contains(num_key) && let Some(filter) = entries.filters.get(i) {
-                    let mut new_cursor = None;
-                    let filtered_entries = entries.iter().map(|e| (e, (filter.cond)(e)));
-                    let new_entries: Vec<Entry> = filtered_entries.into_iter().enumerate().map(
-                        |(j, (e, cond))| (e.clone(), cond, j == self.cursor)
-                    ).filter(
-                        |(_, cond, _)| *cond
-                    ).enumerate().map(
-                        |(j, (e, _, selected))| {
-                            // There's no unique identifier for `Entry`, so we have to do this to
-                            // calculate `new_cursor`.
-                            if selected {
-                                new_cursor = Some(j);
-                            }
-
-                            e
-                        }
-                    ).collect();
-
-                    return Action::TransitToTmpEntries {
-                        entries: Entries {
-                            id: format!("@@tmp-{:x}", rand::random::<u64>()),
-                            title: entries.title.as_ref().map(|t| format!("{t} ({})", filter.name)),
-                            entries: new_entries,
-                            entry_state_count: entries.entry_state_count,
-                            transition: Some(Transition {
-                                id: entries.id.clone(),
-                                description: Some(String::from("exit filter view")),
-                            }),
-                            filters: vec![],
-                            render_canvas: entries.render_canvas,
-                            render_top_bar_extra_message: entries.render_top_bar_extra_message,
-                        },
-                        cursor: new_cursor,
-                    };
-                }
-            }
-        }
+        // `is_held` ignores Shift, so Shift tells extending the selection from moving the cursor.
+        let is_extending_selection = is_shift_down && (
+            self.keymap.is_held(Command::ExtendSelectionDown, input)
+            || self.keymap.is_held(Command::ExtendSelectionUp, input)
+        );
 
-        if input.down_keys.contains(&KeyCode::Down) {
+        if self.keymap.is_held(Command::NextEntry, input) || is_extending_selection && self.keymap.is_held(Command::ExtendSelectionDown, input) {
             self.scrolling_with_arrow_keys = (self.scrolling_with_arrow_keys - 1).min(-1);
         }
 
-        else if input.down_keys.contains(&KeyCode::Up) {
+        else if self.keymap.is_held(Command::PrevEntry, input) || is_extending_selection && self.keymap.is_held(Command::ExtendSelectionUp, input) {
             self.scrolling_with_arrow_keys = (self.scrolling_with_arrow_keys + 1).max(1);
         }
 
@@ -93,103 +98,51 @@ impl State {
             scroll_up = true;
         }
 
-        let side_bar_start = if self.wide_side_bar { 600.0 } else { 900.0 };
-
-        if !is_shift_down && !is_ctrl_down && !is_alt_down {
-            if !entries.is_empty() {
-                let mut scroll_speed = 1;
-
-                if input.mouse_wheel.1 < 0.0 && input.mouse_pos.0 >= side_bar_start {
-                    scroll_down = true;
-                    scroll_speed = (entries.len() / 32).max(1);
-                }
-
-                else if input.mouse_wheel.1 > 0.0 && input.mouse_pos.0 >= side_bar_start {
-                    scroll_up = true;
-                    scroll_speed = (entries.len() / 32).max(1);
-                }
-
-                if input.pressed_keys.contains(&KeyCode::Down) || scroll_down {
-                    self.cursor = (self.cursor + scroll_speed) % entries.len();
-                }
-
-                else if input.pressed_keys.contains(&KeyCode::Up) || scroll_up {
-                    self.cursor = (self.cursor + entries.len() - scroll_speed) % entries.len();
-                }
+        let is_mouse_on_side_bar = check_contain(self.layout().side_bar, input.mouse_pos);
+        let mut scroll_speed = 1;
 
-                let mut n = None;
-
-                for (i, num_key) in num_keys[1..].iter().enumerate() {
-                    if input.pressed_keys.contains(num_key) {
-                        n = Some(i);
-                        break;
-                    }
-                }
-
-                if let Some(n) = n {
-                    self.cursor = n * (entries.len() - 1) / 8;
-                }
+        if !is_shift_down && !is_ctrl_down && !is_alt_down && is_mouse_on_side_bar && !entries.is_empty() {
+            if input.mouse_wheel.1 < 0.0 {
+                scroll_down = true;
+                scroll_speed = (entries.len() / 32).max(1);
             }
 
-            if input.pressed_keys.contains(&KeyCode::Left) {
-                self.wide_side_bar = true;
+            else if input.mouse_wheel.1 > 0.0 {
+                scroll_up = true;
+                scroll_speed = (entries.len() / 32).max(1);
             }
+        }
 
-            else if input.pressed_keys.contains(&KeyCode::Right) {
-                self.wide_side_bar = false;
-            }
+        if !entries.is_empty() {
+            let cursor = self.cursor;
 
-            if input.pressed_keys.contains(&KeyCode::H) {
-                self.show_help = !self.show_help;
+            if commands.contains(&Command::NextEntry) || scroll_down {
+                self.cursor = (self.cursor + scroll_speed) % entries.len();
             }
 
-            if input.pressed_keys.contains(&KeyCode::M) {
-                if entries.entry_state_count < 2 {
-                    self.show_popup("There's no state to change!");
-                }
-
-                else {
-                    self.entry_state.0 = (self.entry_state.0 + 1) % entries.entry_state_count;
-                }
+            else if commands.contains(&Command::PrevEntry) || scroll_up {
+                self.cursor = (self.cursor + entries.len() - scroll_speed) % entries.len();
             }
 
-            if input.pressed_keys.contains(&KeyCode::N) {
-                if entries.entry_state_count < 2 {
-                    self.show_popup("There's no state to change!");
-                }
+            if is_extending_selection && self.cursor != cursor {
+                self.select_range(cursor);
+            }
+        }
 
-                else {
-                    self.entry_state.0 = (self.entry_state.0 + entries.entry_state_count - 1) % entries.entry_state_count;
-                }
+        for command in commands.into_iter() {
+            // They're handled above or below.
+            if command == Command::Quit || command == Command::NextEntry || command == Command::PrevEntry {
+                continue;
             }
 
-            if input.pressed_keys.contains(&KeyCode::Space) {
-                self.camera_zoom = 1.0;
-                self.camera_pos = (450.0, 300.0);
+            if let Some(action) = self.run_command(entries, command) {
+                return action;
             }
         }
 
-        if is_ctrl_down {
-            for (key, key_code, transition) in [
-                ("Up", KeyCode::Up, &entries.transition),
-                ("Left", KeyCode::Left, &entries.get(self.cursor).map(|entry| entry.transition1.clone()).unwrap_or(None)),
-                ("Right", KeyCode::Right, &entries.get(self.cursor).map(|entry| entry.transition2.clone()).unwrap_or(None)),
-            ] {
-                if input.pressed_keys.contains(&key_code) {
-                    if let Some(transition) = transition {
-                        self.curr_entries_id = transition.id.to_string();
-                        self.reset_entries_state();
-                        return Action::Transit {
-                            id: transition.id.to_string(),
-                            cursor: None,
-                        };
-                    }
-
-                    else {
-                        self.show_popup(&format!("There's no transition mapped to Ctrl+{key} key."));
-                    }
-                }
-            }
+        // The keymap has priority over the keys of the entry actions.
+        if self.keymap.triggered(input).is_empty() && let Some(i) = entries.actions.iter().position(|action| action.key.map(|key| key.is_pressed(input)).unwrap_or(false)) {
+            self.run_entry_action(entries, i);
         }
 
         let mut camera_move_speed = 1.0;
@@ -200,7 +153,7 @@ impl State {
         let mut zoom_in = false;
         let mut zoom_out = false;
 
-        if input.mouse_pos.0 < side_bar_start {
+        if !is_mouse_on_side_bar {
             if is_ctrl_down {
                 if input.mouse_wheel.1 < 0.0 {
                     zoom_in = true;
@@ -240,23 +193,23 @@ impl State {
             (10.0 / self.camera_zoom * camera_move_speed, false)
         };
 
-        if input.down_keys.contains(&KeyCode::W) || scroll_up {
+        if self.keymap.is_held(Command::MoveCameraUp, input) || scroll_up {
             self.camera_pos.1 -= camera_move_speed;
         }
 
-        if input.down_keys.contains(&KeyCode::A) || scroll_left {
+        if self.keymap.is_held(Command::MoveCameraLeft, input) || scroll_left {
             self.camera_pos.0 -= camera_move_speed;
         }
 
-        if input.down_keys.contains(&KeyCode::S) || scroll_down {
+        if self.keymap.is_held(Command::MoveCameraDown, input) || scroll_down {
             self.camera_pos.1 += camera_move_speed;
         }
 
-        if input.down_keys.contains(&KeyCode::D) || scroll_right {
+        if self.keymap.is_held(Command::MoveCameraRight, input) || scroll_right {
             self.camera_pos.0 += camera_move_speed;
         }
 
-        if input.down_keys.contains(&KeyCode::Z) || zoom_in {
+        if self.keymap.is_held(Command::ZoomIn, input) || zoom_in {
             if zoom_faster {
                 self.camera_zoom = (self.camera_zoom * 1.2).min(8.0);
             }
@@ -266,7 +219,7 @@ impl State {
             }
         }
 
-        if input.down_keys.contains(&KeyCode::X) || zoom_out {
+        if self.keymap.is_held(Command::ZoomOut, input) || zoom_out {
             if zoom_faster {
                 self.camera_zoom = (self.camera_zoom * 0.8333).max(0.1);
             }
@@ -276,22 +229,91 @@ impl State {
             }
         }
 
-        if self.wide_side_bar && check_contain(
-            [584.0, 344.0, 32.0, 32.0],
-            input.mouse_pos,
-        ) && input.mouse_pressed[0] {
-            self.wide_side_bar = false;
+        if let Some(splitter) = self.dragging {
+            if input.mouse_down[0] && !input.mouse_released[0] {
+                self.drag_splitter(splitter, input.mouse_pos);
+            }
+
+            else {
+                self.dragging = None;
+            }
         }
 
-        else if !self.wide_side_bar && check_contain(
-            [884.0, 344.0, 32.0, 32.0],
-            input.mouse_pos,
-        ) && input.mouse_pressed[0] {
-            self.wide_side_bar = true;
+        else if let Some((start, _)) = self.text_selection {
+            if input.mouse_down[0] && !input.mouse_released[0] {
+                self.text_selection = Some((start, self.screen_to_canvas(input.mouse_pos)));
+            }
+
+            else {
+                self.copy_text_selection(entries);
+            }
+        }
+
+        else if check_contain(self.layout().toggle_button(), input.mouse_pos) && input.mouse_pressed[0] {
+            self.wide_side_bar = !self.wide_side_bar;
+            self.side_bar_size = None;
+        }
+
+        else if let Some(splitter) = self.hovered_splitter(input) && input.mouse_pressed[0] {
+            self.dragging = Some(splitter);
         }
 
         else if let Some(i) = self.hovered_entry && input.mouse_pressed[0] {
+            let cursor = self.cursor;
             self.cursor = i;
+
+            if is_shift_down {
+                self.select_range(cursor);
+            }
+
+            else if is_ctrl_down {
+                self.toggle_selection(i);
+            }
+        }
+
+        else if input.mouse_pressed[0]
+            && !self.show_help
+            && let Some(action) = self.hovered_canvas_action(input) {
+            let action = match action {
+                ClickAction::Custom(id) => match (entries.on_click, entries.get(self.cursor)) {
+                    (Some(on_click), Some(entry)) => on_click(entry, self.entry_state, &id),
+                    _ => None,
+                },
+                action => Some(action),
+            };
+
+            match action {
+                Some(ClickAction::Toggle(key)) => {
+                    let canvas_key = (self.curr_entries_id.to_string(), self.cursor, self.entry_state);
+                    self.canvas_states.entry(canvas_key.clone()).or_default().toggle(&key);
+                    self.cache.canvas.remove(&canvas_key);
+                },
+                Some(ClickAction::Transit { id, cursor }) => {
+                    return Action::Transit { id, cursor };
+                },
+                Some(ClickAction::SetEntryState(s)) => {
+                    if s < entries.entry_state_count {
+                        self.entry_state = EntryState(s);
+                    }
+
+                    else {
+                        self.show_popup(&format!("There's no state {s}."));
+                    }
+                },
+                Some(ClickAction::Open(path)) => {
+                    if let Err(e) = open_external(&path) {
+                        self.show_popup(&format!("Failed to open `{path}`: {e}"));
+                    }
+                },
+                // `on_click` is not supposed to return another custom action.
+                Some(ClickAction::Custom(_)) | None => {},
+            }
+        }
+
+        // Dragging on the canvas (not on a clickable area) selects text.
+        else if input.mouse_pressed[0] && !self.show_help && check_contain(self.layout().canvas, input.mouse_pos) {
+            let pos = self.screen_to_canvas(input.mouse_pos);
+            self.text_selection = Some((pos, pos));
         }
 
         if self.cursor != original_cursor {
@@ -301,19 +323,568 @@ impl State {
         Action::None
     }
 
-    fn show_popup(&mut self, message: &str) {
+    /// It runs a command that's not continuous (see `Command::is_continuous`). It's used by both the
+    /// keymap and the command palette.
+    fn run_command(&mut self, entries: &Entries, command: Command) -> Option<Action> {
+        match command {
+            Command::Quit => {
+                return Some(Action::Quit);
+            },
+            Command::NextEntry => {
+                if !entries.is_empty() {
+                    self.cursor = (self.cursor + 1) % entries.len();
+                }
+            },
+            Command::PrevEntry => {
+                if !entries.is_empty() {
+                    self.cursor = (self.cursor + entries.len() - 1) % entries.len();
+                }
+            },
+            Command::QuickJump(n) => {
+                if !entries.is_empty() {
+                    self.cursor = (n - 1) * (entries.len() - 1) / 8;
+                }
+            },
+            Command::ExpandSideBar => {
+                self.wide_side_bar = true;
+                self.side_bar_size = None;
+            },
+            Command::ShrinkSideBar => {
+                self.wide_side_bar = false;
+                self.side_bar_size = None;
+            },
+            Command::ToggleHelp => {
+                self.show_help = !self.show_help;
+            },
+            Command::NextEntryState | Command::PrevEntryState => {
+                if entries.entry_state_count < 2 {
+                    self.show_popup("There's no state to change!");
+                }
+
+                else if command == Command::NextEntryState {
+                    self.entry_state.0 = (self.entry_state.0 + 1) % entries.entry_state_count;
+                }
+
+                else {
+                    self.entry_state.0 = (self.entry_state.0 + entries.entry_state_count - 1) % entries.entry_state_count;
+                }
+            },
+            Command::ResetCamera => {
+                self.camera_zoom = 1.0;
+                self.camera_pos = (450.0, 300.0);
+            },
+            Command::ShowHistory => {
+                if let Some(entry) = entries.get(self.cursor) {
+                    return Some(Action::ShowHistory { name: entry.name.to_string() });
+                }
+
+                else {
+                    self.show_popup("There's no entry to see the history of!");
+                }
+            },
+            Command::FindFlakyEntries => {
+                return Some(Action::ShowFlakyEntries);
+            },
+            Command::ToggleDashboard => {
+                self.show_dashboard = !self.show_dashboard;
+            },
+            Command::OpenPalette => {
+                return Some(Action::OpenPalette);
+            },
+            Command::GoBack => {
+                return Some(Action::GoBack);
+            },
+            Command::GoForward => {
+                return Some(Action::GoForward);
+            },
+            Command::TogglePin => {
+                return Some(Action::TogglePin);
+            },
+            Command::ShowPinned => {
+                return Some(Action::ShowPinned);
+            },
+            Command::EditAnnotation => {
+                return Some(Action::EditAnnotation);
+            },
+            Command::ShowIssue => {
+                return Some(Action::ShowIssue { issue: None });
+            },
+            Command::SearchEntry => {
+                if entries.is_empty() {
+                    self.show_popup("There's no entry to search!");
+                }
+
+                else {
+                    self.search = Some(EntrySearch::new(entries));
+                }
+            },
+            Command::Transit | Command::Transit1 | Command::Transit2 => {
+                let transition = match command {
+                    Command::Transit => entries.transition.clone(),
+                    Command::Transit1 => entries.get(self.cursor).and_then(|entry| entry.transition1.clone()),
+                    _ => entries.get(self.cursor).and_then(|entry| entry.transition2.clone()),
+                };
+
+                if let Some(transition) = transition {
+                    // An entry of a linked entries (e.g. "Pinned") goes to the entry that it stands for.
+                    let cursor = match command {
+                        Command::Transit1 => entries.get(self.cursor).and_then(|entry| linked_cursor(entries, entry)),
+                        _ => None,
+                    };
+
+                    return Some(Action::Transit {
+                        id: transition.id.to_string(),
+                        cursor,
+                    });
+                }
+
+                else {
+                    self.show_popup(&format!("There's no transition mapped to {} key.", self.keymap.describe(&[command])));
+                }
+            },
+            Command::Filter(n) => {
+                if let Some(filter) = entries.filters.get(n - 1) {
+                    return Some(self.filter(entries, &filter.name, |_, e| (filter.cond)(e)));
+                }
+            },
+            Command::SetTriageLabel(n) => {
+                if n > self.triage_labels.len() {
+                    self.show_popup(&format!("There's no triage label {n}."));
+                    return None;
+                }
+
+                return Some(Action::SetTriageLabel { label: Some(n - 1) });
+            },
+            Command::ClearTriageLabel => {
+                return Some(Action::SetTriageLabel { label: None });
+            },
+            Command::FilterByTriageLabel => {
+                let label = entries.get(self.cursor).and_then(|entry| self.triage_label_of(entries, entry));
+                return Some(self.filter_by_triage_label(entries, label));
+            },
+            Command::ExtendSelectionDown | Command::ExtendSelectionUp => {
+                if !entries.is_empty() {
+                    let cursor = self.cursor;
+
+                    self.cursor = if command == Command::ExtendSelectionDown {
+                        (self.cursor + 1).min(entries.len() - 1)
+                    } else {
+                        self.cursor.max(1) - 1
+                    };
+                    self.select_range(cursor);
+                }
+            },
+            Command::ToggleSelection => {
+                if !entries.is_empty() {
+                    self.toggle_selection(self.cursor);
+                }
+            },
+            Command::SelectAll => {
+                self.selection = (0..entries.len()).collect();
+                self.selection_anchor = None;
+            },
+            Command::ClearSelection => {
+                self.clear_selection();
+            },
+            Command::ShowSelection => {
+                if self.selection.is_empty() {
+                    self.show_popup("There's no selected entry!");
+                    return None;
+                }
+
+                let selection = self.selection.clone();
+                return Some(self.filter(entries, "selected", |i, _| selection.contains(&i)));
+            },
+            Command::CopyEntryName => {
+                let names = self.targets().into_iter().filter_map(|i| entries.get(i)).map(|entry| entry.name.to_string()).collect::<Vec<_>>();
+
+                match names.as_slice() {
+                    [] => { self.show_popup("There's no entry to copy!"); },
+                    [name] => { self.copy_to_clipboard(name, &format!("`{name}`")); },
+                    _ => { self.copy_to_clipboard(&names.join("\n"), &format!("{} names", names.len())); },
+                }
+            },
+            Command::CopyEntryContent => match entries.get(self.cursor).and_then(|entry| entry.content.clone()) {
+                Some(content) => { self.copy_to_clipboard(&content, "the content"); },
+                None => { self.show_popup("This entry has no content!"); },
+            },
+            Command::CopyCanvasText => {
+                let text = graphics_to_text(&self.shown_canvas(entries), None);
+
+                if text.is_empty() {
+                    self.show_popup("There's no text on the canvas!");
+                } else {
+                    self.copy_to_clipboard(&text, "the text of the canvas");
+                }
+            },
+            Command::ExportHtml => {
+                return Some(Action::ExportHtml { all: false });
+            },
+            Command::ExportAllHtml => {
+                return Some(Action::ExportHtml { all: true });
+            },
+            Command::ExportCanvasSvg => {
+                return Some(Action::ExportCanvasSvg);
+            },
+            Command::RunEntryAction(n) => {
+                if n <= entries.actions.len() {
+                    self.run_entry_action(entries, n - 1);
+                }
+
+                else {
+                    self.show_popup(&format!("There's no action mapped to {} key.", self.keymap.describe(&[command])));
+                }
+            },
+            Command::MoveCameraUp
+            | Command::MoveCameraLeft
+            | Command::MoveCameraDown
+            | Command::MoveCameraRight
+            | Command::ZoomIn
+            | Command::ZoomOut => {},
+        }
+
+        None
+    }
+
+    // While the palette is open, it takes all the keyboard inputs.
+    fn palette_frame(&mut self, entries: &Entries, input: &Input) -> Action {
+        let Some(palette) = &mut self.palette else { unreachable!() };
+
+        for ch in input.typed_chars.iter() {
+            palette.push_char(*ch);
+        }
+
+        if input.pressed_keys.contains(&KeyCode::Backspace) {
+            palette.pop_char();
+        }
+
+        if input.pressed_keys.contains(&KeyCode::Down) {
+            palette.move_cursor(true);
+        }
+
+        else if input.pressed_keys.contains(&KeyCode::Up) {
+            palette.move_cursor(false);
+        }
+
+        // Esc quits the app when it's released, so the palette has to be closed
+        // on release too. Otherwise, the same key press would close the palette and
+        // then quit the app.
+        if input.released_keys.contains(&KeyCode::Escape) {
+            self.palette = None;
+        }
+
+        else if input.pressed_keys.contains(&KeyCode::Enter) || input.pressed_keys.contains(&KeyCode::KpEnter) {
+            let selected = palette.selected().map(|item| item.action.clone());
+            self.palette = None;
+
+            match selected {
+                Some(PaletteAction::Command(command)) => {
+                    return self.run_command(entries, command).unwrap_or(Action::None);
+                },
+                Some(PaletteAction::GoTo(id)) => {
+                    return Action::Transit { id, cursor: None };
+                },
+                Some(PaletteAction::ShowIssue(issue)) => {
+                    return Action::ShowIssue { issue: Some(issue) };
+                },
+                Some(PaletteAction::FilterByTriageLabel(label)) => {
+                    return self.filter_by_triage_label(entries, label);
+                },
+                Some(PaletteAction::RunEntryAction(i)) => {
+                    self.run_entry_action(entries, i);
+                },
+                None => {},
+            }
+        }
+
+        Action::None
+    }
+
+    // Like the palette, the search prompt takes all the keyboard inputs. The user can
+    // also click an entry in the search results.
+    fn search_frame(&mut self, entries: &Entries, input: &Input) {
+        let Some(search) = &mut self.search else { unreachable!() };
+
+        for ch in input.typed_chars.iter() {
+            search.push_char(*ch, entries);
+        }
+
+        if input.pressed_keys.contains(&KeyCode::Backspace) {
+            search.pop_char(entries);
+        }
+
+        if input.pressed_keys.contains(&KeyCode::Down) {
+            search.move_cursor(true);
+        }
+
+        else if input.pressed_keys.contains(&KeyCode::Up) {
+            search.move_cursor(false);
+        }
+
+        // See the comment in `palette_frame`.
+        if input.released_keys.contains(&KeyCode::Escape) {
+            self.search = None;
+        }
+
+        else if input.pressed_keys.contains(&KeyCode::Enter) || input.pressed_keys.contains(&KeyCode::KpEnter) {
+            if let Some(i) = search.selected() {
+                self.cursor = i;
+            }
+
+            self.search = None;
+        }
+
+        else if let Some(i) = self.hovered_entry && input.mouse_pressed[0] {
+            self.cursor = i;
+            self.search = None;
+        }
+    }
+
+    // Like the palette, the editor takes all the keyboard inputs.
+    fn annotation_frame(&mut self, input: &Input) {
+        let Some(editor) = &mut self.annotation_editor else { unreachable!() };
+        let (_, is_shift_down, _) = modifiers(input);
+
+        for ch in input.typed_chars.iter() {
+            editor.curr_field().push(*ch);
+        }
+
+        if input.pressed_keys.contains(&KeyCode::Backspace) {
+            editor.curr_field().pop();
+        }
+
+        if input.pressed_keys.contains(&KeyCode::Tab) {
+            editor.field = 1 - editor.field;
+        }
+
+        // See the comment in `palette_frame`.
+        if input.released_keys.contains(&KeyCode::Escape) {
+            self.annotation_editor = None;
+        }
+
+        else if input.pressed_keys.contains(&KeyCode::Enter) || input.pressed_keys.contains(&KeyCode::KpEnter) {
+            if is_shift_down && editor.field == 0 {
+                editor.note.push('\n');
+            }
+
+            else {
+                let Some(editor) = self.annotation_editor.take() else { unreachable!() };
+
+                for (scope, name) in editor.targets.iter() {
+                    let label = self.annotations.get(scope, name).and_then(|a| a.label.clone());
+
+                    if let Err(e) = self.annotations.set(scope, name, editor.annotation(label)) {
+                        self.show_popup(&e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    // If `label` is `None`, it's the entries without a label.
+    fn filter_by_triage_label(&self, entries: &Entries, label: Option<String>) -> Action {
+        let name = label.as_ref().map(|label| label.to_string()).unwrap_or_else(|| String::from("untriaged"));
+        self.filter(entries, &name, |_, e| self.triage_label_of(entries, e) == label)
+    }
+
+    // It creates a tmp entries with the entries that pass the filter.
+    fn filter(&self, entries: &Entries, name: &str, cond: impl Fn(usize, &Entry) -> bool) -> Action {
+        let mut new_cursor = None;
+        let filtered_entries = entries.iter().enumerate().map(|(i, e)| (e, cond(i, e)));
+        let new_entries: Vec<Entry> = filtered_entries.into_iter().enumerate().map(
+            |(j, (e, cond))| (e.clone(), cond, j == self.cursor)
+        ).filter(
+            |(_, cond, _)| *cond
+        ).enumerate().map(
+            |(j, (e, _, selected))| {
+                // There's no unique identifier for `Entry`, so we have to do this to
+                // calculate `new_cursor`.
+                if selected {
+                    new_cursor = Some(j);
+                }
+
+                e
+            }
+        ).collect();
+
+        Action::TransitToTmpEntries {
+            entries: Box::new(Entries {
+                id: format!("@@tmp-{:x}", rand::random::<u64>()),
+                title: entries.title.as_ref().map(|t| format!("{t} ({name})")),
+                entries: new_entries,
+                entry_state_count: entries.entry_state_count,
+                transition: Some(Transition {
+                    id: entries.id.clone(),
+                    description: Some(String::from("exit filter view")),
+                }),
+                filters: vec![],
+                actions: entries.actions.clone(),
+                render_canvas: entries.render_canvas,
+                render_stateful_canvas: entries.render_stateful_canvas,
+                on_click: entries.on_click,
+                render_top_bar_extra_message: entries.render_top_bar_extra_message,
+                run: entries.run.clone(),
+            }),
+            cursor: new_cursor,
+        }
+    }
+
+    pub fn hovered_splitter(&self, input: &Input) -> Option<Splitter> {
+        let layout = self.layout();
+
+        [Splitter::SideBar, Splitter::TopBar].into_iter().find(
+            |splitter| check_contain(layout.splitter(*splitter), input.mouse_pos)
+        )
+    }
+
+    fn drag_splitter(&mut self, splitter: Splitter, mouse_pos: (f32, f32)) {
+        let size = self.layout().drag(splitter, mouse_pos, self.screen_size);
+
+        match splitter {
+            Splitter::SideBar => {
+                self.side_bar_size = Some(size);
+
+                // `Layout::new` clamps the size, and we remember the clamped one.
+                let layout = self.layout();
+                let [_, _, w, h] = layout.side_bar;
+                self.side_bar_size = Some(if layout.side_bar_position == SideBarPosition::Bottom { h } else { w });
+                self.wide_side_bar = layout.is_side_bar_wide();
+            },
+            Splitter::TopBar => {
+                self.top_bar_height = size;
+            },
+        }
+    }
+
+    /// It returns the action of the `Graphic::Clickable` under the mouse.
+    pub fn hovered_canvas_action(&mut self, input: &Input) -> Option<ClickAction> {
+        Some(self.hovered_clickable(input)?.1)
+    }
+
+    /// It returns the rect (in canvas coordinates) and the action of the `Graphic::Clickable` under the mouse.
+    pub fn hovered_clickable(&mut self, input: &Input) -> Option<([f32; 4], ClickAction)> {
+        // The dashboard has no clickable areas, and it hides the canvas.
+        if !check_contain(self.layout().canvas, input.mouse_pos) || self.show_dashboard {
+            return None;
+        }
+
+        let mouse_pos = self.screen_to_canvas(input.mouse_pos);
+
+        for graphic in self.curr_canvas()?.iter().rev() {
+            if let Graphic::Clickable { x, y, w, h, action } = graphic && check_contain([*x, *y, *w, *h], mouse_pos) {
+                return Some(([*x, *y, *w, *h], action.clone()));
+            }
+        }
+
+        None
+    }
+
+    // It runs the action on the selected entries (or the current entry) in another thread.
+    fn run_entry_action(&mut self, entries: &Entries, index: usize) {
+        let action = &entries.actions[index];
+        let targets = self.targets().into_iter().filter(|i| *i < entries.len()).collect::<Vec<_>>();
+
+        if targets.is_empty() {
+            self.show_popup("There's no entry to run the action on!");
+            return;
+        }
+
+        self.running_actions.push(RunningAction::spawn(action, entries, &targets));
+        self.show_popup(&match targets.len() {
+            1 => format!("Running `{}`...", action.name),
+            n => format!("Running `{}` on {n} entries...", action.name),
+        });
+    }
+
+    // It's called every frame, and it shows the result of a finished action.
+    fn poll_entry_actions(&mut self) -> Option<Action> {
+        let i = self.running_actions.iter().position(|action| action.is_finished())?;
+
+        match self.running_actions.remove(i).join() {
+            ActionResult::Message(message) => {
+                self.show_popup(&message);
+                None
+            },
+            ActionResult::Entries(entries) => Some(Action::TransitToTmpEntries { entries, cursor: None }),
+        }
+    }
+
+    fn copy_to_clipboard(&mut self, s: &str, what: &str) {
+        clipboard_set(s);
+        self.show_popup(&format!("Copied {what}."));
+    }
+
+    // It copies the text in the area that the user has dragged over.
+    fn copy_text_selection(&mut self, entries: &Entries) {
+        let Some(((x1, y1), (x2, y2))) = self.text_selection.take() else { return; };
+
+        // It's a click, not a drag.
+        if (x1 - x2).abs() < 4.0 && (y1 - y2).abs() < 4.0 {
+            return;
+        }
+
+        let rect = [x1.min(x2), y1.min(y2), (x1 - x2).abs(), (y1 - y2).abs()];
+        let text = graphics_to_text(&self.shown_canvas(entries), Some(rect));
+
+        if !text.is_empty() {
+            self.copy_to_clipboard(&text, &format!("{} characters", text.chars().count()));
+        }
+    }
+
+    pub fn show_popup(&mut self, message: &str) {
         self.popup = Some((120, message.to_string()));
     }
 
-    fn reset_entries_state(&mut self) {
+    pub fn reset_entries_state(&mut self) {
         self.cursor = 0;
         self.hovered_entry = None;
+        self.clear_selection();
         self.reset_entry_state();
     }
 
-    fn reset_entry_state(&mut self) {
+    // It selects the entries between the anchor and the cursor. If there's no anchor,
+    // `from` becomes the anchor.
+    fn select_range(&mut self, from: usize) {
+        let anchor = *self.selection_anchor.get_or_insert(from);
+        self.selection = (anchor.min(self.cursor)..=anchor.max(self.cursor)).collect();
+    }
+
+    fn toggle_selection(&mut self, i: usize) {
+        match self.selection.binary_search(&i) {
+            Ok(j) => { self.selection.remove(j); },
+            Err(j) => { self.selection.insert(j, i); },
+        }
+
+        self.selection_anchor = Some(i);
+    }
+
+    pub fn reset_entry_state(&mut self) {
         self.entry_state = EntryState(0);
         self.camera_pos = (450.0, 300.0);
         self.camera_zoom = 1.0;
     }
 }
+
+// It opens a url or a file with the default application of the OS.
+// `path` may come from a test output, so it only opens urls and existing files, and it
+// doesn't go through a shell.
+fn open_external(path: &str) -> Result<(), String> {
+    use std::process::Command;
+
+    let is_url = ["http://", "https://", "file://"].iter().any(|scheme| path.starts_with(scheme));
+
+    if !is_url && !std::path::Path::new(path).exists() {
+        return Err(String::from("It's neither a url (http, https or file) nor an existing file."));
+    }
+
+    let result = if cfg!(target_os = "macos") {
+        Command::new("open").arg(path).spawn()
+    } else if cfg!(target_os = "windows") {
+        Command::new("explorer.exe").arg(path).spawn()
+    } else {
+        Command::new("xdg-open").arg(path).spawn()
+    };
+
+    result.map(|_| ()).map_err(|e| format!("{e:?}"))
+}
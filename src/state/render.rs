@@ -1,40 +1,57 @@
 use super::State;
 use crate::config::Config;
 use crate::entry::{Entries, EntryFlag};
-use crate::graphic::{Graphic, TextBox};
+use crate::graphic::{Graphic, TextBox, flag_color, push_text, truncate};
 use crate::input::Input;
+use crate::keymap::Command;
+use crate::pin::{PIN_COLOR, entry_key};
 use crate::transform::{check_contain, move_rel, scale};
+use crate::triage::label_color;
 use macroquad::color::Color;
 
+const ANNOTATION_COLOR: Color = Color { r: 0.55, g: 0.75, b: 0.95, a: 1.0 };
+const SELECTION_COLOR: Color = Color { r: 0.35, g: 0.45, b: 0.7, a: 0.45 };
+
 impl State {
-    /// It thinks that the screen is always 1080x720.
+    /// It draws in the design space (see `layout::design_size`).
     /// There's another function out there that fits the graphics
     /// to the actual screen size.
     pub fn render(&mut self, input: &Input, entries: &Entries, config: &Config) -> Vec<Graphic> {
         let mut graphics = vec![];
 
-        self.render_canvas(&mut graphics);
+        self.render_canvas(input, entries, &mut graphics);
         self.render_top_bar(config, entries, &mut graphics);
         self.render_side_bar(input, config, entries, &mut graphics);
+        self.render_splitters(input, &mut graphics);
 
         if self.show_help {
             self.render_help(entries, &mut graphics);
         }
 
-        if let Some(_) = &self.popup {
+        if self.palette.is_some() {
+            self.render_palette(&mut graphics);
+        }
+
+        if self.annotation_editor.is_some() {
+            self.render_annotation_editor(&mut graphics);
+        }
+
+        if self.popup.is_some() {
             self.render_popup(&mut graphics);
         }
 
         graphics
     }
 
-    fn render_top_bar(&self, config: &Config, entries: &Entries, graphics: &mut Vec<Graphic>) {
+    fn render_top_bar(&mut self, config: &Config, entries: &Entries, graphics: &mut Vec<Graphic>) {
+        let [top_bar_x, top_bar_y, top_bar_w, top_bar_h] = self.layout().top_bar;
+
         // bg
         graphics.push(Graphic::Rect {
-            x: 0.0,
-            y: 0.0,
-            w: if self.wide_side_bar { 600.0 } else { 900.0 },
-            h: 120.0,
+            x: top_bar_x,
+            y: top_bar_y,
+            w: top_bar_w,
+            h: top_bar_h,
             radius: None,
             thickness: None,
             color: config.top_bar_bg,
@@ -50,14 +67,38 @@ impl State {
         if !entries.is_empty() {
             let entry = &entries[self.cursor];
             lines.push((entry.name.to_string(), entry.flag, false));
+
+            if let Some(message) = self.curr_top_bar_extra_message() {
+                lines.push((message, EntryFlag::None, true));
+            }
+
+            if let Some(annotation) = self.annotation_of(entries, entry) {
+                lines.push((annotation.summary(), EntryFlag::None, true));
+            }
         }
 
-        lines.push((format!(
-            "{}{}{}H: Help",
-            if let Some(t) = &entries.transition { format!("Ctrl+Up: {}, ", t.description.as_ref().unwrap_or(&t.id)) } else { String::new() },
-            if let Some(Some(t)) = &entries.get(self.cursor).map(|e| &e.transition1) { format!("Ctrl+Left: {}, ", t.description.as_ref().unwrap_or(&t.id)) } else { String::new() },
-            if let Some(Some(t)) = &entries.get(self.cursor).map(|e| &e.transition2) { format!("Ctrl+Right: {}, ", t.description.as_ref().unwrap_or(&t.id)) } else { String::new() },
-        ), EntryFlag::None, true));
+        let mut hints = vec![];
+
+        for (command, transition) in [
+            (Command::Transit, entries.transition.as_ref()),
+            (Command::Transit1, entries.get(self.cursor).and_then(|e| e.transition1.as_ref())),
+            (Command::Transit2, entries.get(self.cursor).and_then(|e| e.transition2.as_ref())),
+            (Command::ToggleHelp, None),
+        ] {
+            let keys = self.keymap.describe(&[command]);
+
+            if keys.is_empty() {
+                continue;
+            }
+
+            match (command, transition) {
+                (Command::ToggleHelp, _) => { hints.push(format!("{keys}: Help")); },
+                (_, Some(t)) => { hints.push(format!("{keys}: {}", t.description.as_ref().unwrap_or(&t.id))); },
+                (_, None) => {},
+            }
+        }
+
+        lines.push((hints.join(", "), EntryFlag::None, true));
         let (font_size, mut curr_y, line_height) = match lines.len() {
             0 | 1 => (21.0, 60.0, 0.0),
             2 => (21.0, 45.0, 40.0),
@@ -69,11 +110,18 @@ impl State {
                 (16.0, 24.0, 21.0)
             },
         };
-        let center = if self.wide_side_bar { 300.0 } else { 450.0 };
+        let center = top_bar_x + top_bar_w / 2.0;
+
+        // The lines are laid out for a 120px top bar. If the user makes the top bar
+        // smaller, everything shrinks. If it's bigger, the lines are centered.
+        let text_scale = (top_bar_h / 120.0).min(1.0);
+        let font_size = font_size * text_scale;
+        let line_height = line_height * text_scale;
+        curr_y = top_bar_y + curr_y * text_scale + (top_bar_h - 120.0 * text_scale) / 2.0;
 
         for (line, entry_flag, small_font) in lines.into_iter() {
             let font_size = if small_font { font_size * 0.75 } else { font_size };
-            let line_max_len = if self.wide_side_bar { (900.0 / font_size) as usize } else { (1350.0 / font_size) as usize };
+            let line_max_len = (top_bar_w * 1.5 / font_size) as usize;
             let truncated_line = if line.chars().count() > (line_max_len + 4) {
                 format!("{}...", line.chars().take(line_max_len).collect::<String>())
             } else {
@@ -98,19 +146,12 @@ impl State {
             match entry_flag {
                 EntryFlag::None => {},
                 _ => {
-                    let color = match entry_flag {
-                        EntryFlag::Red => Color { r: 0.75, g: 0.25, b: 0.25, a: 1.0 },
-                        EntryFlag::Green => Color { r: 0.25, g: 0.75, b: 0.25, a: 1.0 },
-                        EntryFlag::Blue => Color { r: 0.25, g: 0.25, b: 0.75, a: 1.0 },
-                        EntryFlag::None => unreachable!(),
-                    };
-
                     graphics.push(Graphic::Ellipse {
                         x: curr_x + 10.0,
                         y: curr_y - 6.25,
                         rx: 6.25,
                         ry: 6.25,
-                        color,
+                        color: flag_color(entry_flag),
                         thickness: None,
                     });
                 },
@@ -121,37 +162,63 @@ impl State {
     }
 
     fn render_side_bar(&mut self, input: &Input, config: &Config, entries: &Entries, graphics: &mut Vec<Graphic>) {
-        let (x, w) = if self.wide_side_bar { (600.0, 480.0) } else { (900.0, 180.0) };
-        let title_max_len = if self.wide_side_bar { 36 } else { 8 };
+        let layout = self.layout();
+        let [x, y, w, h] = layout.side_bar;
+        let title_max_len = (((w - 100.0) / 10.0) as usize).max(4);
 
         // bg
         graphics.push(Graphic::Rect {
             x,
-            y: 0.0,
+            y,
             w,
-            h: 720.0,
+            h,
             radius: None,
             thickness: None,
             color: config.side_bar_bg,
         });
 
-        let mut list_start = self.cursor.max(18) - 18;
-        let list_end = (list_start + 37).min(entries.len());
+        // 37 rows if the side bar is 720px high
+        let mut rows = (((h - 68.0) / 17.6) as usize).max(1);
+        let mut curr_y = y + 20.0;
+
+        // While the user is searching, the side bar shows the matched entries, and
+        // the first row is the prompt.
+        let (shown_entries, cursor) = match &self.search {
+            Some(search) => {
+                let prompt = format!("/ {}_", search.query);
+                let prompt = if prompt.chars().count() > title_max_len + 8 {
+                    prompt.chars().skip(prompt.chars().count() - title_max_len - 8).collect()
+                } else {
+                    prompt
+                };
+                push_text(graphics, &prompt, x + 6.4, curr_y, 15.0, config.side_bar_font);
+                curr_y += 17.6;
+                rows = rows.max(2) - 1;
+
+                (search.matches.clone(), search.cursor)
+            },
+            None => ((0..entries.len()).collect::<Vec<_>>(), self.cursor),
+        };
+        let mut list_start = cursor.max(rows / 2) - rows / 2;
+        let list_end = (list_start + rows).min(shown_entries.len());
 
-        if list_end < list_start + 37 {
-            list_start = list_end.max(37) - 37;
+        if list_end < list_start + rows {
+            list_start = list_end.max(rows) - rows;
         }
 
-        let mut curr_y = 20.0;
         let mut is_hovering_on_something = false;
 
-        for i in list_start..list_end {
+        for &i in shown_entries[list_start..list_end].iter() {
             let truncated_title = if entries[i].name.chars().count() > (title_max_len + 4) {
                 format!("{}...", entries[i].name.chars().take(title_max_len).collect::<String>())
             } else {
                 entries[i].name.to_string()
             };
-            let bullet = match (i == self.cursor, Some(i) == self.hovered_entry) {
+            let is_selected = match &self.search {
+                Some(search) => search.selected() == Some(i),
+                None => i == self.cursor,
+            };
+            let bullet = match (is_selected, Some(i) == self.hovered_entry) {
                 (true, false) => ">>",
                 (false, true) => " *",
                 (true, true) => "*>",
@@ -164,6 +231,18 @@ impl State {
             );
             let mut curr_x = x + 6.4;
 
+            if self.selection.binary_search(&i).is_ok() {
+                graphics.push(Graphic::Rect {
+                    x: x + 5.0,
+                    y: curr_y - 14.0,
+                    w: w - 40.0,
+                    h: 17.6,
+                    radius: None,
+                    thickness: None,
+                    color: SELECTION_COLOR,
+                });
+            }
+
             for ch in title.chars() {
                 if ch != ' ' {
                     graphics.push(Graphic::Char {
@@ -178,6 +257,24 @@ impl State {
                 curr_x += 8.0;
             }
 
+            let (entries_id, name) = entry_key(&self.original_entries_id, entries, &entries[i]);
+
+            if self.pins.contains(entries_id, name) {
+                graphics.push(Graphic::Rect {
+                    x: x + 1.0,
+                    y: curr_y - 14.0,
+                    w: 3.0,
+                    h: 16.0,
+                    radius: None,
+                    thickness: None,
+                    color: PIN_COLOR,
+                });
+            }
+
+            let annotation = self.annotations.get(self.scope_of(entries_id), name);
+            let has_note = annotation.map(|annotation| annotation.has_note()).unwrap_or(false);
+            let label = annotation.and_then(|annotation| annotation.label.as_ref());
+
             if check_contain([x + 5.0, curr_y - 17.6, w - 40.0, 17.6], input.mouse_pos) {
                 is_hovering_on_something = true;
                 self.hovered_entry = Some(i);
@@ -186,24 +283,48 @@ impl State {
             match entries[i].flag {
                 EntryFlag::None => {},
                 _ => {
-                    let color = match entries[i].flag {
-                        EntryFlag::Red => Color { r: 0.75, g: 0.25, b: 0.25, a: 1.0 },
-                        EntryFlag::Green => Color { r: 0.25, g: 0.75, b: 0.25, a: 1.0 },
-                        EntryFlag::Blue => Color { r: 0.25, g: 0.25, b: 0.75, a: 1.0 },
-                        EntryFlag::None => unreachable!(),
-                    };
-
                     graphics.push(Graphic::Ellipse {
                         x: curr_x + 7.0,
                         y: curr_y - 5.0,
                         rx: 5.0,
                         ry: 5.0,
-                        color,
+                        color: flag_color(entries[i].flag),
                         thickness: None,
                     });
                 },
             }
 
+            if let Some(label) = label {
+                graphics.push(Graphic::Rect {
+                    x: curr_x + 15.0,
+                    y: curr_y - 10.0,
+                    w: 9.0,
+                    h: 9.0,
+                    radius: Some(1.0),
+                    thickness: None,
+                    color: label_color(&self.triage_labels, label),
+                });
+            }
+
+            // a small speech bubble
+            if has_note {
+                graphics.push(Graphic::Rect {
+                    x: curr_x + 29.0,
+                    y: curr_y - 11.0,
+                    w: 10.0,
+                    h: 8.0,
+                    radius: Some(2.0),
+                    thickness: None,
+                    color: ANNOTATION_COLOR,
+                });
+                graphics.push(Graphic::Triangle {
+                    p1: (curr_x + 31.0, curr_y - 4.0),
+                    p2: (curr_x + 35.0, curr_y - 4.0),
+                    p3: (curr_x + 31.0, curr_y - 1.0),
+                    color: ANNOTATION_COLOR,
+                });
+            }
+
             curr_y += 17.6;
         }
 
@@ -211,12 +332,15 @@ impl State {
             self.hovered_entry = None;
         }
 
-        if self.wide_side_bar && entries.len() > 33 {
+        // There's no room for the scroll bar in a narrow side bar.
+        if w >= 400.0 && entries.len() + 4 > rows {
+            let bar_height = h - 80.0;
+
             graphics.push(Graphic::Rect {
-                x: 1050.0,
-                y: 20.0,
+                x: x + w - 30.0,
+                y: y + 20.0,
                 w: 10.0,
-                h: 640.0,
+                h: bar_height,
                 radius: None,
                 thickness: None,
                 color: Color {
@@ -227,21 +351,42 @@ impl State {
                 },
             });
 
-            for (i, color) in self.curr_scroll_bar_colors().iter().enumerate() {
+            let colors = self.curr_scroll_bar_colors();
+            let cell_height = bar_height / colors.len() as f32;
+
+            for (i, color) in colors.iter().enumerate() {
                 graphics.push(Graphic::Rect {
-                    x: 1055.0,
-                    y: 20.0 + (i * 5) as f32,
+                    x: x + w - 25.0,
+                    y: y + 20.0 + i as f32 * cell_height,
                     w: 4.0,
-                    h: 5.0,
+                    h: cell_height,
                     radius: None,
                     thickness: None,
                     color: *color,
                 });
             }
 
+            // The triage labels are drawn on the left edge of the bar.
+            let label_colors = self.curr_scroll_bar_label_colors();
+            let cell_height = bar_height / label_colors.len() as f32;
+
+            for (i, color) in label_colors.iter().enumerate() {
+                if let Some(color) = color {
+                    graphics.push(Graphic::Rect {
+                        x: x + w - 30.0,
+                        y: y + 20.0 + i as f32 * cell_height,
+                        w: 3.0,
+                        h: cell_height,
+                        radius: None,
+                        thickness: None,
+                        color: *color,
+                    });
+                }
+            }
+
             graphics.push(Graphic::Ellipse {
-                x: 1055.0,
-                y: 20.0 + (self.cursor * 640 / (entries.len() - 1)) as f32,
+                x: x + w - 25.0,
+                y: y + 20.0 + self.cursor as f32 * bar_height / (entries.len() - 1).max(1) as f32,
                 rx: 8.0,
                 ry: 8.0,
                 thickness: None,
@@ -249,14 +394,22 @@ impl State {
             });
         }
 
-        let counter = if entries.is_empty() { String::from("0 / 0") } else { format!("{} / {}", self.cursor + 1, entries.len()) };
-        let mut curr_x = 1065.0 - 8.8 * counter.chars().count() as f32;
+        let counter = if let Some(search) = &self.search {
+            format!("{} / {} matched", search.matches.len(), entries.len())
+        } else if entries.is_empty() {
+            String::from("0 / 0")
+        } else if !self.selection.is_empty() {
+            format!("{} / {} ({} selected)", self.cursor + 1, entries.len(), self.selection.len())
+        } else {
+            format!("{} / {}", self.cursor + 1, entries.len())
+        };
+        let mut curr_x = x + w - 15.0 - 8.8 * counter.chars().count() as f32;
 
         for ch in counter.chars() {
             graphics.push(Graphic::Char {
                 ch,
                 x: curr_x,
-                y: 680.0,
+                y: y + h - 40.0,
                 size: 16.0,
                 color: config.side_bar_font,
             });
@@ -264,92 +417,121 @@ impl State {
         }
 
         // "expand" button
-        if self.wide_side_bar {
-            let alpha = if check_contain([584.0, 344.0, 32.0, 32.0], input.mouse_pos) {
-                1.0
-            } else {
-                0.5
-            };
+        let button = layout.toggle_button();
+        let [p1, p2, p3] = layout.toggle_button_arrow();
+        let alpha = if check_contain(button, input.mouse_pos) {
+            1.0
+        } else {
+            0.5
+        };
 
-            graphics.push(Graphic::Rect {
-                x: 584.0,
-                y: 344.0,
-                w: 32.0,
-                h: 32.0,
+        graphics.push(Graphic::Rect {
+            x: button[0],
+            y: button[1],
+            w: button[2],
+            h: button[3],
+            radius: None,
+            thickness: None,
+            color: Color {
+                r: 0.7,
+                g: 0.7,
+                b: 0.7,
+                a: alpha,
+            },
+        });
+        graphics.push(Graphic::Triangle {
+            p1,
+            p2,
+            p3,
+            color: Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: alpha,
+            },
+        });
+    }
+
+    fn render_splitters(&self, input: &Input, graphics: &mut Vec<Graphic>) {
+        let splitter = match self.dragging {
+            Some(splitter) => splitter,
+            None => match self.hovered_splitter(input) {
+                Some(splitter) => splitter,
+                None => { return; },
+            },
+        };
+        let [x, y, w, h] = self.layout().splitter(splitter);
+
+        // a thin line in the middle of the splitter
+        let [x, y, w, h] = if w > h {
+            [x, y + h / 4.0, w, h / 2.0]
+        } else {
+            [x + w / 4.0, y, w / 2.0, h]
+        };
+
+        graphics.push(Graphic::Rect {
+            x,
+            y,
+            w,
+            h,
+            radius: None,
+            thickness: None,
+            color: Color {
+                r: 0.7,
+                g: 0.7,
+                b: 0.7,
+                a: if self.dragging.is_some() { 0.8 } else { 0.4 },
+            },
+        });
+    }
+
+    fn render_canvas(&mut self, input: &Input, entries: &Entries, graphics: &mut Vec<Graphic>) {
+        // The canvas has 900x600 resolution.
+        let mut canvas = self.shown_canvas(entries);
+        let layout = self.layout();
+
+        if !self.show_help && let Some(([x, y, w, h], _)) = self.hovered_clickable(input) {
+            canvas.push(Graphic::Rect {
+                x,
+                y,
+                w,
+                h,
                 radius: None,
                 thickness: None,
-                color: Color {
-                    r: 0.7,
-                    g: 0.7,
-                    b: 0.7,
-                    a: alpha,
-                },
-            });
-            graphics.push(Graphic::Triangle {
-                p1: (590.0, 350.0),
-                p2: (590.0, 370.0),
-                p3: (610.0, 360.0),
-                color: Color {
-                    r: 0.0,
-                    g: 0.0,
-                    b: 0.0,
-                    a: alpha,
-                },
+                color: Color { r: 1.0, g: 1.0, b: 1.0, a: 0.15 },
             });
         }
 
-        else {
-            let alpha = if check_contain([884.0, 344.0, 32.0, 32.0], input.mouse_pos) {
-                1.0
-            } else {
-                0.5
-            };
-
-            graphics.push(Graphic::Rect {
-                x: 884.0,
-                y: 344.0,
-                w: 32.0,
-                h: 32.0,
+        if let Some(((x1, y1), (x2, y2))) = self.text_selection {
+            canvas.push(Graphic::Rect {
+                x: x1.min(x2),
+                y: y1.min(y2),
+                w: (x1 - x2).abs(),
+                h: (y1 - y2).abs(),
                 radius: None,
                 thickness: None,
-                color: Color {
-                    r: 0.7,
-                    g: 0.7,
-                    b: 0.7,
-                    a: alpha,
-                },
-            });
-            graphics.push(Graphic::Triangle {
-                p1: (910.0, 350.0),
-                p2: (910.0, 370.0),
-                p3: (890.0, 360.0),
-                color: Color {
-                    r: 0.0,
-                    g: 0.0,
-                    b: 0.0,
-                    a: alpha,
-                },
+                color: SELECTION_COLOR,
             });
         }
-    }
 
-    fn render_canvas(&mut self, graphics: &mut Vec<Graphic>) {
-        // The canvas has 900x600 resolution.
-        let mut canvas = self.curr_canvas().unwrap_or(&vec![]).clone();
-        scale(&mut canvas, self.camera_zoom);
+        let zoom = self.camera_zoom * layout.canvas_scale();
+        scale(&mut canvas, zoom);
 
-        // The camera position is mapped to (450, 420) of the screen.
-        // -> canvas has rect (0, 120, 900, 600) and its center is (450, 420).
-        move_rel(&mut canvas, 450.0 - self.camera_pos.0 * self.camera_zoom, 420.0 - self.camera_pos.1 * self.camera_zoom);
+        // The camera position is mapped to the center of the canvas rect.
+        // In the default layout, the canvas rect is (0, 120, 900, 600) and its center is (450, 420).
+        let (center_x, center_y) = layout.canvas_center();
+        move_rel(&mut canvas, center_x - self.camera_pos.0 * zoom, center_y - self.camera_pos.1 * zoom);
         graphics.extend(canvas);
     }
 
     fn render_help(&mut self, entries: &Entries, graphics: &mut Vec<Graphic>) {
+        let (screen_width, screen_height) = self.screen_size;
+
         graphics.push(Graphic::Rect {
             x: 30.0,
             y: 30.0,
-            w: 1020.0,
-            h: 660.0,
+            w: screen_width - 60.0,
+            h: screen_height - 60.0,
             radius: Some(12.0),
             thickness: None,
             color: Color {
@@ -362,8 +544,8 @@ impl State {
         graphics.push(Graphic::Rect {
             x: 40.0,
             y: 40.0,
-            w: 1000.0,
-            h: 640.0,
+            w: screen_width - 80.0,
+            h: screen_height - 80.0,
             radius: Some(12.0),
             thickness: None,
             color: Color {
@@ -379,27 +561,86 @@ impl State {
         //       1. (very small) performance gain
         //       2. even if an entries have no state, we have to show help message if other entrieses have states
         let has_entry = !entries.is_empty();
+        let has_selection = !self.selection.is_empty();
         let has_entry_state = entries.entry_state_count > 1;
         let has_something_on_canvas = !self.curr_canvas().unwrap_or(&vec![]).is_empty();
         let has_transition = entries.transition.is_some() || entries.iter().any(|entry| entry.transition1.is_some() || entry.transition2.is_some());
 
-        let mut lines = vec![
-            (String::from("Esc: Quit"), true),
-            (String::from("Left/Right: Toggle side-bar"), true),
-            (String::from("Up/Down: Jump to prev/next entry"), has_entry),
-            (String::from("1~9: Quick jump"), has_entry),
-            (String::from("W/A/S/D: Move camera"), has_something_on_canvas),
-            (String::from("Shift + W/A/S/D: Move camera faster"), has_something_on_canvas),
-            (String::from("Z/X: Zoom In/Out"), has_something_on_canvas),
-            (String::from("Shift + Z/X: Zoom In/Out faster"), has_something_on_canvas),
-            (String::from("Space: Reset camera"), has_something_on_canvas),
-            (String::from("H: See help message"), true),
-            (String::from("N/M: Go to prev/next entry state"), has_entry_state),
-            (String::from("Ctrl + Up/Left/Right: Transit to another entries"), has_transition),
+        let quick_jumps = (1..=9).map(Command::QuickJump).collect::<Vec<_>>();
+        let move_camera = [Command::MoveCameraUp, Command::MoveCameraLeft, Command::MoveCameraDown, Command::MoveCameraRight];
+        let zoom = [Command::ZoomIn, Command::ZoomOut];
+
+        // (commands, description, show)
+        let mut groups: Vec<(Vec<Command>, String, bool)> = vec![
+            (vec![Command::Quit], String::from("Quit"), true),
+            (vec![Command::ExpandSideBar, Command::ShrinkSideBar], String::from("Toggle side-bar"), true),
+            (vec![Command::PrevEntry, Command::NextEntry], String::from("Jump to prev/next entry"), has_entry),
+            (quick_jumps, String::from("Quick jump"), has_entry),
+            (move_camera.to_vec(), String::from("Move camera"), has_something_on_canvas),
+            (zoom.to_vec(), String::from("Zoom In/Out"), has_something_on_canvas),
+            (vec![Command::ResetCamera], String::from("Reset camera"), has_something_on_canvas),
+            (vec![Command::ToggleHelp], String::from("See help message"), true),
+            (vec![Command::OpenPalette], String::from("Open command palette (every command, filter, transition and entries)"), true),
+            (vec![Command::PrevEntryState, Command::NextEntryState], String::from("Go to prev/next entry state"), has_entry_state),
+            (vec![Command::SearchEntry], String::from("Search entries by name (Enter to jump, Esc to cancel)"), has_entry),
+            (vec![Command::ShowHistory], String::from("See the history of this entry across runs"), has_entry),
+            (vec![Command::FindFlakyEntries], String::from("Find flaky entries across runs"), true),
+            (vec![Command::ToggleDashboard], String::from("Show/hide the summary of the entries"), true),
+            (vec![Command::Transit, Command::Transit1, Command::Transit2], String::from("Transit to another entries"), has_transition),
+            (vec![Command::GoBack, Command::GoForward], String::from("Go back/forward"), true),
+            (vec![Command::TogglePin], String::from("Pin/unpin this entry (or the selected entries)"), has_entry),
+            (vec![Command::ShowPinned], String::from("Show pinned entries"), true),
+            (vec![Command::EditAnnotation], String::from("Edit the note and the issues of this entry (or the selected entries)"), has_entry),
+            (vec![Command::ShowIssue], String::from("Show the entries linked to the issues of this entry"), has_entry),
+            (vec![Command::ClearTriageLabel], String::from("Clear the triage label of this entry"), has_entry),
+            (vec![Command::FilterByTriageLabel], String::from("Show the entries with the same triage label"), has_entry),
+            (vec![Command::ExtendSelectionUp, Command::ExtendSelectionDown], String::from("Select a range of entries (or Shift+click)"), has_entry),
+            (vec![Command::ToggleSelection], String::from("Select/unselect this entry (or Ctrl+click)"), has_entry),
+            (vec![Command::SelectAll], format!("Select all entries ({} to clear the selection)", self.keymap.describe(&[Command::Quit, Command::ClearSelection])), has_entry),
+            (vec![Command::ShowSelection], String::from("Show the selected entries"), has_selection),
+            (vec![Command::CopyEntryName], String::from("Copy the names of the selected entries (or this entry)"), has_entry),
+            (vec![Command::CopyEntryContent], String::from("Copy the content of this entry"), has_entry),
+            (vec![Command::CopyCanvasText], String::from("Copy the text of the canvas (or drag the mouse on the canvas)"), has_something_on_canvas),
+            (vec![Command::ExportHtml, Command::ExportAllHtml], String::from("Export these entries/every entries to an html file"), true),
+            (vec![Command::ExportCanvasSvg], String::from("Export the canvas to an svg file"), has_something_on_canvas),
         ];
 
+        for (i, label) in self.triage_labels.iter().enumerate().take(9) {
+            groups.push((vec![Command::SetTriageLabel(i + 1)], format!("Label this entry as `{}` (again to remove)", label.name), has_entry));
+        }
+
         for (i, filter) in entries.filters.iter().enumerate() {
-            lines.push((format!("Ctrl + {}: {}", i + 1, filter.name), true));
+            groups.push((vec![Command::Filter(i + 1)], filter.name.to_string(), true));
+        }
+
+        let mut lines = vec![];
+
+        for (commands, description, show) in groups.iter() {
+            let keys = self.keymap.describe(commands);
+
+            if keys.is_empty() {
+                continue;
+            }
+
+            lines.push((format!("{keys}: {description}"), *show));
+
+            // The camera commands are faster with Shift.
+            if commands[0].is_continuous() {
+                lines.push((format!("Shift + {keys}: {description} faster"), *show));
+            }
+
+            if commands[0] == Command::ExpandSideBar {
+                lines.push((String::from("Drag the borders to resize the side-bar and the top-bar"), true));
+            }
+        }
+
+        // The entry actions can have their own keys.
+        for (i, action) in entries.actions.iter().enumerate() {
+            let keys = action.describe_keys(i, &self.keymap);
+
+            if !keys.is_empty() {
+                lines.push((format!("{keys}: Run `{}` on this entry (or the selected entries)", action.name), has_entry));
+            }
         }
 
         let help_message = lines.into_iter().filter(|(_, show)| *show).map(|(s, _)| s).collect::<Vec<_>>().join("\n");
@@ -407,18 +648,142 @@ impl State {
             &help_message,
             18.0,
             Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 },
-            [72.0, 72.0, 936.0, 400.0],
+            [72.0, 72.0, screen_width - 144.0, screen_height - 144.0],
         ).render());
     }
 
+    fn render_palette(&self, graphics: &mut Vec<Graphic>) {
+        let Some(palette) = &self.palette else { return; };
+        let (screen_width, screen_height) = self.screen_size;
+        let w = (screen_width - 40.0).min(720.0);
+        let x = (screen_width - w) / 2.0;
+        let y = 60.0;
+        let rows = (((screen_height - y - 100.0) / 26.0) as usize).clamp(1, 12);
+        let list_start = palette.cursor.max(rows - 1) + 1 - rows;
+        let shown = palette.matches().skip(list_start).take(rows).count();
+        let h = 56.0 + shown.max(1) as f32 * 26.0 + 16.0;
+        let max_len = ((w - 40.0) / 8.8) as usize;
+        let white = Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
+        let gray = Color { r: 0.6, g: 0.6, b: 0.6, a: 1.0 };
+
+        graphics.push(Graphic::Rect { x: x - 4.0, y: y - 4.0, w: w + 8.0, h: h + 8.0, radius: Some(8.0), thickness: None, color: white });
+        graphics.push(Graphic::Rect { x, y, w, h, radius: Some(8.0), thickness: None, color: Color { r: 0.1, g: 0.1, b: 0.15, a: 1.0 } });
+
+        // the query
+        let query = format!("> {}_", palette.query);
+        let query = if query.chars().count() > max_len {
+            query.chars().skip(query.chars().count() - max_len).collect()
+        } else {
+            query
+        };
+        push_text(graphics, &query, x + 20.0, y + 34.0, 16.0, white);
+
+        let counter = format!("{} / {}", palette.cursor + usize::from(palette.match_count() > 0), palette.match_count());
+        push_text(graphics, &counter, x + w - 20.0 - counter.chars().count() as f32 * 7.7, y + 34.0, 14.0, gray);
+        graphics.push(Graphic::Line {
+            p1: (x + 10.0, y + 48.0),
+            p2: (x + w - 10.0, y + 48.0),
+            thickness: 1.0,
+            dash: None,
+            color: gray,
+        });
+
+        if shown == 0 {
+            push_text(graphics, "No matches", x + 20.0, y + 76.0, 16.0, gray);
+            return;
+        }
+
+        for (i, item) in palette.matches().enumerate().skip(list_start).take(rows) {
+            let row_y = y + 56.0 + (i - list_start) as f32 * 26.0;
+
+            if i == palette.cursor {
+                graphics.push(Graphic::Rect {
+                    x: x + 8.0,
+                    y: row_y,
+                    w: w - 16.0,
+                    h: 26.0,
+                    radius: Some(4.0),
+                    thickness: None,
+                    color: Color { r: 0.3, g: 0.3, b: 0.45, a: 1.0 },
+                });
+            }
+
+            let keys_len = item.keys.chars().count();
+            let label_max_len = max_len.saturating_sub(keys_len + 2).max(4);
+            let label = if item.label.chars().count() > label_max_len {
+                format!("{}...", item.label.chars().take(label_max_len.saturating_sub(3)).collect::<String>())
+            } else {
+                item.label.to_string()
+            };
+
+            push_text(graphics, &label, x + 20.0, row_y + 19.0, 16.0, white);
+            push_text(graphics, &item.keys, x + w - 20.0 - keys_len as f32 * 7.7, row_y + 19.0, 14.0, gray);
+        }
+    }
+
+    fn render_annotation_editor(&self, graphics: &mut Vec<Graphic>) {
+        let Some(editor) = &self.annotation_editor else { return; };
+        let (screen_width, screen_height) = self.screen_size;
+        let w = (screen_width - 40.0).min(720.0);
+        let h = (screen_height - 120.0).min(420.0);
+        let (x, y) = ((screen_width - w) / 2.0, 60.0);
+        let white = Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 };
+        let gray = Color { r: 0.6, g: 0.6, b: 0.6, a: 1.0 };
+        let max_len = ((w - 60.0) / 8.8) as usize;
+
+        graphics.push(Graphic::Rect { x: x - 4.0, y: y - 4.0, w: w + 8.0, h: h + 8.0, radius: Some(8.0), thickness: None, color: white });
+        graphics.push(Graphic::Rect { x, y, w, h, radius: Some(8.0), thickness: None, color: Color { r: 0.1, g: 0.1, b: 0.15, a: 1.0 } });
+        push_text(graphics, &truncate(&editor.title(), max_len), x + 20.0, y + 34.0, 16.0, ANNOTATION_COLOR);
+
+        let issues_y = y + h - 100.0;
+        let fields = [
+            ("note", &editor.note, y + 60.0, issues_y - y - 90.0),
+            ("issues (comma separated)", &editor.issues, issues_y, 32.0),
+        ];
+
+        for (i, (label, content, field_y, field_h)) in fields.into_iter().enumerate() {
+            push_text(graphics, label, x + 20.0, field_y, 14.0, gray);
+            graphics.push(Graphic::Rect {
+                x: x + 20.0,
+                y: field_y + 8.0,
+                w: w - 40.0,
+                h: field_h,
+                radius: Some(4.0),
+                thickness: Some(if i == editor.field { 2.0 } else { 1.0 }),
+                color: if i == editor.field { white } else { gray },
+            });
+
+            let rows = ((field_h - 8.0) / 20.0).max(1.0) as usize;
+            let mut lines = content.split('\n').map(|line| line.to_string()).collect::<Vec<_>>();
+
+            if i == editor.field {
+                lines.last_mut().unwrap().push('_');
+            }
+
+            // It shows the last lines, where the user is typing.
+            for (j, line) in lines.iter().skip(lines.len().max(rows) - rows).enumerate() {
+                let line = if line.chars().count() > max_len {
+                    line.chars().skip(line.chars().count() - max_len).collect()
+                } else {
+                    line.to_string()
+                };
+                push_text(graphics, &line, x + 30.0, field_y + 30.0 + j as f32 * 20.0, 16.0, white);
+            }
+        }
+
+        push_text(graphics, "Tab: switch field, Shift+Enter: new line, Enter: save, Esc: cancel", x + 20.0, y + h - 20.0, 13.0, gray);
+    }
+
     fn render_popup(&self, graphics: &mut Vec<Graphic>) {
         if let Some((life, message)) = &self.popup {
-            let center = if self.wide_side_bar { 300.0 } else { 450.0 };
+            let [canvas_x, canvas_y, canvas_w, canvas_h] = self.layout().canvas;
+            let center = canvas_x + canvas_w / 2.0;
+            let bottom = canvas_y + canvas_h;
             let mut curr_x = center - message.len() as f32 * 4.4;
 
             graphics.push(Graphic::Rect {
                 x: curr_x - 20.0,
-                y: 600.0,
+                y: bottom - 120.0,
                 w: message.len() as f32 * 8.8 + 40.0,
                 h: 80.0,
                 radius: None,
@@ -432,7 +797,7 @@ impl State {
             });
             graphics.push(Graphic::Rect {
                 x: curr_x - 16.0,
-                y: 604.0,
+                y: bottom - 116.0,
                 w: message.len() as f32 * 8.8 + 32.0,
                 h: 72.0,
                 radius: None,
@@ -449,7 +814,7 @@ impl State {
                 graphics.push(Graphic::Char {
                     ch,
                     x: curr_x,
-                    y: 645.0,
+                    y: bottom - 75.0,
                     size: 16.0,
                     color: Color {
                         r: 1.0,
@@ -2,7 +2,7 @@ use crate::graphic::Graphic;
 use crate::input::Input;
 
 pub fn fit_graphics_to_screen(
-    graphics: &mut Vec<Graphic>,
+    graphics: &mut [Graphic],
     canvas_width: f32,
     canvas_height: f32,
     screen_width: f32,
@@ -54,12 +54,49 @@ pub fn fit_graphics_to_screen(
                 *y2 = *y2 * scale + y_offset;
                 *y3 = *y3 * scale + y_offset;
             },
+            Graphic::Line { p1: (x1, y1), p2: (x2, y2), thickness, dash, .. } => {
+                *x1 = *x1 * scale + x_offset;
+                *x2 = *x2 * scale + x_offset;
+                *y1 = *y1 * scale + y_offset;
+                *y2 = *y2 * scale + y_offset;
+                *thickness *= scale;
+
+                if let Some((dash, gap)) = dash {
+                    *dash *= scale;
+                    *gap *= scale;
+                }
+            },
+            Graphic::Polyline { points, thickness, dash, .. } => {
+                for (x, y) in points.iter_mut() {
+                    *x = *x * scale + x_offset;
+                    *y = *y * scale + y_offset;
+                }
+
+                *thickness *= scale;
+
+                if let Some((dash, gap)) = dash {
+                    *dash *= scale;
+                    *gap *= scale;
+                }
+            },
+            Graphic::Polygon { points, thickness, .. } => {
+                for (x, y) in points.iter_mut() {
+                    *x = *x * scale + x_offset;
+                    *y = *y * scale + y_offset;
+                }
+
+                if let Some(thickness) = thickness {
+                    *thickness *= scale;
+                }
+            },
             Graphic::Char { x, y, size, .. } => {
                 *x = *x * scale + x_offset;
                 *y = *y * scale + y_offset;
                 *size *= scale;
             },
-            Graphic::ImageFile { x, y, w, h, .. } | Graphic::Image { x, y, w, h, .. } => {
+            Graphic::ImageFile { x, y, w, h, .. } |
+            Graphic::Image { x, y, w, h, .. } |
+            Graphic::Clickable { x, y, w, h, .. } => {
                 *x = *x * scale + x_offset;
                 *y = *y * scale + y_offset;
                 *w *= scale;
@@ -92,14 +129,15 @@ pub fn fit_input_to_screen(
     input.mouse_pos.1 = (input.mouse_pos.1 - y_offset) / scale;
 }
 
-pub fn move_rel(graphics: &mut Vec<Graphic>, x_offset: f32, y_offset: f32) {
+pub fn move_rel(graphics: &mut [Graphic], x_offset: f32, y_offset: f32) {
     for graphic in graphics.iter_mut() {
         match graphic {
             Graphic::Rect { x, y, .. } |
             Graphic::Ellipse { x, y, .. } |
             Graphic::Char { x, y, .. } |
             Graphic::ImageFile { x, y, .. } |
-            Graphic::Image { x, y, .. } => {
+            Graphic::Image { x, y, .. } |
+            Graphic::Clickable { x, y, .. } => {
                 *x += x_offset;
                 *y += y_offset;
             },
@@ -111,11 +149,23 @@ pub fn move_rel(graphics: &mut Vec<Graphic>, x_offset: f32, y_offset: f32) {
                 *y2 += y_offset;
                 *y3 += y_offset;
             },
+            Graphic::Line { p1: (x1, y1), p2: (x2, y2), .. } => {
+                *x1 += x_offset;
+                *x2 += x_offset;
+                *y1 += y_offset;
+                *y2 += y_offset;
+            },
+            Graphic::Polyline { points, .. } | Graphic::Polygon { points, .. } => {
+                for (x, y) in points.iter_mut() {
+                    *x += x_offset;
+                    *y += y_offset;
+                }
+            },
         }
     }
 }
 
-pub fn scale(graphics: &mut Vec<Graphic>, scale: f32) {
+pub fn scale(graphics: &mut [Graphic], scale: f32) {
     for graphic in graphics.iter_mut() {
         match graphic {
             Graphic::Rect { x, y, w, h, radius, thickness, .. } => {
@@ -150,12 +200,49 @@ pub fn scale(graphics: &mut Vec<Graphic>, scale: f32) {
                 *y2 *= scale;
                 *y3 *= scale;
             },
+            Graphic::Line { p1: (x1, y1), p2: (x2, y2), thickness, dash, .. } => {
+                *x1 *= scale;
+                *x2 *= scale;
+                *y1 *= scale;
+                *y2 *= scale;
+                *thickness *= scale;
+
+                if let Some((dash, gap)) = dash {
+                    *dash *= scale;
+                    *gap *= scale;
+                }
+            },
+            Graphic::Polyline { points, thickness, dash, .. } => {
+                for (x, y) in points.iter_mut() {
+                    *x *= scale;
+                    *y *= scale;
+                }
+
+                *thickness *= scale;
+
+                if let Some((dash, gap)) = dash {
+                    *dash *= scale;
+                    *gap *= scale;
+                }
+            },
+            Graphic::Polygon { points, thickness, .. } => {
+                for (x, y) in points.iter_mut() {
+                    *x *= scale;
+                    *y *= scale;
+                }
+
+                if let Some(thickness) = thickness {
+                    *thickness *= scale;
+                }
+            },
             Graphic::Char { x, y, size, .. } => {
                 *x *= scale;
                 *y *= scale;
                 *size *= scale;
             },
-            Graphic::ImageFile { x, y, w, h, .. } | Graphic::Image { x, y, w, h, .. } => {
+            Graphic::ImageFile { x, y, w, h, .. } |
+            Graphic::Image { x, y, w, h, .. } |
+            Graphic::Clickable { x, y, w, h, .. } => {
                 *x *= scale;
                 *y *= scale;
                 *w *= scale;
@@ -0,0 +1,113 @@
+use crate::annotation::Annotations;
+use macroquad::color::Color;
+
+/// A label that the user puts on an entry while triaging (e.g. "known issue").
+/// Unlike `EntryFlag`, the user can change it, and it's saved with the annotations
+/// (see `Config::annotations_path`).
+///
+/// The user sets the n-th label of `Config::triage_labels` with Shift+n key.
+#[derive(Clone, Debug)]
+pub struct TriageLabel {
+    pub name: String,
+    pub color: Color,
+}
+
+impl TriageLabel {
+    pub fn new(name: &str, color: Color) -> TriageLabel {
+        TriageLabel { name: name.to_string(), color }
+    }
+}
+
+pub fn default_triage_labels() -> Vec<TriageLabel> {
+    vec![
+        TriageLabel::new("known issue", Color { r: 0.95, g: 0.55, b: 0.2, a: 1.0 }),
+        TriageLabel::new("investigating", Color { r: 0.85, g: 0.4, b: 0.85, a: 1.0 }),
+        TriageLabel::new("fixed upstream", Color { r: 0.3, g: 0.8, b: 0.8, a: 1.0 }),
+        TriageLabel::new("won't fix", Color { r: 0.6, g: 0.6, b: 0.6, a: 1.0 }),
+    ]
+}
+
+/// The color of a label. If the label is not in `labels` (e.g. the config has changed
+/// since it was saved), it's white.
+pub fn label_color(labels: &[TriageLabel], name: &str) -> Color {
+    labels.iter().find(|label| label.name == name).map(|label| label.color).unwrap_or(Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 })
+}
+
+impl Annotations {
+    /// It sets the triage label (`TriageLabel::name`) of `targets` (scope, `Entry::name`),
+    /// or clears it if `label` is `None`. Like `Pins::toggle_all`, if every target already
+    /// has the label, it's cleared. It returns the message for the popup.
+    pub fn toggle_label(&mut self, targets: &[(String, String)], label: Option<String>) -> Result<String, String> {
+        if targets.is_empty() {
+            return Err(String::from("This entry can't be labeled."));
+        }
+
+        let label = if targets.iter().all(
+            |(scope, name)| self.get(scope, name).and_then(|a| a.label.as_ref()) == label.as_ref()
+        ) { None } else { label };
+
+        for (scope, name) in targets.iter() {
+            let mut annotation = self.get(scope, name).cloned().unwrap_or_default();
+            annotation.label = label.clone();
+            self.set(scope, name, annotation)?;
+        }
+
+        Ok(match (targets, &label) {
+            ([(_, name)], Some(label)) => format!("`{name}` is labeled `{label}`."),
+            ([(_, name)], None) => format!("Cleared the label of `{name}`."),
+            (_, Some(label)) => format!("{} entries are labeled `{label}`.", targets.len()),
+            (_, None) => format!("Cleared the labels of {} entries.", targets.len()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotation::Annotation;
+
+    fn targets(names: &[&str]) -> Vec<(String, String)> {
+        names.iter().map(|name| (String::from("s"), name.to_string())).collect()
+    }
+
+    fn label<'a>(annotations: &'a Annotations, name: &str) -> Option<&'a str> {
+        annotations.get("s", name).and_then(|a| a.label.as_deref())
+    }
+
+    #[test]
+    fn toggle_labels() {
+        let mut annotations = Annotations::new(None);
+        let known = Some(String::from("known issue"));
+
+        assert_eq!(annotations.toggle_label(&targets(&["a"]), known.clone()), Ok(String::from("`a` is labeled `known issue`.")));
+        assert_eq!(label(&annotations, "a"), Some("known issue"));
+
+        // Not every target has the label, so it's set to all of them.
+        assert_eq!(annotations.toggle_label(&targets(&["a", "b"]), known.clone()), Ok(String::from("2 entries are labeled `known issue`.")));
+        assert_eq!(label(&annotations, "b"), Some("known issue"));
+
+        assert_eq!(annotations.toggle_label(&targets(&["a", "b"]), known.clone()), Ok(String::from("Cleared the labels of 2 entries.")));
+        assert!(annotations.get("s", "a").is_none());
+
+        assert_eq!(annotations.toggle_label(&targets(&["a"]), None), Ok(String::from("Cleared the label of `a`.")));
+        assert!(annotations.toggle_label(&[], known).is_err());
+    }
+
+    #[test]
+    fn labels_keep_the_notes() {
+        let mut annotations = Annotations::new(None);
+        annotations.set("s", "a", Annotation { note: String::from("n"), ..Annotation::default() }).unwrap();
+        annotations.toggle_label(&targets(&["a"]), Some(String::from("x"))).unwrap();
+        annotations.toggle_label(&targets(&["a"]), Some(String::from("x"))).unwrap();
+
+        assert_eq!(annotations.get("s", "a").map(|a| (a.note.as_str(), a.label.is_none())), Some(("n", true)));
+    }
+
+    #[test]
+    fn colors() {
+        let labels = default_triage_labels();
+
+        assert_eq!(label_color(&labels, "won't fix"), labels[3].color);
+        assert_eq!(label_color(&labels, "gone"), Color { r: 1.0, g: 1.0, b: 1.0, a: 1.0 });
+    }
+}